@@ -0,0 +1,556 @@
+//! Historic test runtimes queried from the evergreen test stats API.
+use std::{collections::HashMap, path::Path, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use reqwest::{header::LINK, Url};
+use serde::Deserialize;
+use tracing::warn;
+
+use super::timing_source::TimingSource;
+use crate::utils::test_path::normalize_test_path;
+
+/// Number of test stats to request in each page.
+const PAGE_SIZE: usize = 1000;
+
+/// A response to an HTTP request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    /// HTTP status code of the response.
+    pub status: u16,
+    /// Body of the response.
+    pub body: String,
+    /// URL of the next page of results, if the response is paginated and there are more pages.
+    pub next_page: Option<String>,
+}
+
+/// A client to make HTTP requests with.
+#[async_trait]
+pub trait HttpClient: Send + Sync {
+    /// Make a GET request to the given URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - URL to request.
+    ///
+    /// # Returns
+    ///
+    /// The response to the request, or an error if no response was received.
+    async fn get(&self, url: &str) -> Result<HttpResponse>;
+}
+
+/// Credentials to authenticate against the evergreen API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvgAuth {
+    /// Evergreen user to authenticate as.
+    pub user: String,
+    /// API key of the evergreen user.
+    pub api_key: String,
+    /// Base URL of the evergreen API server.
+    pub api_server_host: String,
+}
+
+impl EvgAuth {
+    /// Read evergreen credentials from the given yaml file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the evergreen auth file, e.g. `~/.evergreen.yml`.
+    ///
+    /// # Returns
+    ///
+    /// Credentials read from the file.
+    pub fn from_yaml_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read evergreen auth from '{}'", path.display()))?;
+        serde_yaml::from_str(&contents)
+            .with_context(|| format!("Could not parse evergreen auth in '{}'", path.display()))
+    }
+}
+
+/// An HTTP client making authenticated requests to the evergreen API.
+pub struct EvgHttpClient {
+    /// Client to make requests with.
+    client: reqwest::Client,
+    /// Credentials to authenticate with.
+    auth: EvgAuth,
+}
+
+impl EvgHttpClient {
+    /// Create a new client authenticating with the given credentials.
+    ///
+    /// # Arguments
+    ///
+    /// * `auth` - Credentials to authenticate with.
+    ///
+    /// # Returns
+    ///
+    /// New evergreen HTTP client.
+    pub fn new(auth: EvgAuth) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            auth,
+        }
+    }
+}
+
+#[async_trait]
+impl HttpClient for EvgHttpClient {
+    async fn get(&self, url: &str) -> Result<HttpResponse> {
+        let response = self
+            .client
+            .get(url)
+            .header("Api-User", &self.auth.user)
+            .header("Api-Key", &self.auth.api_key)
+            .send()
+            .await?;
+        let status = response.status().as_u16();
+        let next_page = response
+            .headers()
+            .get(LINK)
+            .and_then(|link| link.to_str().ok())
+            .and_then(parse_next_link);
+        let body = response.text().await?;
+        Ok(HttpResponse {
+            status,
+            body,
+            next_page,
+        })
+    }
+}
+
+/// Get the URL of the next page from the value of a `Link` header.
+///
+/// # Arguments
+///
+/// * `link` - Value of the `Link` header, e.g. `<https://evergreen/...>; rel="next"`.
+///
+/// # Returns
+///
+/// URL of the link with a `next` relation, if there is one.
+fn parse_next_link(link: &str) -> Option<String> {
+    link.split(',').find_map(|entry| {
+        let mut parts = entry.split(';');
+        let url = parts.next()?.trim();
+        let is_next = parts.any(|param| {
+            let param = param.trim().replace(' ', "");
+            param == "rel=\"next\"" || param == "rel=next"
+        });
+        if is_next {
+            Some(
+                url.trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string(),
+            )
+        } else {
+            None
+        }
+    })
+}
+
+/// How failed requests should be retried.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Number of times to retry a failed request before giving up.
+    pub max_retries: u32,
+    /// Time to wait before the first retry, doubled after each retry.
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+/// The test stats to query from evergreen.
+#[derive(Debug, Clone)]
+pub struct TestStatsQuery {
+    /// Evergreen project to query.
+    pub project: String,
+    /// Build variant to query.
+    pub variant: String,
+    /// Only query stats for this task, if given.
+    pub task: Option<String>,
+    /// First day to include stats from.
+    pub after_date: NaiveDate,
+    /// Last day to include stats from.
+    pub before_date: NaiveDate,
+}
+
+impl TestStatsQuery {
+    /// Build the URL of the first page of results for this query.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_server_host` - Base URL of the evergreen API server.
+    ///
+    /// # Returns
+    ///
+    /// URL to query the test stats API with.
+    fn url(&self, api_server_host: &str) -> Result<String> {
+        let mut params = vec![
+            ("variants", self.variant.clone()),
+            ("after_date", self.after_date.format("%Y-%m-%d").to_string()),
+            (
+                "before_date",
+                self.before_date.format("%Y-%m-%d").to_string(),
+            ),
+            ("limit", PAGE_SIZE.to_string()),
+        ];
+        if let Some(task) = &self.task {
+            params.push(("tasks", task.clone()));
+        }
+        let url = Url::parse_with_params(
+            &format!(
+                "{}/rest/v2/projects/{}/test_stats",
+                api_server_host.trim_end_matches('/'),
+                self.project
+            ),
+            &params,
+        )?;
+        Ok(url.to_string())
+    }
+}
+
+/// Stats of a test for a single day as reported by the evergreen test stats API.
+#[derive(Debug, Clone, Deserialize)]
+struct EvgTestStats {
+    /// Path of the test file.
+    test_file: String,
+    /// Number of passing runs of the test.
+    num_pass: u64,
+    /// Average duration (in seconds) of passing runs of the test.
+    avg_duration_pass: f64,
+}
+
+/// Test runtimes queried from the evergreen test stats API.
+#[derive(Debug, Clone, Default)]
+pub struct EvergreenTimingSource {
+    /// Map of normalized test paths to runtimes.
+    timings: HashMap<String, f64>,
+}
+
+impl EvergreenTimingSource {
+    /// Query the average runtimes of tests from the evergreen test stats API.
+    ///
+    /// All pages of results are fetched. Requests that fail or return a server error are
+    /// retried according to the given retry policy, while client errors fail immediately.
+    /// Tests reported on several days get the average runtime of all their passing runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Client to make requests with.
+    /// * `api_server_host` - Base URL of the evergreen API server.
+    /// * `query` - Test stats to query.
+    /// * `retry_policy` - How to retry failed requests.
+    ///
+    /// # Returns
+    ///
+    /// Timing source with the runtimes of all tests that passed in the queried time range.
+    pub async fn fetch(
+        client: &dyn HttpClient,
+        api_server_host: &str,
+        query: &TestStatsQuery,
+        retry_policy: RetryPolicy,
+    ) -> Result<Self> {
+        // Map of tests to their total passing runtime and number of passing runs.
+        let mut totals: HashMap<String, (f64, u64)> = HashMap::new();
+        let mut next_page = Some(query.url(api_server_host)?);
+        while let Some(url) = next_page {
+            let response = get_with_retries(client, &url, retry_policy).await?;
+            let stats: Vec<EvgTestStats> = serde_json::from_str(&response.body)
+                .with_context(|| format!("Could not parse test stats from '{}'", url))?;
+            for stat in stats {
+                let total = totals
+                    .entry(normalize_test_path(&stat.test_file))
+                    .or_insert((0.0, 0));
+                total.0 += stat.avg_duration_pass * stat.num_pass as f64;
+                total.1 += stat.num_pass;
+            }
+            next_page = response.next_page;
+        }
+
+        Ok(Self {
+            timings: totals
+                .into_iter()
+                .filter(|(_, (_, num_pass))| *num_pass > 0)
+                .map(|(test, (runtime, num_pass))| (test, runtime / num_pass as f64))
+                .collect(),
+        })
+    }
+
+    /// Number of tests with a runtime in this source.
+    pub fn len(&self) -> usize {
+        self.timings.len()
+    }
+
+    /// True if this source has no runtimes.
+    pub fn is_empty(&self) -> bool {
+        self.timings.is_empty()
+    }
+}
+
+impl TimingSource for EvergreenTimingSource {
+    fn test_runtime(&self, test_file: &str) -> Option<f64> {
+        self.timings.get(&normalize_test_path(test_file)).copied()
+    }
+}
+
+/// Make a GET request, retrying on failures and server errors.
+///
+/// # Arguments
+///
+/// * `client` - Client to make the request with.
+/// * `url` - URL to request.
+/// * `retry_policy` - How to retry failed requests.
+///
+/// # Returns
+///
+/// The successful response, or an error if the request did not succeed.
+async fn get_with_retries(
+    client: &dyn HttpClient,
+    url: &str,
+    retry_policy: RetryPolicy,
+) -> Result<HttpResponse> {
+    let mut backoff = retry_policy.initial_backoff;
+    let mut attempt = 0;
+    loop {
+        let error = match client.get(url).await {
+            Ok(response) if (200..300).contains(&response.status) => return Ok(response),
+            Ok(response) if response.status < 500 && response.status != 429 => {
+                bail!(
+                    "Request to '{}' failed with status {}: {}",
+                    url,
+                    response.status,
+                    response.body
+                );
+            }
+            Ok(response) => format!("status {}: {}", response.status, response.body),
+            Err(err) => err.to_string(),
+        };
+
+        if attempt >= retry_policy.max_retries {
+            bail!(
+                "Request to '{}' failed after {} attempts, last error: {}",
+                url,
+                attempt + 1,
+                error
+            );
+        }
+        warn!(
+            url = url,
+            error = error.as_str(),
+            attempt = attempt + 1,
+            "Request to evergreen failed, retrying",
+        );
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, sync::Mutex};
+
+    use super::*;
+
+    const API_SERVER: &str = "https://evergreen.example.com";
+
+    /// An HTTP client returning scripted responses for each URL.
+    struct MockHttpClient {
+        responses: Mutex<HashMap<String, VecDeque<Result<HttpResponse>>>>,
+        requests: Mutex<Vec<String>>,
+    }
+
+    impl MockHttpClient {
+        fn new(responses: Vec<(String, Result<HttpResponse>)>) -> Self {
+            let mut map: HashMap<String, VecDeque<Result<HttpResponse>>> = HashMap::new();
+            for (url, response) in responses {
+                map.entry(url).or_default().push_back(response);
+            }
+            Self {
+                responses: Mutex::new(map),
+                requests: Mutex::new(vec![]),
+            }
+        }
+
+        fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl HttpClient for MockHttpClient {
+        async fn get(&self, url: &str) -> Result<HttpResponse> {
+            self.requests.lock().unwrap().push(url.to_string());
+            self.responses
+                .lock()
+                .unwrap()
+                .get_mut(url)
+                .and_then(|responses| responses.pop_front())
+                .unwrap_or_else(|| bail!("Unexpected request to {}", url))
+        }
+    }
+
+    fn build_query() -> TestStatsQuery {
+        TestStatsQuery {
+            project: "mongodb-mongo-master".to_string(),
+            variant: "enterprise-rhel-80-64-bit".to_string(),
+            task: None,
+            after_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            before_date: NaiveDate::from_ymd_opt(2024, 1, 14).unwrap(),
+        }
+    }
+
+    fn first_page_url() -> String {
+        build_query().url(API_SERVER).unwrap()
+    }
+
+    fn ok_response(body: &str, next_page: Option<&str>) -> Result<HttpResponse> {
+        Ok(HttpResponse {
+            status: 200,
+            body: body.to_string(),
+            next_page: next_page.map(|url| url.to_string()),
+        })
+    }
+
+    fn error_response(status: u16) -> Result<HttpResponse> {
+        Ok(HttpResponse {
+            status,
+            body: "error".to_string(),
+            next_page: None,
+        })
+    }
+
+    fn no_backoff(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            initial_backoff: Duration::ZERO,
+        }
+    }
+
+    // url tests
+    #[test]
+    fn test_url_should_include_query_parameters() {
+        let mut query = build_query();
+        query.task = Some("jsCore".to_string());
+
+        let url = query.url("https://evergreen.example.com/").unwrap();
+
+        assert_eq!(
+            url,
+            "https://evergreen.example.com/rest/v2/projects/mongodb-mongo-master/test_stats?\
+             variants=enterprise-rhel-80-64-bit&after_date=2024-01-01&before_date=2024-01-14&\
+             limit=1000&tasks=jsCore"
+        );
+    }
+
+    // parse_next_link tests
+    #[test]
+    fn test_parse_next_link_should_find_next_relation() {
+        let link = r#"<https://evergreen.example.com/page2>; rel="next", <https://evergreen.example.com/page0>; rel="prev""#;
+
+        assert_eq!(
+            parse_next_link(link),
+            Some("https://evergreen.example.com/page2".to_string())
+        );
+        assert_eq!(
+            parse_next_link(r#"<https://evergreen.example.com/page0>; rel="prev""#),
+            None
+        );
+    }
+
+    // fetch tests
+    #[tokio::test]
+    async fn test_fetch_should_follow_pagination() {
+        let page_2 = "https://evergreen.example.com/page2";
+        let client = MockHttpClient::new(vec![
+            (
+                first_page_url(),
+                ok_response(
+                    r#"[
+                        {"test_file": "jstests/core/test_0.js", "num_pass": 1, "avg_duration_pass": 10.0},
+                        {"test_file": "jstests/core/test_1.js", "num_pass": 2, "avg_duration_pass": 5.0}
+                    ]"#,
+                    Some(page_2),
+                ),
+            ),
+            (
+                page_2.to_string(),
+                ok_response(
+                    r#"[
+                        {"test_file": "jstests/core/test_0.js", "num_pass": 3, "avg_duration_pass": 2.0},
+                        {"test_file": "jstests\\core\\test_2.js", "num_pass": 0, "avg_duration_pass": 0.0}
+                    ]"#,
+                    None,
+                ),
+            ),
+        ]);
+
+        let timings =
+            EvergreenTimingSource::fetch(&client, API_SERVER, &build_query(), no_backoff(0))
+                .await
+                .unwrap();
+
+        assert_eq!(client.requests().len(), 2);
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings.test_runtime("jstests/core/test_0.js"), Some(4.0));
+        assert_eq!(timings.test_runtime("jstests/core/test_1.js"), Some(5.0));
+        assert_eq!(timings.test_runtime("jstests/core/test_2.js"), None);
+        assert_eq!(timings.test_runtime("jstests/core/test_3.js"), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_should_retry_server_errors() {
+        let client = MockHttpClient::new(vec![
+            (first_page_url(), error_response(503)),
+            (first_page_url(), Err(anyhow::anyhow!("connection reset"))),
+            (
+                first_page_url(),
+                ok_response(
+                    r#"[{"test_file": "jstests/core/test_0.js", "num_pass": 1, "avg_duration_pass": 10.0}]"#,
+                    None,
+                ),
+            ),
+        ]);
+
+        let timings =
+            EvergreenTimingSource::fetch(&client, API_SERVER, &build_query(), no_backoff(2))
+                .await
+                .unwrap();
+
+        assert_eq!(client.requests().len(), 3);
+        assert_eq!(timings.test_runtime("jstests/core/test_0.js"), Some(10.0));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_should_fail_when_retries_run_out() {
+        let client = MockHttpClient::new(vec![
+            (first_page_url(), error_response(500)),
+            (first_page_url(), error_response(500)),
+        ]);
+
+        let result =
+            EvergreenTimingSource::fetch(&client, API_SERVER, &build_query(), no_backoff(1)).await;
+
+        assert!(result.unwrap_err().to_string().contains("after 2 attempts"));
+        assert_eq!(client.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_should_not_retry_client_errors() {
+        let client = MockHttpClient::new(vec![(first_page_url(), error_response(401))]);
+
+        let result =
+            EvergreenTimingSource::fetch(&client, API_SERVER, &build_query(), no_backoff(3)).await;
+
+        assert!(result.unwrap_err().to_string().contains("status 401"));
+        assert_eq!(client.requests().len(), 1);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod evg_config;
 pub mod evg_config_utils;
 pub mod evg_task_history;
+pub mod evg_test_stats;
+pub mod timing_source;
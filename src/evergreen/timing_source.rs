@@ -0,0 +1,151 @@
+//! Sources of historic test runtimes.
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{Context, Result};
+
+use crate::utils::test_path::normalize_test_path;
+
+/// A source of historic test runtimes.
+pub trait TimingSource: Send + Sync {
+    /// Get the historic runtime of the given test.
+    ///
+    /// # Arguments
+    ///
+    /// * `test_file` - Path to the test file.
+    ///
+    /// # Returns
+    ///
+    /// Runtime (in seconds) of the test, or `None` if the source has no data for it.
+    fn test_runtime(&self, test_file: &str) -> Option<f64>;
+}
+
+/// Test runtimes read from a local JSON file mapping test paths to runtimes in seconds.
+#[derive(Debug, Clone, Default)]
+pub struct JsonFileTimingSource {
+    /// Map of normalized test paths to runtimes.
+    timings: HashMap<String, f64>,
+}
+
+impl JsonFileTimingSource {
+    /// Read test runtimes from the given JSON file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the JSON file to read.
+    ///
+    /// # Returns
+    ///
+    /// Timing source with the runtimes in the file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read test timings from '{}'", path.display()))?;
+        Self::from_json(&contents)
+            .with_context(|| format!("Could not parse test timings in '{}'", path.display()))
+    }
+
+    /// Read test runtimes from the given JSON contents.
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - JSON object mapping test paths to runtimes in seconds.
+    ///
+    /// # Returns
+    ///
+    /// Timing source with the given runtimes.
+    pub fn from_json(contents: &str) -> Result<Self> {
+        let timings: HashMap<String, f64> = serde_json::from_str(contents)?;
+        Ok(Self {
+            timings: timings
+                .into_iter()
+                .map(|(test, runtime)| (normalize_test_path(&test), runtime))
+                .collect(),
+        })
+    }
+
+    /// Number of tests with a runtime in this source.
+    pub fn len(&self) -> usize {
+        self.timings.len()
+    }
+
+    /// True if this source has no runtimes.
+    pub fn is_empty(&self) -> bool {
+        self.timings.is_empty()
+    }
+}
+
+impl TimingSource for JsonFileTimingSource {
+    fn test_runtime(&self, test_file: &str) -> Option<f64> {
+        self.timings.get(&normalize_test_path(test_file)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    // from_json tests
+    #[test]
+    fn test_from_json_should_load_runtimes() {
+        let contents = r#"{
+            "jstests/core/test_0.js": 12.5,
+            "jstests/core/test_1.js": 3
+        }"#;
+
+        let timings = JsonFileTimingSource::from_json(contents).unwrap();
+
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings.test_runtime("jstests/core/test_0.js"), Some(12.5));
+        assert_eq!(timings.test_runtime("jstests/core/test_1.js"), Some(3.0));
+    }
+
+    #[test]
+    fn test_from_json_should_fail_on_malformed_json() {
+        assert!(JsonFileTimingSource::from_json(r#"{"jstests/core/test_0.js": 12.5"#).is_err());
+        assert!(JsonFileTimingSource::from_json(r#"{"jstests/core/test_0.js": "slow"}"#).is_err());
+        assert!(JsonFileTimingSource::from_json(r#"["jstests/core/test_0.js"]"#).is_err());
+    }
+
+    // test_runtime tests
+    #[test]
+    fn test_test_runtime_should_be_none_for_missing_tests() {
+        let timings =
+            JsonFileTimingSource::from_json(r#"{"jstests/core/test_0.js": 12.5}"#).unwrap();
+
+        assert_eq!(timings.test_runtime("jstests/core/test_1.js"), None);
+        assert_eq!(timings.test_runtime("test_0.js"), None);
+    }
+
+    #[test]
+    fn test_test_runtime_should_match_normalized_paths() {
+        let timings =
+            JsonFileTimingSource::from_json(r#"{"./jstests\\core\\test_0.js": 12.5}"#).unwrap();
+
+        assert_eq!(timings.test_runtime("jstests/core/test_0.js"), Some(12.5));
+        assert_eq!(timings.test_runtime("./jstests/core/test_0.js"), Some(12.5));
+    }
+
+    // from_file tests
+    #[test]
+    fn test_from_file_should_load_runtimes() {
+        let tmp_dir = TempDir::new("timing_source").unwrap();
+        let path = tmp_dir.path().join("timings.json");
+        std::fs::write(&path, r#"{"jstests/core/test_0.js": 12.5}"#).unwrap();
+
+        let timings = JsonFileTimingSource::from_file(&path).unwrap();
+
+        assert_eq!(timings.test_runtime("jstests/core/test_0.js"), Some(12.5));
+    }
+
+    #[test]
+    fn test_from_file_should_fail_on_malformed_json() {
+        let tmp_dir = TempDir::new("timing_source").unwrap();
+        let path = tmp_dir.path().join("timings.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        let err = JsonFileTimingSource::from_file(&path).unwrap_err();
+
+        assert!(err.to_string().contains("timings.json"));
+    }
+}
@@ -73,6 +73,10 @@ pub const SUITE_NAME: &str = "suite";
 pub const GEN_TASK_CONFIG_LOCATION: &str = "gen_task_config_location";
 /// Maximum amount of resmoke jobs to execute in parallel.
 pub const RESMOKE_JOBS_MAX: &str = "resmoke_jobs_max";
+/// Number of tests run by a generated sub-task.
+pub const SUB_SUITE_TEST_COUNT: &str = "sub_suite_test_count";
+/// Estimated runtime (in seconds) of the tests in a generated sub-task.
+pub const SUB_SUITE_ESTIMATED_RUNTIME: &str = "sub_suite_estimated_runtime_secs";
 /// Number of times to repeat a given resmoke suite.
 pub const REPEAT_SUITES: &str = "resmoke_repeat_suites";
 /// Variant used for compile.
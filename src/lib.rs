@@ -44,7 +44,7 @@ use task_types::{
     fuzzer_tasks::{GenFuzzerService, GenFuzzerServiceImpl},
     generated_suite::GeneratedSuite,
     multiversion::MultiversionServiceImpl,
-    resmoke_config_writer::{ResmokeConfigActor, ResmokeConfigActorService},
+    resmoke_config_writer::{DryRunConfigActor, ResmokeConfigActor, ResmokeConfigActorService},
     resmoke_tasks::{GenResmokeConfig, GenResmokeTaskService, GenResmokeTaskServiceImpl},
 };
 use tokio::{runtime::Handle, task::JoinHandle, time};
@@ -59,6 +59,33 @@ mod services;
 mod task_types;
 mod utils;
 
+pub use evergreen::evg_test_stats::{
+    EvergreenTimingSource, EvgAuth, EvgHttpClient, HttpClient, HttpResponse, RetryPolicy,
+    TestStatsQuery,
+};
+pub use evergreen::timing_source::{JsonFileTimingSource, TimingSource};
+pub use resmoke::resmoke_suite::{
+    load_suites, load_suites_async, partition_by_tag, partition_by_tags, suite_test_diff,
+    ArchiveConfig, ArchiveSelection, BurnInSuite, EffectiveSelection, FixtureConfig, FixturePolicy,
+    MatrixSuiteConfig, ResmokeExecutor, ResmokeSelector, ResmokeSelectorBuilder,
+    ResmokeSuiteConfig, RootTests, SelectorOp, SpilledRoots, SuiteDiff, SuiteError, SuiteLint,
+    SuiteValidationError, TagExpression, TagFilter, TagPartition, TaggedTest, TestRoot,
+    DEFAULT_MAX_ANY_TAGS,
+};
+#[cfg(feature = "test-util")]
+pub use resmoke::test_util;
+pub use task_types::resmoke_config_writer::{
+    GenerationManifest, GenerationStats, ManifestSubSuite, ManifestSuite, SubSuitePlan, SuitePlan,
+    WriteConfigOptions, MANIFEST_SCHEMA_VERSION,
+};
+pub use task_types::resmoke_tasks::{
+    clamp_sub_suite_counts, distribute_tests_by_count, distribute_tests_by_count_seeded,
+    distribute_tests_by_max_runtime, distribute_tests_by_runtime, distribute_with_affinity,
+    find_fully_excluded_sub_suites, get_num_sub_suites, sum_runtime, SubSuite, TaskDecorator,
+};
+pub use utils::fs_service::{FsService, InMemoryFsService};
+pub use utils::task_name::SuiteFileNaming;
+
 const BURN_IN_TESTS_PREFIX: &str = "burn_in_tests";
 const BURN_IN_TASKS_PREFIX: &str = "burn_in_tasks";
 const BURN_IN_BV_SUFFIX: &str = "generated-by-burn-in-tags";
@@ -131,6 +158,9 @@ pub struct ExecutionConfiguration<'a> {
     pub evg_auth_file: &'a Path,
     /// Should task splitting use the fallback method by default.
     pub use_task_split_fallback: bool,
+    /// Maximum number of sub-tasks a generated task can be split into across all of its
+    /// multiversion combinations.
+    pub max_total_subtasks: Option<usize>,
     /// Command to execute resmoke.
     pub resmoke_command: &'a str,
     /// Directory to place generated configuration files.
@@ -150,6 +180,21 @@ pub struct ExecutionConfiguration<'a> {
     /// S3 bucket to get test stats from.
     pub s3_test_stats_bucket: &'a str,
     pub subtask_limits: SubtaskLimits,
+    /// Record how tasks would be split instead of writing resmoke configuration files.
+    pub dry_run: bool,
+    /// Fail before writing any configuration if any resmoke suite fails to parse.
+    pub fail_on_parse_error: bool,
+    /// Options controlling how resmoke configuration files are written.
+    pub write_config_options: WriteConfigOptions,
+    /// Only generate tasks running these base suites, generate all tasks if empty.
+    pub suites: Vec<String>,
+    /// File to write a JSON manifest of the generated suites to.
+    pub manifest_file: Option<&'a Path>,
+    /// JSON file mapping test paths to runtimes in seconds, used when it exists in place of
+    /// querying test stats for the tests it contains.
+    pub test_timings_file: Option<&'a Path>,
+    /// Hook to customize every generated resmoke and fuzzer sub-task, including burn_in sub-tasks.
+    pub task_decorator: Option<Arc<dyn TaskDecorator>>,
 }
 
 #[derive(Debug, Clone)]
@@ -167,6 +212,18 @@ pub struct SubtaskLimits {
 
     // Maximum number of subtasks that can be generated for tasks
     pub max_subtasks_per_task: usize,
+
+    // Maximum number of tests a generated subtask should contain. Takes precedence over the
+    // number of subtasks requested and the limits above when given.
+    pub max_tests_per_suite: Option<usize>,
+
+    // Seed to shuffle tests with when splitting tasks by test count. When given, the same seed
+    // always produces the same split.
+    pub distribution_seed: Option<u64>,
+
+    // Order the sub-suites of tasks split by runtime longest first, so the longest sub-suites
+    // are scheduled first. The order of tests within each sub-suite is not changed.
+    pub sort_sub_suites_by_runtime: bool,
 }
 
 /// Collection of services needed to execution.
@@ -176,6 +233,10 @@ pub struct Dependencies {
     gen_task_service: Arc<dyn GenerateTasksService>,
     resmoke_config_actor: Arc<tokio::sync::Mutex<dyn ResmokeConfigActor>>,
     burn_in_service: Arc<dyn BurnInService>,
+    dry_run_actor: Option<Arc<tokio::sync::Mutex<DryRunConfigActor>>>,
+    fs_service: Arc<dyn FsService>,
+    fail_on_parse_error: bool,
+    manifest_file: Option<PathBuf>,
 }
 
 impl Dependencies {
@@ -192,7 +253,26 @@ impl Dependencies {
         execution_config: ExecutionConfiguration,
         s3_client: aws_sdk_s3::Client,
     ) -> Result<Self> {
-        let fs_service = Arc::new(FsServiceImpl::new());
+        Self::new_with_fs_service(execution_config, s3_client, Arc::new(FsServiceImpl::new()))
+    }
+
+    /// Create a new set of dependency instances that write files with the given service.
+    ///
+    /// # Arguments
+    ///
+    /// * `execution_config` - Information about how generation to take place.
+    /// * `s3_client` - Client to query test stats with.
+    /// * `fs_service` - Service to write all generated files with, for example an
+    ///   `InMemoryFsService` to capture them without touching the filesystem.
+    ///
+    /// # Returns
+    ///
+    /// A set of dependencies to run against.
+    pub fn new_with_fs_service(
+        execution_config: ExecutionConfiguration,
+        s3_client: aws_sdk_s3::Client,
+        fs_service: Arc<dyn FsService>,
+    ) -> Result<Self> {
         let discovery_service = Arc::new(ResmokeProxy::new(
             execution_config.resmoke_command,
             execution_config.skip_covered_tests,
@@ -203,7 +283,9 @@ impl Dependencies {
         )?);
         let evg_config_service = Arc::new(execution_config.project_info.get_project_config()?);
         let evg_config_utils = Arc::new(EvgConfigUtilsImpl::new());
-        let gen_fuzzer_service = Arc::new(GenFuzzerServiceImpl::new());
+        let gen_fuzzer_service = Arc::new(GenFuzzerServiceImpl::new(
+            execution_config.task_decorator.clone(),
+        ));
         let gen_sub_tasks_config = execution_config
             .project_info
             .get_generate_sub_tasks_config()?;
@@ -219,27 +301,51 @@ impl Dependencies {
             execution_config.s3_test_stats_bucket.to_string(),
             execution_config.project_info.evg_project.clone(),
         ));
-        let resmoke_config_actor =
-            Arc::new(tokio::sync::Mutex::new(ResmokeConfigActorService::new(
-                discovery_service.clone(),
-                fs_service.clone(),
-                execution_config
-                    .target_directory
-                    .to_str()
-                    .expect("Unexpected target directory"),
-                32,
-            )));
+        let timing_source: Option<Arc<dyn TimingSource>> = match execution_config.test_timings_file
+        {
+            Some(test_timings_file) if test_timings_file.exists() => {
+                let timing_source = JsonFileTimingSource::from_file(test_timings_file)?;
+                event!(
+                    Level::INFO,
+                    "Using {} test timings from {}",
+                    timing_source.len(),
+                    test_timings_file.display()
+                );
+                Some(Arc::new(timing_source))
+            }
+            _ => None,
+        };
+        let (resmoke_config_actor, dry_run_actor) = build_resmoke_config_actor(
+            execution_config.dry_run,
+            discovery_service.clone(),
+            fs_service.clone(),
+            execution_config
+                .target_directory
+                .to_str()
+                .expect("Unexpected target directory"),
+            WriteConfigOptions {
+                record_manifest: execution_config.manifest_file.is_some(),
+                defer_writes: execution_config.fail_on_parse_error,
+                ..execution_config.write_config_options.clone()
+            },
+        );
         let enterprise_dir = evg_config_service.get_module_dir(ENTERPRISE_MODULE);
-        let gen_resmoke_config =
-            GenResmokeConfig::new(execution_config.use_task_split_fallback, enterprise_dir);
+        let gen_resmoke_config = GenResmokeConfig::new(
+            execution_config.use_task_split_fallback,
+            enterprise_dir,
+            execution_config.write_config_options.suite_file_naming,
+            execution_config.max_total_subtasks,
+        );
         let gen_resmoke_task_service = Arc::new(GenResmokeTaskServiceImpl::new(
             task_history_service,
+            timing_source,
             discovery_service,
             resmoke_config_actor.clone(),
             multiversion_service,
-            fs_service,
+            fs_service.clone(),
             gen_resmoke_config,
             execution_config.subtask_limits,
+            execution_config.task_decorator.clone(),
         ));
         let gen_task_service = Arc::new(GenerateTasksServiceImpl::new(
             evg_config_service,
@@ -248,6 +354,7 @@ impl Dependencies {
             gen_resmoke_task_service.clone(),
             config_extraction_service.clone(),
             execution_config.gen_burn_in,
+            &execution_config.suites,
         ));
 
         let burn_in_discovery = Arc::new(BurnInProxy::new(
@@ -266,6 +373,10 @@ impl Dependencies {
             gen_task_service,
             resmoke_config_actor,
             burn_in_service,
+            dry_run_actor,
+            fs_service,
+            fail_on_parse_error: execution_config.fail_on_parse_error,
+            manifest_file: execution_config.manifest_file.map(|p| p.to_path_buf()),
         })
     }
 }
@@ -289,6 +400,49 @@ impl GeneratedConfig {
     }
 }
 
+/// Actor that writes resmoke configuration files, and the dry-run actor if it is planning only.
+type ResmokeConfigActors = (
+    Arc<tokio::sync::Mutex<dyn ResmokeConfigActor>>,
+    Option<Arc<tokio::sync::Mutex<DryRunConfigActor>>>,
+);
+
+/// Create the actor that resmoke configurations for generated tasks are sent to.
+///
+/// # Arguments
+///
+/// * `dry_run` - Only record how tasks would be split instead of writing any files.
+/// * `discovery_service` - Service to query resmoke suite configurations.
+/// * `fs_service` - Service to write generated suite files with.
+/// * `target_dir` - Directory to write generated suite files to.
+/// * `write_config_options` - Options controlling how suite files are written.
+///
+/// # Returns
+///
+/// The actor to send resmoke configurations to, and the same actor as a `DryRunConfigActor`
+/// if `dry_run` is set.
+fn build_resmoke_config_actor(
+    dry_run: bool,
+    discovery_service: Arc<dyn TestDiscovery>,
+    fs_service: Arc<dyn FsService>,
+    target_dir: &str,
+    write_config_options: WriteConfigOptions,
+) -> ResmokeConfigActors {
+    if dry_run {
+        let dry_run_actor = Arc::new(tokio::sync::Mutex::new(DryRunConfigActor::new()));
+        (dry_run_actor.clone(), Some(dry_run_actor))
+    } else {
+        let resmoke_config_actor =
+            Arc::new(tokio::sync::Mutex::new(ResmokeConfigActorService::new(
+                discovery_service,
+                fs_service,
+                target_dir,
+                32,
+                write_config_options,
+            )));
+        (resmoke_config_actor, None)
+    }
+}
+
 /// Create 'generate.tasks' configuration for all generated tasks in the provided evergreen
 /// project configuration.
 ///
@@ -296,14 +450,43 @@ impl GeneratedConfig {
 ///
 /// * `deps` - Dependencies needed to perform generation.
 /// * `target_directory` - Directory to store generated configuration.
-pub async fn generate_configuration(deps: &Dependencies, target_directory: &Path) -> Result<()> {
+///
+/// # Returns
+///
+/// Statistics about the suites handled during generation.
+pub async fn generate_configuration(
+    deps: &Dependencies,
+    target_directory: &Path,
+) -> Result<GenerationStats> {
     let generate_tasks_service = deps.gen_task_service.clone();
-    std::fs::create_dir_all(target_directory)?;
+    deps.fs_service.create_dir_all(target_directory)?;
 
     // We are going to do 2 passes through the project build variants. In this first pass, we
     // are actually going to create all the generated tasks that we discover.
     let generated_tasks = generate_tasks_service.build_generated_tasks(deps).await?;
 
+    // In strict mode, the resmoke configuration files are held back until every suite has been
+    // handled, so stop before any of them are written if a suite could not be parsed or any
+    // other error was encountered building the files.
+    if deps.fail_on_parse_error {
+        let mut resmoke_config_actor = deps.resmoke_config_actor.lock().await;
+        let failures = resmoke_config_actor.flush().await?;
+        let failed_suites = resmoke_config_actor.stats().await?.failed_suites;
+        if !failed_suites.is_empty() {
+            bail!(format!(
+                "Failed to parse resmoke suites: {:?}",
+                failed_suites
+            ));
+        }
+        if !failures.is_empty() {
+            bail!(format!(
+                "Encountered errors building resmoke configuration files: {:?}",
+                failures
+            ));
+        }
+        resmoke_config_actor.commit().await?;
+    }
+
     // Now that we have generated all the tasks we want to make another pass through all the
     // build variants and add references to the generated tasks that each build variant includes.
     let generated_build_variants =
@@ -326,7 +509,10 @@ pub async fn generate_configuration(deps: &Dependencies, target_directory: &Path
 
     let mut config_file = target_directory.to_path_buf();
     config_file.push("evergreen_config.json");
-    std::fs::write(config_file, serde_json::to_string_pretty(&gen_evg_project)?)?;
+    deps.fs_service.write_file(
+        &config_file,
+        &serde_json::to_string_pretty(&gen_evg_project)?,
+    )?;
     let mut resmoke_config_actor = deps.resmoke_config_actor.lock().await;
     let failures = resmoke_config_actor.flush().await?;
     if !failures.is_empty() {
@@ -335,7 +521,47 @@ pub async fn generate_configuration(deps: &Dependencies, target_directory: &Path
             failures
         ));
     }
-    Ok(())
+
+    if let Some(manifest_file) = &deps.manifest_file {
+        let manifest = resmoke_config_actor.manifest().await?;
+        deps.fs_service
+            .write_file(manifest_file, &serde_json::to_string_pretty(&manifest)?)?;
+    }
+
+    let stats = resmoke_config_actor.stats().await?;
+    event!(
+        Level::INFO,
+        suites_parsed = stats.suites_parsed,
+        suites_failed = stats.suites_failed,
+        sub_suites = stats.sub_suites,
+        tests_distributed = stats.tests_distributed,
+        avg_tests_per_sub_suite = stats.avg_tests_per_sub_suite(),
+        "Generation stats",
+    );
+    Ok(stats)
+}
+
+/// Determine how all generated tasks in the provided evergreen project configuration would be
+/// split, without writing any configuration files.
+///
+/// # Arguments
+///
+/// * `deps` - Dependencies needed to perform generation, created with `dry_run` enabled.
+///
+/// # Returns
+///
+/// Plans describing how each generated resmoke task would be split, sorted by task name.
+pub async fn plan_configuration(deps: &Dependencies) -> Result<Vec<SuitePlan>> {
+    let dry_run_actor = match &deps.dry_run_actor {
+        Some(dry_run_actor) => dry_run_actor.clone(),
+        None => bail!("Planning configuration requires dependencies created with dry_run enabled"),
+    };
+
+    deps.gen_task_service.build_generated_tasks(deps).await?;
+
+    let mut plans = dry_run_actor.lock().await.plans().to_vec();
+    plans.sort_by(|a, b| a.task_name.cmp(&b.task_name));
+    Ok(plans)
 }
 
 /// A service for generating tasks.
@@ -413,6 +639,7 @@ struct GenerateTasksServiceImpl {
     gen_resmoke_service: Arc<dyn GenResmokeTaskService>,
     config_extraction_service: Arc<dyn ConfigExtractionService>,
     gen_burn_in: bool,
+    suite_filter: HashSet<String>,
 }
 
 impl GenerateTasksServiceImpl {
@@ -425,6 +652,8 @@ impl GenerateTasksServiceImpl {
     /// * `gen_fuzzer_service` - Service to generate fuzzer tasks.
     /// * `gen_resmoke_service` - Service for generating resmoke tasks.
     /// * `config_extraction_service` - Service to extraction configuration from evergreen config.
+    /// * `gen_burn_in` - Should burn_in tasks be generated.
+    /// * `suites` - Only generate tasks running these base suites, generate all tasks if empty.
     pub fn new(
         evg_config_service: Arc<dyn EvgConfigService>,
         evg_config_utils: Arc<dyn EvgConfigUtils>,
@@ -432,6 +661,7 @@ impl GenerateTasksServiceImpl {
         gen_resmoke_service: Arc<dyn GenResmokeTaskService>,
         config_extraction_service: Arc<dyn ConfigExtractionService>,
         gen_burn_in: bool,
+        suites: &[String],
     ) -> Self {
         Self {
             evg_config_service,
@@ -440,10 +670,47 @@ impl GenerateTasksServiceImpl {
             gen_resmoke_service,
             config_extraction_service,
             gen_burn_in,
+            suite_filter: suites
+                .iter()
+                .map(|suite| base_suite_name(suite).to_string())
+                .collect(),
+        }
+    }
+
+    /// Determine if the suite run by the given task was requested.
+    ///
+    /// # Arguments
+    ///
+    /// * `task_def` - Task definition to check.
+    ///
+    /// # Returns
+    ///
+    /// true if no suites were requested or the task runs one of the requested suites.
+    fn is_suite_requested(&self, task_def: &EvgTask) -> bool {
+        if self.suite_filter.is_empty() {
+            return true;
         }
+        let suite_name = self.evg_config_utils.find_suite_name(task_def);
+        self.suite_filter.contains(base_suite_name(suite_name))
     }
 }
 
+/// Get the base name of a suite, without any directories or file extension.
+///
+/// # Arguments
+///
+/// * `suite` - Suite name or path to suite file.
+///
+/// # Returns
+///
+/// Name of the suite file without its extension.
+fn base_suite_name(suite: &str) -> &str {
+    Path::new(suite)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(suite)
+}
+
 /// An implementation of GeneratorTasksService.
 #[async_trait]
 impl GenerateTasksService for GenerateTasksServiceImpl {
@@ -544,7 +811,9 @@ impl GenerateTasksService for GenerateTasksServiceImpl {
 
                 seen_tasks.insert(task_name);
                 if let Some(task_def) = task_map.get(&task.name) {
-                    if self.evg_config_utils.is_task_generated(task_def) {
+                    if self.evg_config_utils.is_task_generated(task_def)
+                        && self.is_suite_requested(task_def)
+                    {
                         // Spawn off a tokio task to do the actual generation work.
                         thread_handles.push(create_task_worker(
                             deps,
@@ -1055,11 +1324,17 @@ pub async fn build_s3_client() -> aws_sdk_s3::Client {
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
+    use maplit::hashmap;
     use rstest::rstest;
-    use shrub_rs::models::task::TaskDependency;
+    use shrub_rs::models::{
+        commands::fn_call_with_params, params::ParamValue, task::TaskDependency,
+    };
 
     use crate::{
         evergreen::evg_config_utils::MultiversionGenerateTaskConfig,
+        resmoke::{resmoke_proxy::MultiversionConfig, resmoke_suite::ResmokeSuiteConfig},
         task_types::{
             fuzzer_tasks::FuzzerGenTaskParams,
             generated_suite::GeneratedSubTask,
@@ -1137,9 +1412,155 @@ mod tests {
                 None,
             )),
             false,
+            &[],
         )
     }
 
+    // tests for base_suite_name.
+    #[rstest]
+    #[case("core", "core")]
+    #[case("core.yml", "core")]
+    #[case("buildscripts/resmokeconfig/suites/core.yml", "core")]
+    fn test_base_suite_name_should_strip_directories_and_extensions(
+        #[case] suite: &str,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(base_suite_name(suite), expected);
+    }
+
+    // tests for build_generated_tasks.
+    struct MockSuiteConfigService {
+        build_variant: BuildVariant,
+        tasks: Vec<EvgTask>,
+    }
+    impl EvgConfigService for MockSuiteConfigService {
+        fn get_build_variant_map(&self) -> HashMap<String, &BuildVariant> {
+            hashmap! {self.build_variant.name.clone() => &self.build_variant}
+        }
+
+        fn get_task_def_map(&self) -> HashMap<String, EvgTask> {
+            self.tasks
+                .iter()
+                .map(|task| (task.name.clone(), task.clone()))
+                .collect()
+        }
+
+        fn sort_build_variants_by_required(&self) -> Vec<String> {
+            vec![self.build_variant.name.clone()]
+        }
+
+        fn get_module_dir(&self, _module_name: &str) -> Option<String> {
+            None
+        }
+    }
+
+    struct MockGenerateTasksService {}
+    #[async_trait]
+    impl GenerateTasksService for MockGenerateTasksService {
+        async fn build_generated_tasks(
+            &self,
+            _deps: &Dependencies,
+        ) -> Result<Arc<Mutex<GenTaskCollection>>> {
+            Ok(Arc::new(Mutex::new(HashMap::new())))
+        }
+
+        fn generate_build_variants(
+            &self,
+            _deps: &Dependencies,
+            _generated_tasks: Arc<Mutex<GenTaskCollection>>,
+        ) -> Result<Vec<BuildVariant>> {
+            Ok(vec![])
+        }
+
+        fn generate_burn_in_build_variant_info(
+            &self,
+            _burn_in_tag_build_variant_info: &mut HashMap<String, BurnInTagBuildVariantInfo>,
+            _build_variant: &BuildVariant,
+            _build_variant_map: &HashMap<String, &BuildVariant>,
+        ) {
+        }
+
+        async fn generate_task(
+            &self,
+            task_def: &EvgTask,
+            _build_variant: &BuildVariant,
+        ) -> Result<Option<Box<dyn GeneratedSuite>>> {
+            Ok(Some(Box::new(GeneratedResmokeSuite {
+                task_name: task_def.name.clone(),
+                sub_suites: vec![],
+            })))
+        }
+    }
+
+    fn build_resmoke_gen_task(name: &str, suite: &str) -> EvgTask {
+        EvgTask {
+            name: name.to_string(),
+            commands: Some(vec![fn_call_with_params(
+                "generate resmoke tasks",
+                hashmap! {"suite".to_string() => ParamValue::from(suite)},
+            )]),
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case(vec![], vec!["auth-linux-enterprise", "core-linux-enterprise", "sharding-linux-enterprise"])]
+    #[case(vec!["core"], vec!["core-linux-enterprise"])]
+    #[case(vec!["core.yml", "sharding"], vec!["core-linux-enterprise", "sharding-linux-enterprise"])]
+    #[tokio::test]
+    async fn test_build_generated_tasks_should_only_generate_requested_suites(
+        #[case] suites: Vec<&str>,
+        #[case] expected_tasks: Vec<&str>,
+    ) {
+        let tasks = vec![
+            build_resmoke_gen_task("auth", "auth"),
+            build_resmoke_gen_task("core", "core"),
+            build_resmoke_gen_task("sharding", "buildscripts/resmokeconfig/suites/sharding.yml"),
+        ];
+        let build_variant = BuildVariant {
+            name: "bv".to_string(),
+            tasks: tasks
+                .iter()
+                .map(|task| TaskRef {
+                    name: task.name.clone(),
+                    distros: None,
+                    activate: None,
+                    depends_on: None,
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let suites: Vec<String> = suites.into_iter().map(|s| s.to_string()).collect();
+        let evg_config_utils = Arc::new(EvgConfigUtilsImpl::new());
+        let gen_task_service = GenerateTasksServiceImpl::new(
+            Arc::new(MockSuiteConfigService {
+                build_variant,
+                tasks,
+            }),
+            evg_config_utils.clone(),
+            Arc::new(MockGenFuzzerService {}),
+            Arc::new(MockGenResmokeTasksService {}),
+            Arc::new(ConfigExtractionServiceImpl::new(
+                evg_config_utils.clone(),
+                Arc::new(MockMultiversionService {}),
+                "generating_task".to_string(),
+                "config_location".to_string(),
+                None,
+            )),
+            false,
+            &suites,
+        );
+        let mut deps = build_mocked_dependencies(build_mocked_burn_in_service(vec![]));
+        deps.evg_config_utils = evg_config_utils;
+        deps.gen_task_service = Arc::new(MockGenerateTasksService {});
+
+        let generated_tasks = gen_task_service.build_generated_tasks(&deps).await.unwrap();
+
+        let mut task_names: Vec<String> = generated_tasks.lock().unwrap().keys().cloned().collect();
+        task_names.sort();
+        assert_eq!(task_names, expected_tasks);
+    }
+
     // tests for lookup_task_name.
     #[rstest]
     #[case(false, "my_task", "my_platform", "my_task-my_platform")]
@@ -1364,7 +1785,229 @@ mod tests {
                 MockResmokeConfigActorService {},
             )),
             burn_in_service: Arc::new(burn_in_service),
+            dry_run_actor: None,
+            fs_service: Arc::new(InMemoryFsService::new()),
+            fail_on_parse_error: false,
+            manifest_file: None,
+        }
+    }
+
+    // plan_configuration tests.
+    #[tokio::test]
+    async fn test_plan_configuration_should_require_dry_run() {
+        let deps = build_mocked_dependencies(build_mocked_burn_in_service(vec![]));
+
+        assert!(plan_configuration(&deps).await.is_err());
+    }
+
+    struct MockTestDiscovery {}
+    impl TestDiscovery for MockTestDiscovery {
+        fn discover_tests(&self, _suite_name: &str) -> Result<Vec<String>> {
+            Ok(vec![])
+        }
+
+        fn get_suite_config(&self, _suite_name: &str) -> Result<ResmokeSuiteConfig> {
+            Ok(ResmokeSuiteConfig::from_str(
+                "
+                test_kind: js_test
+
+                selector:
+                  roots:
+                    - jstests/core/*.js
+
+                executor:
+                  fixture:
+                    class: MyFixture
+                ",
+            )?)
+        }
+
+        fn get_multiversion_config(&self) -> Result<MultiversionConfig> {
+            Ok(MultiversionConfig {
+                last_versions: vec![],
+                requires_fcv_tag: "requires_fcv".to_string(),
+                requires_fcv_tag_lts: None,
+                requires_fcv_tag_continuous: None,
+            })
+        }
+    }
+
+    struct MockWritingGenerateTasksService {}
+    #[async_trait]
+    impl GenerateTasksService for MockWritingGenerateTasksService {
+        async fn build_generated_tasks(
+            &self,
+            deps: &Dependencies,
+        ) -> Result<Arc<Mutex<GenTaskCollection>>> {
+            let mut resmoke_config_actor = deps.resmoke_config_actor.lock().await;
+            resmoke_config_actor
+                .write_sub_suite(&ResmokeSuiteGenerationInfo {
+                    task_name: "my_task".to_string(),
+                    origin_suite: "my_suite".to_string(),
+                    require_multiversion_generate_tasks: false,
+                    sub_suites: vec![SubSuite {
+                        index: 0,
+                        name: "my_task".to_string(),
+                        origin_suite: "my_suite".to_string(),
+                        test_list: vec!["test_0.js".to_string()],
+                        ..Default::default()
+                    }],
+                })
+                .await;
+            Ok(Arc::new(Mutex::new(HashMap::new())))
+        }
+
+        fn generate_build_variants(
+            &self,
+            _deps: &Dependencies,
+            _generated_tasks: Arc<Mutex<GenTaskCollection>>,
+        ) -> Result<Vec<BuildVariant>> {
+            Ok(vec![])
         }
+
+        fn generate_burn_in_build_variant_info(
+            &self,
+            _burn_in_tag_build_variant_info: &mut HashMap<String, BurnInTagBuildVariantInfo>,
+            _build_variant: &BuildVariant,
+            _build_variant_map: &HashMap<String, &BuildVariant>,
+        ) {
+        }
+
+        async fn generate_task(
+            &self,
+            _task_def: &EvgTask,
+            _build_variant: &BuildVariant,
+        ) -> Result<Option<Box<dyn GeneratedSuite>>> {
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plan_configuration_should_not_write_any_files() {
+        let fs_service = Arc::new(InMemoryFsService::new());
+        let (resmoke_config_actor, dry_run_actor) = build_resmoke_config_actor(
+            true,
+            Arc::new(MockTestDiscovery {}),
+            fs_service.clone(),
+            "target",
+            WriteConfigOptions::default(),
+        );
+        let deps = Dependencies {
+            gen_task_service: Arc::new(MockWritingGenerateTasksService {}),
+            resmoke_config_actor,
+            dry_run_actor,
+            ..build_mocked_dependencies(build_mocked_burn_in_service(vec![]))
+        };
+
+        let plans = plan_configuration(&deps).await.unwrap();
+
+        assert_eq!(plans.len(), 1);
+        assert_eq!(plans[0].task_name, "my_task");
+        assert!(fs_service.files().is_empty());
+    }
+
+    // generate_configuration tests.
+    #[tokio::test]
+    async fn test_generate_configuration_should_write_all_files_with_fs_service() {
+        let target_directory = Path::new("in_memory_generated_config");
+        let fs_service = Arc::new(InMemoryFsService::new());
+        let (resmoke_config_actor, dry_run_actor) = build_resmoke_config_actor(
+            false,
+            Arc::new(MockTestDiscovery {}),
+            fs_service.clone(),
+            target_directory.to_str().unwrap(),
+            WriteConfigOptions {
+                record_manifest: true,
+                ..Default::default()
+            },
+        );
+        let deps = Dependencies {
+            gen_task_service: Arc::new(MockWritingGenerateTasksService {}),
+            resmoke_config_actor,
+            dry_run_actor,
+            fs_service: fs_service.clone(),
+            manifest_file: Some(target_directory.join("manifest.json")),
+            ..build_mocked_dependencies(build_mocked_burn_in_service(vec![]))
+        };
+
+        generate_configuration(&deps, target_directory)
+            .await
+            .unwrap();
+
+        let mut files: Vec<String> = fs_service.files().into_keys().collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                "in_memory_generated_config/evergreen_config.json",
+                "in_memory_generated_config/manifest.json",
+                "in_memory_generated_config/my_task_0.yml",
+            ]
+        );
+        assert!(!target_directory.exists());
+    }
+
+    struct MockBadFixtureTestDiscovery {}
+    impl TestDiscovery for MockBadFixtureTestDiscovery {
+        fn discover_tests(&self, _suite_name: &str) -> Result<Vec<String>> {
+            Ok(vec![])
+        }
+
+        fn get_suite_config(&self, _suite_name: &str) -> Result<ResmokeSuiteConfig> {
+            Ok(ResmokeSuiteConfig::from_str(
+                "
+                test_kind: js_test
+
+                selector:
+                  roots:
+                    - jstests/core/*.js
+
+                executor:
+                  fixture:
+                    class: ReplicaSetFixture
+                    num_nodes: many
+                ",
+            )?)
+        }
+
+        fn get_multiversion_config(&self) -> Result<MultiversionConfig> {
+            Ok(MultiversionConfig {
+                last_versions: vec![],
+                requires_fcv_tag: "requires_fcv".to_string(),
+                requires_fcv_tag_lts: None,
+                requires_fcv_tag_continuous: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_configuration_in_strict_mode_should_not_write_files_on_build_errors() {
+        let target_directory = Path::new("in_memory_generated_config");
+        let fs_service = Arc::new(InMemoryFsService::new());
+        let (resmoke_config_actor, dry_run_actor) = build_resmoke_config_actor(
+            false,
+            Arc::new(MockBadFixtureTestDiscovery {}),
+            fs_service.clone(),
+            target_directory.to_str().unwrap(),
+            WriteConfigOptions {
+                defer_writes: true,
+                fixture_policy: Some(FixturePolicy::default()),
+                ..Default::default()
+            },
+        );
+        let deps = Dependencies {
+            gen_task_service: Arc::new(MockWritingGenerateTasksService {}),
+            resmoke_config_actor,
+            dry_run_actor,
+            fs_service: fs_service.clone(),
+            fail_on_parse_error: true,
+            ..build_mocked_dependencies(build_mocked_burn_in_service(vec![]))
+        };
+
+        let result = generate_configuration(&deps, target_directory).await;
+
+        assert!(result.is_err());
+        assert!(fs_service.files().is_empty());
     }
 
     // tests for create_burn_in_worker.
@@ -7,8 +7,9 @@ use std::{
 use anyhow::Result;
 use clap::Parser;
 use mongo_task_generator::{
-    build_s3_client, generate_configuration, Dependencies, ExecutionConfiguration, ProjectInfo,
-    SubtaskLimits,
+    build_s3_client, generate_configuration, plan_configuration, Dependencies,
+    ExecutionConfiguration, FixturePolicy, ProjectInfo, SubtaskLimits, SuiteFileNaming,
+    WriteConfigOptions,
 };
 use serde::Deserialize;
 use tracing::{error, event, Level};
@@ -24,6 +25,8 @@ const DEFAULT_MAX_SUBTASKS_PER_TASK: &str = "10";
 const DEFAULT_DEFAULT_SUBTASKS_PER_TASKS: &str = "5";
 const DEFAULT_TEST_RUNTIME_PER_REQUIRED_SUBTASK: &str = "3600";
 const DEFAULT_LARGE_REQUIRED_TASK_RUNTIME_THRESHOLD: &str = "7200";
+const DEFAULT_FIXTURE_MIN_NODES: &str = "1";
+const DEFAULT_FIXTURE_MAX_NODES: &str = "3";
 
 /// Expansions from evergreen to determine settings for how task should be generated.
 #[derive(Debug, Deserialize)]
@@ -152,6 +155,76 @@ struct Args {
     // Maximum number of subtasks that can be generated for tasks
     #[clap(long, default_value = DEFAULT_MAX_SUBTASKS_PER_TASK)]
     max_subtasks_per_task: usize,
+
+    // Maximum number of tests that a generated subtask should contain. Takes precedence over
+    // the number of subtasks requested and the limits above when given.
+    #[clap(long)]
+    max_tests_per_suite: Option<usize>,
+
+    /// Maximum number of sub-tasks a generated task can be split into across all of its
+    /// multiversion combinations.
+    #[clap(long)]
+    max_total_subtasks: Option<usize>,
+
+    // Seed to shuffle tests with when splitting tasks by test count, so the split is
+    // reproducible between runs.
+    #[clap(long)]
+    distribution_seed: Option<u64>,
+
+    // Emit the sub-suites of tasks split by historic runtime longest first.
+    #[clap(long)]
+    sort_sub_suites_by_runtime: bool,
+
+    /// Report how tasks would be split instead of writing any configuration files.
+    #[clap(long)]
+    dry_run: bool,
+
+    /// Fail with a list of the offending suites if any resmoke suite cannot be parsed.
+    #[clap(long)]
+    fail_on_parse_error: bool,
+
+    /// Maximum number of inline roots a generated suite can have. Suites with more roots read
+    /// them from a `<suite name>_roots.txt` file written next to the suite file.
+    #[clap(long)]
+    max_inline_roots: Option<usize>,
+
+    /// Sort roots and excluded files in generated suites alphabetically.
+    #[clap(long)]
+    sort_suite_paths: bool,
+
+    /// Zero-pad the index in generated suite file names to this many digits, e.g. `suite_003.yml`
+    /// with a width of 3. By default suite files are named after their generated task.
+    #[clap(long)]
+    suite_file_index_width: Option<usize>,
+
+    /// Scale the fixture of generated suites to use one node (or shard, for sharded clusters)
+    /// for every this many tests, never more than the original suite uses. Fixtures are not
+    /// scaled unless this is given.
+    #[clap(long)]
+    fixture_tests_per_node: Option<usize>,
+
+    /// Minimum number of nodes a scaled fixture should use.
+    #[clap(long, default_value = DEFAULT_FIXTURE_MIN_NODES)]
+    fixture_min_nodes: u32,
+
+    /// Maximum number of nodes a scaled fixture should use.
+    #[clap(long, default_value = DEFAULT_FIXTURE_MAX_NODES)]
+    fixture_max_nodes: u32,
+
+    /// Only generate tasks running the given base suite, matched on the suite file name without
+    /// its extension. Can be given multiple times.
+    #[clap(long = "suite")]
+    suites: Vec<String>,
+
+    /// File to write a JSON manifest of the generated sub-suites, their tests and estimated
+    /// runtimes to.
+    #[clap(long, value_parser)]
+    manifest: Option<PathBuf>,
+
+    /// JSON file mapping test paths to their runtime in seconds. When the file exists, test
+    /// stats are only fetched for tests missing from it.
+    #[clap(long, value_parser)]
+    test_timings_file: Option<PathBuf>,
 }
 
 /// Configure logging for the command execution.
@@ -175,10 +248,21 @@ async fn main() {
         &evg_expansions.project,
         gen_sub_tasks_config_file.as_ref(),
     );
+    let manifest_file = args.manifest.as_ref().map(|p| expand_path(p));
+    let test_timings_file = args.test_timings_file.as_ref().map(|p| expand_path(p));
+    let (fixture_min_nodes, fixture_max_nodes) = (args.fixture_min_nodes, args.fixture_max_nodes);
+    let fixture_policy = args
+        .fixture_tests_per_node
+        .map(|tests_per_node| FixturePolicy {
+            min_nodes: fixture_min_nodes,
+            max_nodes: fixture_max_nodes,
+            tests_per_node,
+        });
     let execution_config = ExecutionConfiguration {
         project_info: &project_info,
         evg_auth_file: &expand_path(&args.evg_auth_file),
         use_task_split_fallback: args.use_task_split_fallback,
+        max_total_subtasks: args.max_total_subtasks,
         resmoke_command: &args.resmoke_command,
         target_directory: &expand_path(&args.target_directory),
         generating_task: &evg_expansions.task_name,
@@ -193,11 +277,40 @@ async fn main() {
             max_subtasks_per_task: args.max_subtasks_per_task,
             default_subtasks_per_task: args.default_subtasks_per_task,
             large_required_task_runtime_threshold: args.large_required_task_runtime_threshold,
+            max_tests_per_suite: args.max_tests_per_suite,
+            distribution_seed: args.distribution_seed,
+            sort_sub_suites_by_runtime: args.sort_sub_suites_by_runtime,
+        },
+        dry_run: args.dry_run,
+        fail_on_parse_error: args.fail_on_parse_error,
+        write_config_options: WriteConfigOptions {
+            max_inline_roots: args.max_inline_roots,
+            sort_paths: args.sort_suite_paths,
+            suite_file_naming: args
+                .suite_file_index_width
+                .map_or(SuiteFileNaming::SameAsTask, SuiteFileNaming::ZeroPadded),
+            fixture_policy,
+            ..Default::default()
         },
+        suites: args.suites.clone(),
+        manifest_file: manifest_file.as_deref(),
+        test_timings_file: test_timings_file.as_deref(),
+        task_decorator: None,
     };
     let s3_client = build_s3_client().await;
     let deps = Dependencies::new(execution_config, s3_client).unwrap();
 
+    if args.dry_run {
+        match plan_configuration(&deps).await {
+            Ok(plans) => println!("{}", serde_json::to_string_pretty(&plans).unwrap()),
+            Err(err) => {
+                eprintln!("Error encountered during execution: {:?}", err);
+                exit(1);
+            }
+        }
+        return;
+    }
+
     let start = Instant::now();
     let result = generate_configuration(&deps, &args.target_directory).await;
     event!(
@@ -1,5 +1,7 @@
 pub mod burn_in_proxy;
 pub mod resmoke_proxy;
 pub mod resmoke_suite;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
 mod external_cmd;
@@ -1,4 +1,9 @@
-use std::{path::Path, str::FromStr, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    str::FromStr,
+    time::Instant,
+};
 
 use anyhow::Result;
 use serde::Deserialize;
@@ -32,6 +37,21 @@ pub trait TestDiscovery: Send + Sync {
 
     /// Get the multiversion configuration to generate against.
     fn get_multiversion_config(&self) -> Result<MultiversionConfig>;
+
+    /// Get the tags of each test that belongs to the given suite, if they are known.
+    ///
+    /// Resmoke does not report the tags of tests, so by default they are unknown.
+    ///
+    /// # Arguments
+    ///
+    /// * `suite_name` - Name of test suite to query.
+    ///
+    /// # Returns
+    ///
+    /// Tags of each test in the given suite, or `None` if they are not known.
+    fn get_test_tags(&self, _suite_name: &str) -> Result<Option<HashMap<String, HashSet<String>>>> {
+        Ok(None)
+    }
 }
 
 /// Implementation of `TestDiscovery` that queries details from resmoke.
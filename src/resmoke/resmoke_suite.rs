@@ -1,13 +1,24 @@
 //! Representation of a resmoke suite file.
 
-use std::{collections::HashSet, str::FromStr};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    convert::TryFrom,
+    fmt::{Display, Formatter},
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use futures::{stream, StreamExt};
+use maplit::hashset;
 use serde::{Deserialize, Serialize};
-use serde_yaml::{Error, Value};
-use tracing::error;
+use serde_yaml::{value::TaggedValue, Error, Mapping, Value};
+use sha2::{Digest, Sha256};
+use tracing::{error, warn};
 
-#[derive(Serialize, Debug, Clone, Deserialize)]
+use crate::utils::test_path::normalize_test_path;
+
+#[derive(Serialize, Debug, Clone, Deserialize, PartialEq)]
 #[serde(untagged)]
 pub enum TestRoot {
     /// The path to a file containing the list of root tests.
@@ -16,17 +27,263 @@ pub enum TestRoot {
     Roots { roots: Vec<String> },
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+impl TestRoot {
+    /// Create a list of root tests from newline-delimited text.
+    ///
+    /// This follows the format of resmoke root files: whitespace is trimmed from each line and
+    /// blank lines and lines starting with `#` are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - Newline-delimited list of tests.
+    ///
+    /// # Returns
+    ///
+    /// A `Roots` test root containing the listed tests.
+    pub fn from_lines(s: &str) -> TestRoot {
+        TestRoot::Roots {
+            roots: parse_root_lines(s),
+        }
+    }
+
+    /// Get the list of root tests, reading them from the root file if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_dir` - Directory relative paths to the root file are resolved against.
+    ///
+    /// # Returns
+    ///
+    /// The inline list of tests for `Roots`, or the tests listed in the root file for `Root`.
+    pub fn resolve_roots(&self, base_dir: &Path) -> Result<Vec<String>> {
+        match self {
+            TestRoot::Roots { roots } => Ok(roots.clone()),
+            TestRoot::Root { root } => {
+                let path = base_dir.join(root);
+                let contents = match std::fs::read_to_string(&path) {
+                    Ok(contents) => contents,
+                    Err(err) => bail!("Failed to read root file '{}': {}", path.display(), err),
+                };
+                Ok(parse_root_lines(&contents))
+            }
+        }
+    }
+}
+
+/// Parse a list of tests from newline-delimited text in the format of resmoke root files.
+///
+/// # Arguments
+///
+/// * `s` - Newline-delimited list of tests.
+///
+/// # Returns
+///
+/// The listed tests, skipping blank lines and lines starting with `#`.
+fn parse_root_lines(s: &str) -> Vec<String> {
+    s.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// A tag matching expression used by resmoke to select tests.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum TagExpression {
+    /// A single tag that must be present.
+    Flat(String),
+    /// A list of expressions that must all match.
+    AllOf {
+        #[serde(rename = "$allOf")]
+        all_of: Vec<TagExpression>,
+    },
+    /// A list of expressions of which at least one must match.
+    AnyOf {
+        #[serde(rename = "$anyOf")]
+        any_of: Vec<TagExpression>,
+    },
+    /// An expression that must not match.
+    Not {
+        #[serde(rename = "$not")]
+        not: Box<TagExpression>,
+    },
+}
+
+impl TagExpression {
+    /// Create an expression that matches when both this and the given expression match.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Expression to combine with this one.
+    ///
+    /// # Returns
+    ///
+    /// An `$allOf` expression containing both expressions.
+    pub fn and(self, other: TagExpression) -> Self {
+        match self {
+            TagExpression::AllOf { mut all_of } => {
+                all_of.push(other);
+                TagExpression::AllOf { all_of }
+            }
+            expression => TagExpression::AllOf {
+                all_of: vec![expression, other],
+            },
+        }
+    }
+
+    /// Create an expression that matches when either this or the given expression match.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Expression to combine with this one.
+    ///
+    /// # Returns
+    ///
+    /// An `$anyOf` expression containing both expressions.
+    pub fn or(self, other: TagExpression) -> Self {
+        match self {
+            TagExpression::AnyOf { mut any_of } => {
+                any_of.push(other);
+                TagExpression::AnyOf { any_of }
+            }
+            expression => TagExpression::AnyOf {
+                any_of: vec![expression, other],
+            },
+        }
+    }
+
+    /// Determine if a test with the given tags matches this expression.
+    ///
+    /// Structured `$allOf`, `$anyOf` and `$not` expressions are evaluated fully. Flat
+    /// expressions are evaluated on a best-effort basis: a single tag name matches if the test
+    /// has that tag, and expressions combining tags with `!`, `&&`, `||` and parentheses are
+    /// evaluated with the usual precedence. A flat string that cannot be parsed as an expression
+    /// is treated as a single tag name.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - Tags of the test.
+    ///
+    /// # Returns
+    ///
+    /// true if the expression matches the tags.
+    pub fn matches(&self, tags: &HashSet<String>) -> bool {
+        match self {
+            TagExpression::Flat(expression) => evaluate_flat_tag_expression(expression, tags)
+                .unwrap_or_else(|| tags.contains(expression.trim())),
+            TagExpression::AllOf { all_of } => {
+                all_of.iter().all(|expression| expression.matches(tags))
+            }
+            TagExpression::AnyOf { any_of } => {
+                any_of.iter().any(|expression| expression.matches(tags))
+            }
+            TagExpression::Not { not } => !not.matches(tags),
+        }
+    }
+
+    /// Create an equivalent expression in a canonical form.
+    ///
+    /// Nested `$allOf` and `$anyOf` expressions of the same kind are flattened into their parent,
+    /// their sub-expressions are sorted with duplicates removed, lists of a single expression are
+    /// replaced by that expression and double negations are removed. Flat expressions have
+    /// surrounding whitespace trimmed but are otherwise left as written.
+    ///
+    /// # Returns
+    ///
+    /// The expression in canonical form.
+    pub fn canonicalize(&self) -> Self {
+        fn canonical_list(
+            expressions: &[TagExpression],
+            flatten: fn(TagExpression) -> Result<Vec<TagExpression>, TagExpression>,
+            build: fn(Vec<TagExpression>) -> TagExpression,
+        ) -> TagExpression {
+            let mut canonical = vec![];
+            for expression in expressions.iter().map(TagExpression::canonicalize) {
+                match flatten(expression) {
+                    Ok(nested) => canonical.extend(nested),
+                    Err(expression) => canonical.push(expression),
+                }
+            }
+            canonical.sort_by_cached_key(|expression| serde_yaml::to_string(expression).ok());
+            canonical.dedup();
+            if canonical.len() == 1 {
+                canonical.pop().unwrap()
+            } else {
+                build(canonical)
+            }
+        }
+
+        match self {
+            TagExpression::Flat(expression) => TagExpression::Flat(expression.trim().to_string()),
+            TagExpression::AllOf { all_of } => canonical_list(
+                all_of,
+                |expression| match expression {
+                    TagExpression::AllOf { all_of } => Ok(all_of),
+                    expression => Err(expression),
+                },
+                |all_of| TagExpression::AllOf { all_of },
+            ),
+            TagExpression::AnyOf { any_of } => canonical_list(
+                any_of,
+                |expression| match expression {
+                    TagExpression::AnyOf { any_of } => Ok(any_of),
+                    expression => Err(expression),
+                },
+                |any_of| TagExpression::AnyOf { any_of },
+            ),
+            TagExpression::Not { not } => match not.canonicalize() {
+                TagExpression::Not { not } => *not,
+                expression => TagExpression::Not {
+                    not: Box::new(expression),
+                },
+            },
+        }
+    }
+
+    /// Add all the tags referenced by this expression to the given set.
+    ///
+    /// Flat expressions are split on the operator characters `&`, `|`, `!`, `(`, `)` and `,`
+    /// and whitespace, so a flat string written as an expression contributes each of its tags.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - Set to add the referenced tags to.
+    fn collect_tags(&self, tags: &mut BTreeSet<String>) {
+        match self {
+            TagExpression::Flat(expression) => tags.extend(
+                expression
+                    .split(|c: char| c.is_whitespace() || "&|!(),".contains(c))
+                    .filter(|tag| !tag.is_empty())
+                    .map(|tag| tag.to_string()),
+            ),
+            TagExpression::AllOf {
+                all_of: expressions,
+            }
+            | TagExpression::AnyOf {
+                any_of: expressions,
+            } => expressions
+                .iter()
+                .for_each(|expression| expression.collect_tags(tags)),
+            TagExpression::Not { not } => not.collect_tags(tags),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct ResmokeSelector {
     /// A str or dict representing a tag matching expression that the tags of the
     /// selected tests must not match. Incompatible with 'include_tags'.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub exclude_tags: Option<String>,
+    pub exclude_tags: Option<TagExpression>,
     /// A list of paths or glob patterns the tests must not be included in.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exclude_files: Option<Vec<String>>,
     /// A list of tags. No selected tests can have any of them.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_sorted_set"
+    )]
     pub exclude_with_any_tags: Option<HashSet<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub group_size: Option<usize>,
@@ -41,7 +298,7 @@ pub struct ResmokeSelector {
     /// A str or dict representing a tag matching expression that the tags of the
     /// selected tests must match. Incompatible with 'exclude_tags'.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub include_tags: Option<String>,
+    pub include_tags: Option<TagExpression>,
     #[serde(flatten, skip_serializing_if = "Option::is_none")]
     pub test_root: Option<TestRoot>,
     /// Filename of a tag file associating tests to tags.
@@ -49,167 +306,7592 @@ pub struct ResmokeSelector {
     pub tag_file: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub test: Option<String>,
+    /// Any other keys in the selector, preserved so they are written back out unchanged.
+    #[serde(flatten, deserialize_with = "deserialize_selector_extra")]
+    pub extra: BTreeMap<String, Value>,
 }
 
-#[derive(Serialize, Debug, Clone, Deserialize)]
-pub struct ResmokeExecutor {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub archive: Option<Box<Value>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub hooks: Option<Vec<Value>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub config: Option<Box<Value>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub fixture: Option<Box<Value>>,
+/// Deserialize the unmodelled keys of a selector.
+///
+/// The flattened `test_root` does not consume the `root` or `roots` keys, so they are removed
+/// here to avoid writing them out twice.
+fn deserialize_selector_extra<'de, D>(deserializer: D) -> Result<BTreeMap<String, Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let mut extra = BTreeMap::<String, Value>::deserialize(deserializer)?;
+    extra.remove("root");
+    extra.remove("roots");
+    Ok(extra)
 }
 
-/// Configuration of a resmoke test suite.
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct ResmokeSuiteConfig {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub matrix_suite: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-    pub test_kind: String,
-    pub selector: ResmokeSelector,
-    pub executor: ResmokeExecutor,
+/// Serialize a set of strings in sorted order so the output is stable between runs.
+fn serialize_sorted_set<S>(set: &Option<HashSet<String>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let sorted = set.as_ref().map(|set| {
+        let mut items: Vec<&String> = set.iter().collect();
+        items.sort();
+        items
+    });
+    sorted.serialize(serializer)
 }
 
-impl FromStr for ResmokeSuiteConfig {
-    type Err = Error;
+impl ResmokeSelector {
+    /// Create a builder to construct a selector.
+    pub fn builder() -> ResmokeSelectorBuilder {
+        ResmokeSelectorBuilder::new()
+    }
 
-    /// Read Resmoke suite configuration from the given string.
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let resmoke_suite_config: Result<Self, serde_yaml::Error> = serde_yaml::from_str(s);
-        if resmoke_suite_config.is_err() {
-            error!(yaml = s, "Failed to parse yaml for ResmokeSuiteConfig",);
+    /// Get the number of root entries referenced by this selector.
+    ///
+    /// Root entries may be glob patterns, so this is an estimate of the number of tests.
+    ///
+    /// # Returns
+    ///
+    /// Number of roots for an inline list of roots, `None` if the roots are read from a file
+    /// or no roots are specified.
+    pub fn root_count(&self) -> Option<usize> {
+        match &self.test_root {
+            Some(TestRoot::Roots { roots }) => Some(roots.len()),
+            Some(TestRoot::Root { .. }) | None => None,
         }
-        resmoke_suite_config
     }
-}
 
-#[allow(clippy::to_string_trait_impl)]
-impl ToString for ResmokeSuiteConfig {
-    /// Convert this resmoke suite configuration to a string.
-    fn to_string(&self) -> String {
-        serde_yaml::to_string(self).unwrap()
+    /// Determine if this selector reads test tags from an external tag file.
+    ///
+    /// Selectors without a `tag_file` only rely on the tags defined inline in the tests. A blank
+    /// `tag_file` is treated the same as no `tag_file`.
+    ///
+    /// # Returns
+    ///
+    /// true if the tag file needs to be available alongside suites using this selector.
+    pub fn uses_external_tag_file(&self) -> bool {
+        self.tag_file
+            .as_deref()
+            .is_some_and(|tag_file| !tag_file.trim().is_empty())
     }
-}
 
-impl ResmokeSuiteConfig {
-    /// Create a new resmoke suite configuration based on this one but running certain tests.
+    /// Get every tag referenced by this selector.
+    ///
+    /// # Returns
+    ///
+    /// The union of the tags referenced by `include_tags`, `exclude_tags`,
+    /// `include_with_any_tags` and `exclude_with_any_tags`.
+    pub fn referenced_tags(&self) -> BTreeSet<String> {
+        let mut tags = BTreeSet::new();
+        for expression in [self.include_tags.as_ref(), self.exclude_tags.as_ref()]
+            .iter()
+            .flatten()
+        {
+            expression.collect_tags(&mut tags);
+        }
+        if let Some(include_with_any_tags) = &self.include_with_any_tags {
+            tags.extend(include_with_any_tags.iter().cloned());
+        }
+        if let Some(exclude_with_any_tags) = &self.exclude_with_any_tags {
+            tags.extend(exclude_with_any_tags.iter().cloned());
+        }
+        tags
+    }
+
+    /// Describe how the fields of this selector combine to select tests.
+    ///
+    /// resmoke starts from the candidate tests given by `roots` (or `test`), keeps only the
+    /// candidates matching `include_files` if it is set, removes any candidates matching
+    /// `exclude_files`, and finally applies the tag filters to what remains.
+    ///
+    /// # Returns
+    ///
+    /// The components of the selection, in the order resmoke applies them.
+    pub fn effective_includes(&self) -> EffectiveSelection {
+        let candidates = match (&self.test_root, &self.test) {
+            (Some(test_root), _) => Some(test_root.clone()),
+            (None, Some(test)) => Some(TestRoot::Roots {
+                roots: vec![test.clone()],
+            }),
+            (None, None) => None,
+        };
+
+        EffectiveSelection {
+            candidates,
+            include_files: self.include_files.clone(),
+            exclude_files: self.exclude_files.clone().unwrap_or_default(),
+            tag_filter: TagFilter {
+                include_tags: self.include_tags.clone(),
+                exclude_tags: self.exclude_tags.clone(),
+                include_with_any_tags: self.include_with_any_tags.clone(),
+                exclude_with_any_tags: self.exclude_with_any_tags.clone(),
+            },
+        }
+    }
+
+    /// Determine if this selector would select the given test, without invoking resmoke.
+    ///
+    /// Selection follows the order described in [`ResmokeSelector::effective_includes`]. Paths
+    /// are matched as globs where `*` does not cross directories and `**` does. Since the
+    /// contents of a `root` file are not available here, any test is considered a candidate of
+    /// a selector using one. Tag expressions are evaluated as described in
+    /// [`TagExpression::matches`].
     ///
     /// # Arguments
     ///
-    /// * `run_tests` - When provided, the new configuration should only run these tests.
-    /// * `exclude_tests` - When provided, the new configuration should exclude these tests.
+    /// * `test_path` - Path of the test to check.
+    /// * `test_tags` - Tags of the test.
     ///
     /// # Returns
     ///
-    /// New resmoke configuration with a selector based on provided parameters.
-    pub fn with_new_tests(
-        &self,
-        run_tests: Option<&[String]>,
-        exclude_tests: Option<&[String]>,
-    ) -> Self {
-        let mut config = self.clone();
-        let mut updated_selector = self.selector.clone();
-        if let Some(exclude_tests) = exclude_tests {
-            let mut files_to_exclude = vec![];
-            if let Some(excluded_files) = &updated_selector.exclude_files {
-                files_to_exclude.extend(excluded_files);
+    /// true if the test would be selected.
+    pub fn selects(&self, test_path: &str, test_tags: &HashSet<String>) -> bool {
+        self.selects_path(test_path) && self.effective_includes().tag_filter.matches(test_tags)
+    }
+
+    /// Determine if the roots and file filters of this selector would select the given test.
+    ///
+    /// This is the same as `selects` with the tag filters ignored, for when the tags of the
+    /// test are not known.
+    ///
+    /// # Arguments
+    ///
+    /// * `test_path` - Path of the test to check.
+    ///
+    /// # Returns
+    ///
+    /// true if the test is a candidate that is not filtered out by `include_files` or
+    /// `exclude_files`.
+    pub fn selects_path(&self, test_path: &str) -> bool {
+        let test_path = normalize_test_path(test_path);
+        let matches_any = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| path_matches(pattern, &test_path))
+        };
+        let selection = self.effective_includes();
+
+        let is_candidate = match &selection.candidates {
+            Some(TestRoot::Roots { roots }) => matches_any(roots),
+            Some(TestRoot::Root { .. }) => true,
+            None => false,
+        };
+        if !is_candidate {
+            return false;
+        }
+        if let Some(include_files) = &selection.include_files {
+            if !matches_any(include_files) {
+                return false;
             }
-            files_to_exclude.extend(exclude_tests.iter());
-            updated_selector.exclude_files = Some(
-                files_to_exclude
-                    .into_iter()
-                    .map(|s| s.to_string())
-                    .collect(),
-            );
-        } else if let Some(run_tests) = run_tests {
-            updated_selector.exclude_files = None;
-            updated_selector.test_root = Some(TestRoot::Roots {
-                roots: run_tests.iter().map(|s| s.to_string()).collect(),
-            });
         }
+        !matches_any(&selection.exclude_files)
+    }
 
-        config.selector = updated_selector;
-        config
+    /// Require that selected tests have the given tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - Tag selected tests must have.
+    pub fn add_required_tag(&mut self, tag: &str) {
+        let tag = TagExpression::Flat(tag.to_string());
+        self.include_tags = Some(match self.include_tags.take() {
+            Some(expression) => expression.and(tag),
+            None => tag,
+        });
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Rewrite the paths in this selector to a canonical repo-relative form.
+    ///
+    /// In canonical form paths use `/` as the separator, are relative to the root of the
+    /// repository, and contain no `.` components or leading `./`. `..` components are resolved
+    /// lexically where possible. Absolute paths inside `repo_root` are made relative to it,
+    /// absolute paths outside of it are left as they are. Glob patterns are kept intact.
+    ///
+    /// The inline `roots`, `root` file, `test`, `include_files` and `exclude_files` are
+    /// rewritten.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo_root` - Root of the repository that relative paths are relative to.
+    pub fn normalize_paths(&mut self, repo_root: &Path) {
+        let normalize = |path: &mut String| *path = canonical_repo_path(path, repo_root);
+        match &mut self.test_root {
+            Some(TestRoot::Roots { roots }) => roots.iter_mut().for_each(normalize),
+            Some(TestRoot::Root { root }) => normalize(root),
+            None => (),
+        }
+        if let Some(test) = &mut self.test {
+            normalize(test);
+        }
+        for files in [&mut self.include_files, &mut self.exclude_files]
+            .iter_mut()
+            .filter_map(|files| files.as_mut())
+        {
+            files.iter_mut().for_each(normalize);
+        }
+    }
 
-    // with_new_tests tests
-    #[test]
-    fn test_with_new_tests_can_add_tests_to_exclude_list() {
-        let config_yaml = "
-            test_kind: js_test
+    /// Exclude every JavaScript test under the given directory.
+    ///
+    /// A single `<dir>/**/*.js` glob is appended to `exclude_files`, where `**` matches any
+    /// number of nested directories, instead of listing each test. Path separators are
+    /// normalized to `/` and trailing separators are removed, so `jstests/core/` and
+    /// `jstests\core` both add `jstests/core/**/*.js`. The glob is not added again if it is
+    /// already excluded.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory containing the tests to exclude.
+    pub fn exclude_dir(&mut self, dir: &str) {
+        let dir = normalize_test_path(dir);
+        let glob = format!("{}/**/*.js", dir.trim_end_matches('/'));
+        let exclude_files = self.exclude_files.get_or_insert_with(Vec::new);
+        if !exclude_files.contains(&glob) {
+            exclude_files.push(glob);
+        }
+    }
 
-            selector:
-              roots:
-                - jstests/auth/*.js
-              exclude_files:
-                - jstests/auth/repl.js
-                - jstests/core/add1.js
-        
-            executor:
-              config:
-                value
-              fixture:
-                class: MyFixture
-                num_nodes: 3
-        ";
+    /// Create a new selector by applying an override selector on top of this one.
+    ///
+    /// Fields are combined as follows:
+    ///
+    /// * `exclude_files` - Union of both lists, in order with duplicates removed.
+    /// * `exclude_with_any_tags` - Union of both sets.
+    /// * `include_tags` - `$allOf` both expressions, tests must match both.
+    /// * `exclude_tags` - `$anyOf` both expressions, tests matching either are excluded.
+    /// * All other fields (including `roots`) - The override's value if set, otherwise this
+    ///   selector's value.
+    ///
+    /// # Arguments
+    ///
+    /// * `override_selector` - Selector to apply on top of this one.
+    ///
+    /// # Returns
+    ///
+    /// Selector combining both selectors.
+    pub fn overlay(&self, override_selector: &ResmokeSelector) -> ResmokeSelector {
+        let base = self.clone();
+        let over = override_selector.clone();
+        ResmokeSelector {
+            exclude_tags: combine_options(base.exclude_tags, over.exclude_tags, TagExpression::or),
+            exclude_files: combine_options(base.exclude_files, over.exclude_files, |base, over| {
+                let mut files = base;
+                for file in over {
+                    if !files.contains(&file) {
+                        files.push(file);
+                    }
+                }
+                files
+            }),
+            exclude_with_any_tags: combine_options(
+                base.exclude_with_any_tags,
+                over.exclude_with_any_tags,
+                |base, over| base.union(&over).cloned().collect(),
+            ),
+            group_size: over.group_size.or(base.group_size),
+            group_count_multiplier: over.group_count_multiplier.or(base.group_count_multiplier),
+            include_with_any_tags: over.include_with_any_tags.or(base.include_with_any_tags),
+            include_files: over.include_files.or(base.include_files),
+            include_tags: combine_options(base.include_tags, over.include_tags, TagExpression::and),
+            test_root: over.test_root.or(base.test_root),
+            tag_file: over.tag_file.or(base.tag_file),
+            test: over.test.or(base.test),
+            extra: {
+                let mut extra = base.extra;
+                extra.extend(over.extra);
+                extra
+            },
+        }
+    }
 
-        let exclude_test_list = vec!["test0.js".to_string(), "test1.js".to_string()];
+    /// Require that selected tests do not have the given tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - Tag selected tests must not have.
+    pub fn add_forbidden_tag(&mut self, tag: &str) {
+        let tag = TagExpression::Flat(tag.to_string());
+        self.exclude_tags = Some(match self.exclude_tags.take() {
+            Some(expression) => expression.or(tag),
+            None => tag,
+        });
+    }
 
-        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
-        let new_config = resmoke_suite.with_new_tests(None, Some(&exclude_test_list));
+    /// Find the explicitly listed roots of this selector that do not exist on disk.
+    ///
+    /// Roots listed inline, in a root file, or as a single `test` are checked. Glob patterns are
+    /// skipped, since a glob matching nothing is not necessarily a mistake. If the selector reads
+    /// its roots from a root file that cannot be read, the root file itself is reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo_root` - Directory the roots are relative to.
+    ///
+    /// # Returns
+    ///
+    /// The roots that do not exist, in the order they are listed.
+    pub fn missing_roots(&self, repo_root: &Path) -> Vec<String> {
+        let roots = match &self.test_root {
+            Some(test_root @ TestRoot::Root { root }) => match test_root.resolve_roots(repo_root) {
+                Ok(roots) => roots,
+                Err(_) => return vec![root.clone()],
+            },
+            Some(TestRoot::Roots { roots }) => roots.clone(),
+            None => vec![],
+        };
 
-        assert!(new_config.selector.exclude_files.is_some());
-        if let Some(excluded_files) = new_config.selector.exclude_files {
-            for test in exclude_test_list {
-                assert!(excluded_files.contains(&test));
+        roots
+            .into_iter()
+            .chain(self.test.clone())
+            .filter(|root| !is_glob_pattern(root))
+            .filter(|root| !repo_root.join(root).exists())
+            .collect()
+    }
+
+    /// Determine if this selector chooses its tests only through `include_files`, without any
+    /// `roots` or `test`.
+    ///
+    /// # Returns
+    ///
+    /// true if `include_files` is the only source of tests for this selector.
+    pub fn selects_only_by_include_files(&self) -> bool {
+        self.test_root.is_none() && self.test.is_none() && self.include_files.is_some()
+    }
+
+    /// Apply the given edit to this selector.
+    ///
+    /// # Arguments
+    ///
+    /// * `op` - Edit to apply.
+    pub fn apply(&mut self, op: SelectorOp) {
+        match op {
+            SelectorOp::AddExclude(path) => {
+                let exclude_files = self.exclude_files.get_or_insert_with(Vec::new);
+                if !exclude_files.contains(&path) {
+                    exclude_files.push(path);
+                }
+            }
+            SelectorOp::ExcludeDir(dir) => self.exclude_dir(&dir),
+            SelectorOp::SetRoots(roots) => {
+                self.test = None;
+                self.test_root = Some(TestRoot::Roots { roots });
+            }
+            SelectorOp::SetRootFile(root) => {
+                self.test = None;
+                self.test_root = Some(TestRoot::Root { root });
             }
+            SelectorOp::AddRequiredTag(tag) => self.add_required_tag(&tag),
+            SelectorOp::AddForbiddenTag(tag) => self.add_forbidden_tag(&tag),
         }
     }
+}
 
-    #[test]
-    fn test_with_new_tests_can_add_tests_to_test_root() {
-        let config_yaml = "
+/// An edit to a `ResmokeSelector`, described as data so edits can be serialized, logged and
+/// replayed with `ResmokeSelector::apply`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectorOp {
+    /// Add a path or glob pattern to `exclude_files`, if it is not already excluded.
+    AddExclude(String),
+    /// Exclude every JavaScript test under a directory, see `ResmokeSelector::exclude_dir`.
+    ExcludeDir(String),
+    /// Replace the roots, and any single `test`, with the given roots.
+    SetRoots(Vec<String>),
+    /// Replace the roots, and any single `test`, with a file listing the roots.
+    SetRootFile(String),
+    /// Require that selected tests have a tag.
+    AddRequiredTag(String),
+    /// Require that selected tests do not have a tag.
+    AddForbiddenTag(String),
+}
+
+/// A token in a flat tag expression.
+#[derive(Debug, Clone, PartialEq)]
+enum TagToken {
+    Tag(String),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+/// Split a flat tag expression into tokens.
+///
+/// `&`/`&&` and `|`/`||` are accepted for and and or, and anything that is not an operator,
+/// parenthesis or whitespace is part of a tag name.
+fn tokenize_flat_tag_expression(expression: &str) -> Vec<TagToken> {
+    let mut tokens = vec![];
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '&' | '|' => {
+                if chars.peek() == Some(&c) {
+                    chars.next();
+                }
+                tokens.push(if c == '&' {
+                    TagToken::And
+                } else {
+                    TagToken::Or
+                });
+            }
+            '!' => tokens.push(TagToken::Not),
+            '(' => tokens.push(TagToken::Open),
+            ')' => tokens.push(TagToken::Close),
+            c if c.is_whitespace() => (),
+            c => {
+                let mut tag = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || "&|!()".contains(next) {
+                        break;
+                    }
+                    tag.push(next);
+                    chars.next();
+                }
+                tokens.push(TagToken::Tag(tag));
+            }
+        }
+    }
+    tokens
+}
+
+/// Evaluate a flat tag expression against the tags of a test.
+///
+/// `!` binds tightest, then `&&`, then `||`.
+///
+/// # Arguments
+///
+/// * `expression` - Flat tag expression to evaluate.
+/// * `tags` - Tags of the test.
+///
+/// # Returns
+///
+/// The result of the expression, or `None` if it could not be parsed.
+fn evaluate_flat_tag_expression(expression: &str, tags: &HashSet<String>) -> Option<bool> {
+    fn or_expression(tokens: &[TagToken], pos: &mut usize, tags: &HashSet<String>) -> Option<bool> {
+        let mut result = and_expression(tokens, pos, tags)?;
+        while tokens.get(*pos) == Some(&TagToken::Or) {
+            *pos += 1;
+            result |= and_expression(tokens, pos, tags)?;
+        }
+        Some(result)
+    }
+
+    fn and_expression(
+        tokens: &[TagToken],
+        pos: &mut usize,
+        tags: &HashSet<String>,
+    ) -> Option<bool> {
+        let mut result = unary_expression(tokens, pos, tags)?;
+        while tokens.get(*pos) == Some(&TagToken::And) {
+            *pos += 1;
+            result &= unary_expression(tokens, pos, tags)?;
+        }
+        Some(result)
+    }
+
+    fn unary_expression(
+        tokens: &[TagToken],
+        pos: &mut usize,
+        tags: &HashSet<String>,
+    ) -> Option<bool> {
+        let token = tokens.get(*pos)?;
+        *pos += 1;
+        match token {
+            TagToken::Tag(tag) => Some(tags.contains(tag)),
+            TagToken::Not => unary_expression(tokens, pos, tags).map(|result| !result),
+            TagToken::Open => {
+                let result = or_expression(tokens, pos, tags)?;
+                if tokens.get(*pos) != Some(&TagToken::Close) {
+                    return None;
+                }
+                *pos += 1;
+                Some(result)
+            }
+            TagToken::And | TagToken::Or | TagToken::Close => None,
+        }
+    }
+
+    let tokens = tokenize_flat_tag_expression(expression);
+    let mut pos = 0;
+    let result = or_expression(&tokens, &mut pos, tags)?;
+    if pos != tokens.len() {
+        return None;
+    }
+    Some(result)
+}
+
+/// Convert a path to the canonical repo-relative form described in
+/// `ResmokeSelector::normalize_paths`.
+///
+/// # Arguments
+///
+/// * `path` - Path to convert.
+/// * `repo_root` - Root of the repository.
+///
+/// # Returns
+///
+/// The path in canonical form.
+fn canonical_repo_path(path: &str, repo_root: &Path) -> String {
+    let path = normalize_test_path(path);
+    let mut relative = Path::new(&path);
+    if relative.is_absolute() {
+        match relative.strip_prefix(repo_root) {
+            Ok(inside_repo) => relative = inside_repo,
+            Err(_) => return path,
+        }
+    }
+
+    let mut parts: Vec<&str> = vec![];
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str().unwrap_or_default()),
+            Component::ParentDir if parts.last().is_some_and(|last| *last != "..") => {
+                parts.pop();
+            }
+            Component::ParentDir => parts.push(".."),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => (),
+        }
+    }
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+/// Determine if a selector path is a glob pattern rather than a plain path.
+///
+/// # Arguments
+///
+/// * `path` - Path from a selector.
+///
+/// # Returns
+///
+/// true if the path contains any glob special characters.
+fn is_glob_pattern(path: &str) -> bool {
+    path.chars().any(|c| matches!(c, '*' | '?' | '['))
+}
+
+/// Determine if a test path matches a path or glob pattern from a selector.
+///
+/// `*` does not match across directories, `**` does.
+///
+/// # Arguments
+///
+/// * `pattern` - Path or glob pattern.
+/// * `test_path` - Normalized path of the test.
+///
+/// # Returns
+///
+/// true if the test path matches the pattern.
+fn path_matches(pattern: &str, test_path: &str) -> bool {
+    let pattern = normalize_test_path(pattern);
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+    match glob::Pattern::new(&pattern) {
+        Ok(glob) => glob.matches_with(test_path, options),
+        Err(_) => pattern == test_path,
+    }
+}
+
+/// The components a selector uses to choose tests, in the order resmoke applies them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectiveSelection {
+    /// Tests (or glob patterns) to consider, `None` if the selector does not name any.
+    pub candidates: Option<TestRoot>,
+    /// If set, only candidates matching one of these paths or patterns are kept.
+    pub include_files: Option<Vec<String>>,
+    /// Candidates matching any of these paths or patterns are removed.
+    pub exclude_files: Vec<String>,
+    /// Tag filters applied to the remaining tests.
+    pub tag_filter: TagFilter,
+}
+
+/// Tag based filters of a selector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagFilter {
+    /// Expression the tags of selected tests must match.
+    pub include_tags: Option<TagExpression>,
+    /// Expression the tags of selected tests must not match.
+    pub exclude_tags: Option<TagExpression>,
+    /// Selected tests must have at least one of these tags.
+    pub include_with_any_tags: Option<Vec<String>>,
+    /// Selected tests must not have any of these tags.
+    pub exclude_with_any_tags: Option<HashSet<String>>,
+}
+
+impl TagFilter {
+    /// Determine if a test with the given tags passes these filters.
+    ///
+    /// See [`TagExpression::matches`] for which forms of `include_tags` and `exclude_tags` are
+    /// supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - Tags of the test.
+    ///
+    /// # Returns
+    ///
+    /// true if the test passes every tag filter.
+    pub fn matches(&self, tags: &HashSet<String>) -> bool {
+        if let Some(include_with_any_tags) = &self.include_with_any_tags {
+            if !include_with_any_tags.iter().any(|tag| tags.contains(tag)) {
+                return false;
+            }
+        }
+        if let Some(exclude_with_any_tags) = &self.exclude_with_any_tags {
+            if exclude_with_any_tags.iter().any(|tag| tags.contains(tag)) {
+                return false;
+            }
+        }
+        if let Some(include_tags) = &self.include_tags {
+            if !include_tags.matches(tags) {
+                return false;
+            }
+        }
+        if let Some(exclude_tags) = &self.exclude_tags {
+            if exclude_tags.matches(tags) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Combine two optional values.
+///
+/// # Arguments
+///
+/// * `base` - First value.
+/// * `over` - Second value.
+/// * `combine` - Function to combine the values if both are present.
+///
+/// # Returns
+///
+/// The combined value if both are present, otherwise whichever value is present.
+fn combine_options<T, F>(base: Option<T>, over: Option<T>, combine: F) -> Option<T>
+where
+    F: FnOnce(T, T) -> T,
+{
+    match (base, over) {
+        (Some(base), Some(over)) => Some(combine(base, over)),
+        (base, over) => over.or(base),
+    }
+}
+
+/// Builder to construct a `ResmokeSelector` one field at a time.
+#[derive(Debug, Clone)]
+pub struct ResmokeSelectorBuilder {
+    /// Selector being built.
+    selector: ResmokeSelector,
+}
+
+impl ResmokeSelectorBuilder {
+    /// Create a new builder with no fields set.
+    pub fn new() -> Self {
+        Self {
+            selector: ResmokeSelector::default(),
+        }
+    }
+
+    /// Set the list of root tests.
+    pub fn roots(mut self, roots: Vec<String>) -> Self {
+        self.selector.test_root = Some(TestRoot::Roots { roots });
+        self
+    }
+
+    /// Set the file containing the list of root tests.
+    pub fn root(mut self, root: &str) -> Self {
+        self.selector.test_root = Some(TestRoot::Root {
+            root: root.to_string(),
+        });
+        self
+    }
+
+    /// Set the single test to run.
+    pub fn test(mut self, test: &str) -> Self {
+        self.selector.test = Some(test.to_string());
+        self
+    }
+
+    /// Set the paths or glob patterns of tests to exclude.
+    pub fn exclude_files(mut self, exclude_files: Vec<String>) -> Self {
+        self.selector.exclude_files = Some(exclude_files);
+        self
+    }
+
+    /// Set the paths or glob patterns of tests to include.
+    pub fn include_files(mut self, include_files: Vec<String>) -> Self {
+        self.selector.include_files = Some(include_files);
+        self
+    }
+
+    /// Set the tag expression selected tests must not match.
+    pub fn exclude_tags(mut self, exclude_tags: TagExpression) -> Self {
+        self.selector.exclude_tags = Some(exclude_tags);
+        self
+    }
+
+    /// Set the tag expression selected tests must match.
+    pub fn include_tags(mut self, include_tags: TagExpression) -> Self {
+        self.selector.include_tags = Some(include_tags);
+        self
+    }
+
+    /// Set the tags that no selected test can have.
+    pub fn exclude_with_any_tags(mut self, tags: HashSet<String>) -> Self {
+        self.selector.exclude_with_any_tags = Some(tags);
+        self
+    }
+
+    /// Set the tags of which every selected test must have at least one.
+    pub fn include_with_any_tags(mut self, tags: Vec<String>) -> Self {
+        self.selector.include_with_any_tags = Some(tags);
+        self
+    }
+
+    /// Set the group size.
+    pub fn group_size(mut self, group_size: usize) -> Self {
+        self.selector.group_size = Some(group_size);
+        self
+    }
+
+    /// Set the group count multiplier.
+    pub fn group_count_multiplier(mut self, multiplier: f64) -> Self {
+        self.selector.group_count_multiplier = Some(multiplier);
+        self
+    }
+
+    /// Set the tag file associating tests to tags.
+    pub fn tag_file(mut self, tag_file: &str) -> Self {
+        self.selector.tag_file = Some(tag_file.to_string());
+        self
+    }
+
+    /// Create the selector that has been built.
+    pub fn build(self) -> ResmokeSelector {
+        self.selector
+    }
+}
+
+impl Default for ResmokeSelectorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Deserialize, Default, PartialEq)]
+pub struct ResmokeExecutor {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<Box<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<Vec<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<Box<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixture: Option<Box<Value>>,
+    /// Variables used for templating the executor, commonly set by matrix suites.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_variables: Option<Box<Value>>,
+    /// Any other keys in the executor, preserved so they are written back out unchanged.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// Selection of what should be archived.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ArchiveSelection {
+    /// Archive everything or nothing.
+    All(bool),
+    /// Archive only the listed items.
+    Only(Vec<String>),
+}
+
+/// Typed view of the `executor.archive` section of a resmoke suite.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ArchiveConfig {
+    /// Hooks that should trigger archiving on failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hooks: Option<ArchiveSelection>,
+    /// Tests that should trigger archiving on failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tests: Option<ArchiveSelection>,
+    /// Any other archive configuration, passed through untouched.
+    #[serde(flatten)]
+    other: Mapping,
+}
+
+impl ArchiveConfig {
+    /// Hooks that should trigger archiving on failure, if specified.
+    pub fn hooks(&self) -> Option<&ArchiveSelection> {
+        self.hooks.as_ref()
+    }
+
+    /// Set the hooks that should trigger archiving on failure.
+    ///
+    /// # Arguments
+    ///
+    /// * `hooks` - Hooks that should trigger archiving.
+    pub fn set_hooks(&mut self, hooks: ArchiveSelection) {
+        self.hooks = Some(hooks);
+    }
+
+    /// Tests that should trigger archiving on failure, if specified.
+    pub fn tests(&self) -> Option<&ArchiveSelection> {
+        self.tests.as_ref()
+    }
+
+    /// Set the tests that should trigger archiving on failure.
+    ///
+    /// # Arguments
+    ///
+    /// * `tests` - Tests that should trigger archiving.
+    pub fn set_tests(&mut self, tests: ArchiveSelection) {
+        self.tests = Some(tests);
+    }
+}
+
+impl TryFrom<&Value> for ArchiveConfig {
+    type Error = Error;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        serde_yaml::from_value(value.clone())
+    }
+}
+
+impl TryFrom<ArchiveConfig> for Value {
+    type Error = Error;
+
+    fn try_from(archive: ArchiveConfig) -> Result<Self, Self::Error> {
+        serde_yaml::to_value(archive)
+    }
+}
+
+/// Typed view of the `executor.fixture` section of a resmoke suite.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct FixtureConfig {
+    /// Name of the fixture class.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    class: Option<String>,
+    /// Number of nodes the fixture will use.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_nodes: Option<u32>,
+    /// Number of shards a sharded cluster fixture will use.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_shards: Option<u32>,
+    /// Any other fixture configuration, passed through untouched.
+    #[serde(flatten)]
+    other: Mapping,
+}
+
+impl FixtureConfig {
+    /// Name of the fixture class, if specified.
+    pub fn class(&self) -> Option<&str> {
+        self.class.as_deref()
+    }
+
+    /// Number of nodes the fixture will use, if specified.
+    pub fn num_nodes(&self) -> Option<u32> {
+        self.num_nodes
+    }
+
+    /// Set the number of nodes the fixture should use.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_nodes` - Number of nodes the fixture should use.
+    pub fn set_num_nodes(&mut self, num_nodes: u32) {
+        self.num_nodes = Some(num_nodes);
+    }
+
+    /// Number of shards a sharded cluster fixture will use, if specified.
+    pub fn num_shards(&self) -> Option<u32> {
+        self.num_shards
+    }
+
+    /// Set the number of shards a sharded cluster fixture should use.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_shards` - Number of shards the fixture should use.
+    pub fn set_num_shards(&mut self, num_shards: u32) {
+        self.num_shards = Some(num_shards);
+    }
+}
+
+/// Policy for scaling the size of a suite's fixture with the number of tests it runs.
+///
+/// The default policy uses one node (or shard) for every 20 tests, with at least 1 and at most
+/// 3 nodes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixturePolicy {
+    /// Minimum number of nodes the fixture should use.
+    pub min_nodes: u32,
+    /// Maximum number of nodes the fixture should use. Takes precedence over `min_nodes`.
+    pub max_nodes: u32,
+    /// Number of tests each node should be used for.
+    pub tests_per_node: usize,
+}
+
+impl Default for FixturePolicy {
+    fn default() -> Self {
+        Self {
+            min_nodes: 1,
+            max_nodes: 3,
+            tests_per_node: 20,
+        }
+    }
+}
+
+impl FixturePolicy {
+    /// Determine how many nodes a fixture running the given number of tests should use.
+    ///
+    /// # Arguments
+    ///
+    /// * `test_count` - Number of tests the fixture will run.
+    ///
+    /// # Returns
+    ///
+    /// Number of nodes to use, within the bounds of this policy.
+    pub fn node_count(&self, test_count: usize) -> u32 {
+        let tests_per_node = self.tests_per_node.max(1);
+        let nodes = test_count.div_ceil(tests_per_node);
+        let nodes = u32::try_from(nodes).unwrap_or(u32::MAX);
+        nodes.max(self.min_nodes).min(self.max_nodes)
+    }
+}
+
+impl TryFrom<&Value> for FixtureConfig {
+    type Error = Error;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        serde_yaml::from_value(value.clone())
+    }
+}
+
+impl TryFrom<FixtureConfig> for Value {
+    type Error = Error;
+
+    fn try_from(fixture: FixtureConfig) -> Result<Self, Self::Error> {
+        serde_yaml::to_value(fixture)
+    }
+}
+
+impl ResmokeExecutor {
+    /// Get a typed view of the archive configuration.
+    ///
+    /// # Returns
+    ///
+    /// The archive configuration if one is set.
+    pub fn archive_config(&self) -> Result<Option<ArchiveConfig>> {
+        Ok(self
+            .archive
+            .as_deref()
+            .map(ArchiveConfig::try_from)
+            .transpose()?)
+    }
+
+    /// Replace the archive configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `archive` - Archive configuration to use.
+    ///
+    /// # Returns
+    ///
+    /// Error if the archive configuration could not be converted to yaml.
+    pub fn set_archive_config(&mut self, archive: ArchiveConfig) -> Result<()> {
+        self.archive = Some(Box::new(Value::try_from(archive)?));
+        Ok(())
+    }
+
+    /// Get the names of the hooks this executor runs.
+    ///
+    /// Hooks can be specified either as the name of the hook or as a mapping with a `class`
+    /// key naming the hook.
+    ///
+    /// # Returns
+    ///
+    /// Names of all the hooks run by this executor.
+    pub fn hook_names(&self) -> Vec<String> {
+        self.hooks
+            .iter()
+            .flatten()
+            .filter_map(|hook| hook_name(hook).map(|name| name.to_string()))
+            .collect()
+    }
+
+    /// Keep only the hooks whose name passes the given predicate.
+    ///
+    /// Hooks specified as mappings are kept or removed as a whole, including any additional
+    /// configuration they carry. Hooks whose name cannot be determined are always kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `pred` - Predicate called with the name of each hook, returns true to keep the hook.
+    pub fn retain_hooks<F: Fn(&str) -> bool>(&mut self, pred: F) {
+        if let Some(hooks) = self.hooks.as_mut() {
+            hooks.retain(|hook| hook_name(hook).map_or(true, &pred));
+        }
+    }
+
+    /// Determine if this executor runs the given hook.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of hook to check for.
+    ///
+    /// # Returns
+    ///
+    /// true if the hook is run by this executor.
+    pub fn has_hook(&self, name: &str) -> bool {
+        self.hook_names().iter().any(|hook| hook == name)
+    }
+
+    /// Merge the given values into the executor configuration.
+    ///
+    /// Mappings are merged recursively at every depth, with values from `extra` winning on
+    /// conflicting keys. Any other value, including sequences, in `extra` replaces the
+    /// existing value.
+    ///
+    /// # Arguments
+    ///
+    /// * `extra` - Configuration to merge into the existing configuration.
+    pub fn merge_config(&mut self, extra: &Value) {
+        match self.config.as_deref_mut() {
+            Some(config) => merge_values(config, extra),
+            None => self.config = Some(Box::new(extra.clone())),
+        }
+    }
+
+    /// Get a typed view of the fixture configuration.
+    ///
+    /// # Returns
+    ///
+    /// The fixture configuration if one is set.
+    pub fn fixture_config(&self) -> Result<Option<FixtureConfig>> {
+        Ok(self
+            .fixture
+            .as_deref()
+            .map(FixtureConfig::try_from)
+            .transpose()?)
+    }
+
+    /// Replace the fixture configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `fixture` - Fixture configuration to use.
+    ///
+    /// # Returns
+    ///
+    /// Error if the fixture configuration could not be converted to yaml.
+    pub fn set_fixture_config(&mut self, fixture: FixtureConfig) -> Result<()> {
+        self.fixture = Some(Box::new(Value::try_from(fixture)?));
+        Ok(())
+    }
+}
+
+/// Top-level keys that are understood in a resmoke suite configuration.
+const KNOWN_SUITE_KEYS: [&str; 5] = [
+    "matrix_suite",
+    "description",
+    "test_kind",
+    "selector",
+    "executor",
+];
+
+/// Get the name of the given hook.
+///
+/// # Arguments
+///
+/// * `hook` - Hook as either the name of the hook or a mapping with a `class` key.
+///
+/// # Returns
+///
+/// Name of the hook if it could be determined.
+fn hook_name(hook: &Value) -> Option<&str> {
+    match hook {
+        Value::String(name) => Some(name),
+        Value::Mapping(_) => hook["class"].as_str(),
+        _ => None,
+    }
+}
+
+/// Marker starting the origin recorded in a suite description.
+const ORIGIN_PREFIX: &str = "[origin: ";
+
+/// Marker ending the origin recorded in a suite description.
+const ORIGIN_SUFFIX: &str = "]";
+
+/// Recursively merge the `extra` value into the `base` value.
+///
+/// # Arguments
+///
+/// * `base` - Value to merge into.
+/// * `extra` - Value to merge from, wins on conflicts.
+fn merge_values(base: &mut Value, extra: &Value) {
+    match (base, extra) {
+        (Value::Mapping(base), Value::Mapping(extra)) => {
+            for (key, extra_value) in extra {
+                match base.get_mut(key) {
+                    Some(base_value) => merge_values(base_value, extra_value),
+                    None => {
+                        base.insert(key.clone(), extra_value.clone());
+                    }
+                }
+            }
+        }
+        (base, extra) => *base = extra.clone(),
+    }
+}
+
+/// Create a copy of the given value with the keys of all mappings sorted.
+///
+/// # Arguments
+///
+/// * `value` - Value to canonicalize.
+///
+/// # Returns
+///
+/// Value with the keys of all nested mappings in sorted order, or an error if a mapping key
+/// could not be serialized to sort it.
+fn canonicalize_value(value: &Value) -> Result<Value, Error> {
+    Ok(match value {
+        Value::Mapping(mapping) => {
+            let mut entries: Vec<(String, Value, Value)> = mapping
+                .iter()
+                .map(|(k, v)| Ok((serde_yaml::to_string(k)?, k.clone(), canonicalize_value(v)?)))
+                .collect::<Result<_, Error>>()?;
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Mapping(entries.into_iter().map(|(_, k, v)| (k, v)).collect())
+        }
+        Value::Sequence(sequence) => Value::Sequence(
+            sequence
+                .iter()
+                .map(canonicalize_value)
+                .collect::<Result<_, Error>>()?,
+        ),
+        Value::Tagged(tagged) => Value::Tagged(Box::new(TaggedValue {
+            tag: tagged.tag.clone(),
+            value: canonicalize_value(&tagged.value)?,
+        })),
+        _ => value.clone(),
+    })
+}
+
+/// A suite configuration that repeatedly runs a set of changed tests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BurnInSuite {
+    /// Configuration running the changed tests.
+    pub config: ResmokeSuiteConfig,
+    /// Number of times each test should be run.
+    pub repeat: usize,
+}
+
+impl BurnInSuite {
+    /// Get the resmoke arguments needed to run the tests the requested number of times.
+    ///
+    /// resmoke suite files cannot express repetition, so these need to be passed on the resmoke
+    /// command line of the task running the suite.
+    pub fn resmoke_args(&self) -> String {
+        format!("--repeatTests={}", self.repeat)
+    }
+
+    /// Build the arguments to pass to resmoke to run this suite, including the repeat count.
+    ///
+    /// # Arguments
+    ///
+    /// * `suite_file` - Path to the file the suite configuration is written to.
+    /// * `extra_args` - Additional arguments to pass to resmoke, added last.
+    ///
+    /// # Returns
+    ///
+    /// Arguments to invoke resmoke with, starting with the `run` subcommand.
+    pub fn run_command(&self, suite_file: &str, extra_args: &[String]) -> Vec<String> {
+        let mut args = self.config.run_command(suite_file, &[]);
+        args.push(self.resmoke_args());
+        args.extend(extra_args.iter().cloned());
+        args
+    }
+}
+
+/// The root tests declared by a suite configuration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RootTests<'a> {
+    /// Tests listed inline with `roots` (or a single `test`).
+    Inline(&'a [String]),
+    /// Path to a file listing the tests.
+    File(&'a str),
+    /// No root tests are declared.
+    Unspecified,
+}
+
+/// A resmoke suite configuration whose roots were moved to a separate root file.
+#[derive(Debug, Clone)]
+pub struct SpilledRoots {
+    /// Configuration reading its roots from the root file.
+    pub config: ResmokeSuiteConfig,
+    /// Path the root file should be written to.
+    pub roots_path: PathBuf,
+    /// Contents of the root file.
+    pub roots_contents: String,
+}
+
+/// Problems that can be detected in a resmoke suite configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SuiteValidationError {
+    /// Both `include_tags` and `exclude_tags` were specified.
+    ConflictingTagExpressions,
+    /// Tags present in both `include_with_any_tags` and `exclude_with_any_tags`.
+    OverlappingAnyTags(Vec<String>),
+    /// A suite was requested to run an empty list of tests.
+    EmptyTestList,
+    /// `group_size` was set to 0.
+    InvalidGroupSize,
+    /// `group_count_multiplier` was not a positive, finite number.
+    InvalidGroupCountMultiplier(f64),
+    /// The roots are read from the named root file, so which tests are selected is not known.
+    UnresolvedRootFile(String),
+    /// Top-level keys that are not part of a resmoke suite configuration.
+    UnknownKeys(Vec<String>),
+}
+
+impl Display for SuiteValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SuiteValidationError::ConflictingTagExpressions => write!(
+                f,
+                "'include_tags' and 'exclude_tags' cannot both be specified in a selector"
+            ),
+            SuiteValidationError::OverlappingAnyTags(tags) => write!(
+                f,
+                "tags found in both 'include_with_any_tags' and 'exclude_with_any_tags': {}",
+                tags.join(", ")
+            ),
+            SuiteValidationError::EmptyTestList => {
+                write!(f, "a generated suite must run at least one test")
+            }
+            SuiteValidationError::InvalidGroupSize => {
+                write!(f, "'group_size' must be greater than 0")
+            }
+            SuiteValidationError::InvalidGroupCountMultiplier(multiplier) => write!(
+                f,
+                "'group_count_multiplier' must be a positive, finite number: {}",
+                multiplier
+            ),
+            SuiteValidationError::UnresolvedRootFile(root) => write!(
+                f,
+                "roots are read from the root file '{}', which must be resolved first",
+                root
+            ),
+            SuiteValidationError::UnknownKeys(keys) => write!(
+                f,
+                "unknown keys found in resmoke suite configuration: {}",
+                keys.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SuiteValidationError {}
+
+/// Default maximum number of tags an `*_with_any_tags` list can have before it is linted.
+pub const DEFAULT_MAX_ANY_TAGS: usize = 20;
+
+/// Problems in a resmoke suite configuration that resmoke accepts but that suggest the suite
+/// is misconfigured.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SuiteLint {
+    /// A `*_with_any_tags` list had more tags than the allowed maximum.
+    TooManyAnyTags {
+        /// Name of the selector field.
+        field: &'static str,
+        /// Number of tags in the field.
+        count: usize,
+        /// Maximum number of tags allowed.
+        max: usize,
+    },
+    /// Tests listed explicitly in `roots` that are also listed in `exclude_files`, so resmoke
+    /// will never run them.
+    ExcludedRoots(Vec<String>),
+}
+
+impl Display for SuiteLint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SuiteLint::TooManyAnyTags { field, count, max } => write!(
+                f,
+                "'{}' has {} tags, more than the maximum of {}",
+                field, count, max
+            ),
+            SuiteLint::ExcludedRoots(tests) => write!(
+                f,
+                "tests found in both 'roots' and 'exclude_files': {}",
+                tests.join(", ")
+            ),
+        }
+    }
+}
+
+/// Problems that can occur working with a resmoke suite configuration.
+#[derive(Debug)]
+pub enum SuiteError {
+    /// The configuration could not be parsed.
+    Parse {
+        /// Path of the file being parsed, if the configuration came from a file.
+        path: Option<PathBuf>,
+        /// Line (1-based) the error occurred on, if known.
+        line: Option<usize>,
+        /// Column (1-based) the error occurred on, if known.
+        column: Option<usize>,
+        /// Underlying parse error.
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// The configuration could not be serialized.
+    Serialize {
+        /// Underlying serialization error.
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// The configuration was parsed but is not valid.
+    Validation(SuiteValidationError),
+    /// A file could not be read.
+    Io {
+        /// Path of the file being read, if known.
+        path: Option<PathBuf>,
+        /// Underlying I/O error.
+        source: std::io::Error,
+    },
+}
+
+impl SuiteError {
+    /// Create a parse error from the given YAML error.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file being parsed, if the configuration came from a file.
+    /// * `err` - Error returned by the YAML parser.
+    fn from_yaml(path: Option<&Path>, err: Error) -> Self {
+        let location = err.location();
+        SuiteError::Parse {
+            path: path.map(|p| p.to_path_buf()),
+            line: location.as_ref().map(|l| l.line()),
+            column: location.as_ref().map(|l| l.column()),
+            source: Box::new(err),
+        }
+    }
+
+    /// Path of the file the error relates to, if the configuration came from a file.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            SuiteError::Parse { path, .. } | SuiteError::Io { path, .. } => path.as_deref(),
+            SuiteError::Serialize { .. } | SuiteError::Validation(_) => None,
+        }
+    }
+}
+
+impl Display for SuiteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SuiteError::Parse { path, source, .. } => {
+                // The parse error already describes where in the configuration it occurred.
+                write!(f, "failed to parse suite")?;
+                if let Some(path) = path {
+                    write!(f, " file '{}'", path.display())?;
+                }
+                write!(f, ": {}", source)
+            }
+            SuiteError::Serialize { source } => {
+                write!(f, "failed to serialize suite: {}", source)
+            }
+            SuiteError::Validation(err) => write!(f, "invalid suite: {}", err),
+            SuiteError::Io {
+                path: Some(path),
+                source,
+            } => write!(
+                f,
+                "failed to read suite file '{}': {}",
+                path.display(),
+                source
+            ),
+            SuiteError::Io { path: None, source } => {
+                write!(f, "failed to read suite: {}", source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SuiteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SuiteError::Validation(err) => Some(err),
+            SuiteError::Io { source, .. } => Some(source),
+            SuiteError::Parse { source, .. } | SuiteError::Serialize { source } => {
+                Some(source.as_ref())
+            }
+        }
+    }
+}
+
+impl From<SuiteValidationError> for SuiteError {
+    fn from(err: SuiteValidationError) -> Self {
+        SuiteError::Validation(err)
+    }
+}
+
+/// Configuration of a resmoke test suite.
+///
+/// The `Default` configuration has an empty `test_kind`, which is only a placeholder: resmoke
+/// will not accept it, so it should be set before the configuration is written.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ResmokeSuiteConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matrix_suite: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub test_kind: String,
+    pub selector: ResmokeSelector,
+    pub executor: ResmokeExecutor,
+}
+
+impl FromStr for ResmokeSuiteConfig {
+    type Err = SuiteError;
+
+    /// Read Resmoke suite configuration from the given string.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let resmoke_suite_config: Result<Self, serde_yaml::Error> = serde_yaml::from_str(s);
+        resmoke_suite_config.map_err(|err| {
+            error!(yaml = s, "Failed to parse yaml for ResmokeSuiteConfig",);
+            SuiteError::from_yaml(None, err)
+        })
+    }
+}
+
+impl TryFrom<&str> for ResmokeSuiteConfig {
+    type Error = SuiteError;
+
+    /// Read Resmoke suite configuration from the given string.
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Self::from_str(s)
+    }
+}
+
+impl TryFrom<String> for ResmokeSuiteConfig {
+    type Error = SuiteError;
+
+    /// Read Resmoke suite configuration from the given string.
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::from_str(&s)
+    }
+}
+
+#[allow(clippy::to_string_trait_impl)]
+impl ToString for ResmokeSuiteConfig {
+    /// Convert this resmoke suite configuration to a string.
+    ///
+    /// Fields are written in the order they are declared and the contents of set fields are
+    /// sorted, so the output is stable between runs and parsing the output and converting it
+    /// back to a string produces the same result.
+    ///
+    /// Panics if the configuration cannot be serialized, use `try_to_string` to handle errors.
+    fn to_string(&self) -> String {
+        self.try_to_string().unwrap()
+    }
+}
+
+impl ResmokeSuiteConfig {
+    /// Read resmoke suite configuration from the given file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to file containing the suite configuration.
+    ///
+    /// # Returns
+    ///
+    /// Resmoke suite configuration contained in the file.
+    pub fn from_file(path: &Path) -> Result<Self, SuiteError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| SuiteError::Io {
+            path: Some(path.to_path_buf()),
+            source,
+        })?;
+
+        Self::from_file_contents(path, &contents)
+    }
+
+    /// Parse resmoke suite configuration that was read from the given file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to file the configuration was read from.
+    /// * `contents` - Contents of the file.
+    ///
+    /// # Returns
+    ///
+    /// Resmoke suite configuration contained in the file.
+    fn from_file_contents(path: &Path, contents: &str) -> Result<Self, SuiteError> {
+        let resmoke_suite_config: Result<Self, Error> = serde_yaml::from_str(contents);
+        resmoke_suite_config.map_err(|source| {
+            error!(
+                file = path.display().to_string(),
+                error = source.to_string().as_str(),
+                "Failed to parse yaml for ResmokeSuiteConfig from file",
+            );
+            SuiteError::from_yaml(Some(path), source)
+        })
+    }
+
+    /// Convert this resmoke suite configuration to a string.
+    ///
+    /// # Returns
+    ///
+    /// YAML representation of the configuration or an error if it could not be serialized.
+    pub fn try_to_string(&self) -> Result<String, SuiteError> {
+        serde_yaml::to_string(self).map_err(|err| SuiteError::Serialize {
+            source: Box::new(err),
+        })
+    }
+
+    /// Convert this resmoke suite configuration to a string with a leading comment block.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - Text to add as a comment before the configuration, each line of the text
+    ///   will be a separate comment line.
+    ///
+    /// # Returns
+    ///
+    /// YAML representation of the configuration or an error if it could not be serialized.
+    pub fn to_string_with_header(&self, header: &str) -> Result<String, SuiteError> {
+        let comments: String = header
+            .lines()
+            .map(|line| {
+                if line.is_empty() {
+                    "#\n".to_string()
+                } else {
+                    format!("# {}\n", line)
+                }
+            })
+            .collect();
+        Ok(format!("{}{}", comments, self.try_to_string()?))
+    }
+
+    /// Build the arguments to pass to resmoke to run this suite.
+    ///
+    /// resmoke reads the selector and executor from the suite file, so the suite itself does
+    /// not need any additional flags.
+    ///
+    /// This is for running a suite locally. Generated evergreen tasks do not use it: their
+    /// "run generated tests" function adds `run --suites=...` itself and is only given the extra
+    /// arguments, which `ResmokeGenParams` builds.
+    ///
+    /// # Arguments
+    ///
+    /// * `suite_file` - Path to the file this suite is written to.
+    /// * `extra_args` - Additional arguments to pass to resmoke, added after the suite.
+    ///
+    /// # Returns
+    ///
+    /// Arguments to invoke resmoke with, starting with the `run` subcommand.
+    pub fn run_command(&self, suite_file: &str, extra_args: &[String]) -> Vec<String> {
+        let mut args = vec!["run".to_string(), format!("--suites={}", suite_file)];
+        args.extend(extra_args.iter().cloned());
+        args
+    }
+
+    /// Convert this resmoke suite configuration to a string, noting why tests were excluded.
+    ///
+    /// serde_yaml cannot emit comments, so the configuration is serialized as usual and then a
+    /// trailing comment is added to each `exclude_files` entry with a reason, e.g.
+    /// `- jstests/core/x.js  # excluded: flaky BF-1234`. Any newlines in a reason are replaced
+    /// with spaces so that the comment stays on one line.
+    ///
+    /// # Arguments
+    ///
+    /// * `reasons` - Map of excluded files to the reason they were excluded.
+    ///
+    /// # Returns
+    ///
+    /// YAML representation of the configuration or an error if it could not be serialized.
+    pub fn to_string_with_exclude_reasons(
+        &self,
+        reasons: &BTreeMap<String, String>,
+    ) -> Result<String, SuiteError> {
+        let contents = self.try_to_string()?;
+        let mut in_selector = false;
+        let mut exclude_item_prefix: Option<String> = None;
+        let lines: Vec<String> = contents
+            .lines()
+            .map(|line| {
+                if let Some(prefix) = &exclude_item_prefix {
+                    if let Some(item) = line.strip_prefix(prefix.as_str()) {
+                        let excluded_file: Option<String> = serde_yaml::from_str(item).ok();
+                        if let Some(reason) = excluded_file.and_then(|f| reasons.get(&f)) {
+                            return format!(
+                                "{}  # excluded: {}",
+                                line,
+                                reason.replace(['\r', '\n'], " ")
+                            );
+                        }
+                        return line.to_string();
+                    }
+                    exclude_item_prefix = None;
+                }
+
+                if !line.starts_with(' ') {
+                    in_selector = line == "selector:";
+                } else if in_selector {
+                    let indent = &line[..line.len() - line.trim_start().len()];
+                    if line.trim_start() == "exclude_files:" {
+                        exclude_item_prefix = Some(format!("{}- ", indent));
+                    }
+                }
+                line.to_string()
+            })
+            .collect();
+        Ok(format!("{}\n", lines.join("\n")))
+    }
+
+    /// Convert this resmoke suite configuration to a JSON string.
+    ///
+    /// Like the YAML representation, fields are written in a stable order.
+    ///
+    /// # Returns
+    ///
+    /// JSON representation of the configuration or an error if it could not be serialized.
+    pub fn to_json(&self) -> Result<String, SuiteError> {
+        serde_json::to_string_pretty(self).map_err(|err| SuiteError::Serialize {
+            source: Box::new(err),
+        })
+    }
+
+    /// Read Resmoke suite configuration from the given JSON string.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - JSON contents of the suite configuration.
+    ///
+    /// # Returns
+    ///
+    /// The parsed resmoke suite configuration.
+    pub fn from_json(s: &str) -> Result<Self, SuiteError> {
+        let resmoke_suite_config: Result<Self, serde_json::Error> = serde_json::from_str(s);
+        resmoke_suite_config.map_err(|err| {
+            error!(json = s, "Failed to parse json for ResmokeSuiteConfig",);
+            SuiteError::Parse {
+                path: None,
+                line: Some(err.line()),
+                column: Some(err.column()),
+                source: Box::new(err),
+            }
+        })
+    }
+
+    /// Read Resmoke suite configuration from the given string, optionally validating it.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - YAML contents of the suite configuration.
+    /// * `validate` - If true, return an error if the configuration fails validation.
+    ///
+    /// # Returns
+    ///
+    /// The parsed resmoke suite configuration.
+    pub fn parse(s: &str, validate: bool) -> Result<Self, SuiteError> {
+        let config = Self::from_str(s)?;
+        if validate {
+            config.validate()?;
+        }
+        Ok(config)
+    }
+
+    /// Read Resmoke suite configuration from the given string, rejecting unknown top-level keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - YAML contents of the suite configuration.
+    ///
+    /// # Returns
+    ///
+    /// The parsed resmoke suite configuration or an error naming any unknown keys.
+    pub fn from_str_strict(s: &str) -> Result<Self, SuiteError> {
+        let contents: Value =
+            serde_yaml::from_str(s).map_err(|err| SuiteError::from_yaml(None, err))?;
+        if let Some(mapping) = contents.as_mapping() {
+            let unknown_keys: Vec<String> = mapping
+                .keys()
+                .map(|k| {
+                    k.as_str()
+                        .map(|k| k.to_string())
+                        .unwrap_or_else(|| format!("{:?}", k))
+                })
+                .filter(|k| !KNOWN_SUITE_KEYS.contains(&k.as_str()))
+                .collect();
+            if !unknown_keys.is_empty() {
+                return Err(SuiteValidationError::UnknownKeys(unknown_keys).into());
+            }
+        }
+
+        Self::from_str(s)
+    }
+
+    /// Check that this configuration is something resmoke will accept.
+    ///
+    /// This checks that conflicting tag selectors are not used together and that any grouping
+    /// settings are usable.
+    ///
+    /// Lints of the configuration, such as overly broad `*_with_any_tags` lists or roots that
+    /// are also excluded, are logged as warnings using `DEFAULT_MAX_ANY_TAGS` but do not fail
+    /// validation.
+    ///
+    /// # Returns
+    ///
+    /// An error describing the first problem found in the configuration.
+    pub fn validate(&self) -> Result<(), SuiteValidationError> {
+        self.validate_with_max_any_tags(DEFAULT_MAX_ANY_TAGS)
+    }
+
+    /// Check that this configuration is something resmoke will accept, linting the
+    /// `*_with_any_tags` lists against the given maximum.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_any_tags` - Maximum number of tags an `*_with_any_tags` list can have before a
+    ///   warning is logged.
+    ///
+    /// # Returns
+    ///
+    /// An error describing the first problem found in the configuration.
+    pub fn validate_with_max_any_tags(
+        &self,
+        max_any_tags: usize,
+    ) -> Result<(), SuiteValidationError> {
+        for lint in self.lint(max_any_tags) {
+            warn!(
+                description = self.description.as_deref().unwrap_or(""),
+                lint = lint.to_string().as_str(),
+                "Resmoke suite configuration looks misconfigured",
+            );
+        }
+
+        let selector = &self.selector;
+        if selector.include_tags.is_some() && selector.exclude_tags.is_some() {
+            return Err(SuiteValidationError::ConflictingTagExpressions);
+        }
+
+        if selector.group_size == Some(0) {
+            return Err(SuiteValidationError::InvalidGroupSize);
+        }
+
+        if let Some(multiplier) = selector.group_count_multiplier {
+            if !multiplier.is_finite() || multiplier <= 0.0 {
+                return Err(SuiteValidationError::InvalidGroupCountMultiplier(
+                    multiplier,
+                ));
+            }
+        }
+
+        if let (Some(include_tags), Some(exclude_tags)) = (
+            &selector.include_with_any_tags,
+            &selector.exclude_with_any_tags,
+        ) {
+            let mut overlap: Vec<String> = include_tags
+                .iter()
+                .filter(|t| exclude_tags.contains(*t))
+                .cloned()
+                .collect();
+            if !overlap.is_empty() {
+                overlap.sort();
+                overlap.dedup();
+                return Err(SuiteValidationError::OverlappingAnyTags(overlap));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find problems in this configuration that resmoke accepts but that suggest it is
+    /// misconfigured.
+    ///
+    /// Roots that are also listed in `exclude_files` are only found by comparing exact paths,
+    /// so a root that is excluded by a glob pattern (or a glob root overlapping an exclusion)
+    /// is not reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_any_tags` - Maximum number of tags `include_with_any_tags` and
+    ///   `exclude_with_any_tags` can each have.
+    ///
+    /// # Returns
+    ///
+    /// The lints found in the configuration.
+    pub fn lint(&self, max_any_tags: usize) -> Vec<SuiteLint> {
+        let selector = &self.selector;
+        let any_tag_counts = [
+            (
+                "include_with_any_tags",
+                selector.include_with_any_tags.as_ref().map(|t| t.len()),
+            ),
+            (
+                "exclude_with_any_tags",
+                selector.exclude_with_any_tags.as_ref().map(|t| t.len()),
+            ),
+        ];
+
+        let mut lints: Vec<SuiteLint> = any_tag_counts
+            .iter()
+            .filter_map(|(field, count)| match count {
+                Some(count) if *count > max_any_tags => Some(SuiteLint::TooManyAnyTags {
+                    field,
+                    count: *count,
+                    max: max_any_tags,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        if let (Some(TestRoot::Roots { roots }), Some(exclude_files)) =
+            (&selector.test_root, &selector.exclude_files)
+        {
+            let mut overlap: Vec<String> = roots
+                .iter()
+                .filter(|r| exclude_files.contains(*r))
+                .cloned()
+                .collect();
+            if !overlap.is_empty() {
+                overlap.sort();
+                overlap.dedup();
+                lints.push(SuiteLint::ExcludedRoots(overlap));
+            }
+        }
+
+        lints
+    }
+
+    /// Create a copy of this configuration that can be run directly as a generated suite.
+    ///
+    /// Generated suites should never be matrix suites, so `matrix_suite` is cleared.
+    fn as_generated_suite(&self) -> Self {
+        let mut config = self.clone();
+        if config.matrix_suite == Some(true) {
+            warn!(
+                description = config.description.as_deref().unwrap_or(""),
+                "Clearing 'matrix_suite' from generated suite configuration",
+            );
+            config.matrix_suite = None;
+        }
+        config
+    }
+
+    /// Create a new resmoke suite configuration based on this one but running certain tests.
+    ///
+    /// Any `include_files` on the original selector are carried through unchanged. Resmoke
+    /// applies `include_files` as a filter on the tests matched by the roots, so the generated
+    /// suite will run the intersection of `run_tests` and the original `include_files`.
+    ///
+    /// When `run_tests` is provided, any single `test` on the original selector is replaced by
+    /// the new roots.
+    ///
+    /// Selectors that set neither `roots` nor `test` and rely entirely on `include_files` are
+    /// supported. When only `exclude_tests` is provided, no roots are added to such a selector;
+    /// its `include_files` are preserved and the tests are appended to `exclude_files`.
+    ///
+    /// Generated suites are run directly by resmoke, so if this configuration is a matrix
+    /// suite, `matrix_suite` will be cleared on the new configuration and a warning logged.
+    ///
+    /// # Arguments
+    ///
+    /// * `run_tests` - When provided, the new configuration should only run these tests.
+    ///   Duplicate tests are only added once, in the order they were first seen.
+    /// * `exclude_tests` - When provided, the new configuration should exclude these tests.
+    ///   Tests that are already excluded will not be added again.
+    ///
+    /// Path separators in the provided tests are normalized to `/` and any leading `./` removed.
+    ///
+    /// For compatibility, an empty `run_tests` list is accepted and produces a configuration
+    /// with empty roots; use `try_with_new_tests` to reject it instead.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration with a selector based on provided parameters.
+    pub fn with_new_tests(
+        &self,
+        run_tests: Option<&[String]>,
+        exclude_tests: Option<&[String]>,
+    ) -> Self {
+        self.with_new_tests_iter(run_tests.map(|t| t.iter()), exclude_tests.map(|t| t.iter()))
+    }
+
+    /// Create a new resmoke suite configuration based on this one but running certain tests.
+    ///
+    /// This behaves the same as `with_new_tests`, but accepts any iterator of test names so
+    /// the tests do not need to be collected first. The two lists can be different types of
+    /// iterator; an argument passed as `None` needs its type given, e.g. `None::<&[String]>`.
+    ///
+    /// # Arguments
+    ///
+    /// * `run_tests` - When provided, the new configuration should only run these tests.
+    /// * `exclude_tests` - When provided, the new configuration should exclude these tests.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration with a selector based on provided parameters.
+    pub fn with_new_tests_iter<R, E>(&self, run_tests: Option<R>, exclude_tests: Option<E>) -> Self
+    where
+        R: IntoIterator,
+        R::Item: AsRef<str>,
+        E: IntoIterator,
+        E::Item: AsRef<str>,
+    {
+        let mut config = self.as_generated_suite();
+        let mut updated_selector = self.selector.clone();
+        if let Some(exclude_tests) = exclude_tests {
+            // The test source (`roots`, `test` or only `include_files`) is left untouched, so
+            // selectors without any roots keep selecting tests through `include_files`.
+            let mut seen = HashSet::new();
+            let mut files_to_exclude = updated_selector.exclude_files.take().unwrap_or_default();
+            files_to_exclude.retain(|s| seen.insert(s.clone()));
+            for test in exclude_tests {
+                let test = normalize_test_path(test.as_ref());
+                if seen.insert(test.clone()) {
+                    files_to_exclude.push(test);
+                }
+            }
+            updated_selector.exclude_files = Some(files_to_exclude);
+        } else if let Some(run_tests) = run_tests {
+            updated_selector.exclude_files = None;
+            updated_selector.test = None;
+            let mut seen = HashSet::new();
+            updated_selector.test_root = Some(TestRoot::Roots {
+                roots: run_tests
+                    .into_iter()
+                    .map(|s| normalize_test_path(s.as_ref()))
+                    .filter(|test| seen.insert(test.clone()))
+                    .collect(),
+            });
+        }
+
+        config.selector = updated_selector;
+        config
+    }
+
+    /// Split this configuration into one sub-suite configuration per list of tests.
+    ///
+    /// Each sub-suite is created with `with_new_tests` and described with
+    /// `describe_sub_suite`.
+    ///
+    /// # Arguments
+    ///
+    /// * `test_lists` - Lists of tests each sub-suite should run.
+    ///
+    /// # Returns
+    ///
+    /// A resmoke configuration for each provided list of tests.
+    pub fn split_into(&self, test_lists: &[Vec<String>]) -> Vec<Self> {
+        self.split_iter(test_lists).collect()
+    }
+
+    /// Split this configuration into sub-suites that list their tests as roots, clearing
+    /// `include_files`.
+    ///
+    /// This is for suites that select their tests only through `include_files`. Such a suite has
+    /// no roots to divide, so each sub-suite instead gets one of the given lists as its roots.
+    /// Unlike `split_into`, `include_files` is removed from every sub-suite: the roots now
+    /// enumerate the selection, so the globs no longer need to be applied. The test lists must
+    /// therefore already be the tests `include_files` selects, e.g. from resmoke test
+    /// discovery, as any other test in them will now be run.
+    ///
+    /// # Arguments
+    ///
+    /// * `test_lists` - Lists of already selected tests each sub-suite should run.
+    ///
+    /// # Returns
+    ///
+    /// A resmoke configuration for each provided list of tests.
+    pub fn split_into_roots(&self, test_lists: &[Vec<String>]) -> Vec<Self> {
+        test_lists
+            .iter()
+            .enumerate()
+            .map(|(index, tests)| self.with_tests_as_roots(tests).describe_sub_suite(index))
+            .collect()
+    }
+
+    /// Create a new resmoke suite configuration running exactly the given tests as its roots.
+    ///
+    /// This is the same as `with_new_tests` with `run_tests`, except that `include_files` is
+    /// cleared instead of being carried through, see `split_into_roots`.
+    ///
+    /// # Arguments
+    ///
+    /// * `run_tests` - Already selected tests the new configuration should run.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration with the given tests as its roots and no `include_files`.
+    pub fn with_tests_as_roots(&self, run_tests: &[String]) -> Self {
+        let mut config = self.with_new_tests(Some(run_tests), None);
+        config.selector.include_files = None;
+        config
+    }
+
+    /// Lazily split this configuration into one sub-suite configuration per list of tests.
+    ///
+    /// This creates the same sub-suites as `split_into`, but each one is only built when the
+    /// iterator reaches it, so they do not all need to be held in memory at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `test_lists` - Lists of tests each sub-suite should run.
+    ///
+    /// # Returns
+    ///
+    /// Iterator over a resmoke configuration for each provided list of tests.
+    pub fn split_iter<'a>(
+        &'a self,
+        test_lists: &'a [Vec<String>],
+    ) -> impl Iterator<Item = Self> + 'a {
+        test_lists.iter().enumerate().map(move |(index, tests)| {
+            self.with_new_tests(Some(tests), None)
+                .describe_sub_suite(index)
+        })
+    }
+
+    /// Split this configuration into one sub-suite configuration per list of tests, applying
+    /// the given transform to each sub-suite.
+    ///
+    /// Sub-suites are created as with `split_into` and then passed to `transform` along with
+    /// their index. If `transform` returns an error, or the transformed sub-suite configuration
+    /// fails `validate`, the error is returned. A sub-suite that is invalid because this
+    /// configuration is invalid is reported too, unless `transform` fixes it.
+    ///
+    /// # Arguments
+    ///
+    /// * `test_lists` - Lists of tests each sub-suite should run.
+    /// * `transform` - Function to modify the sub-suite at the given index.
+    ///
+    /// # Returns
+    ///
+    /// A resmoke configuration for each provided list of tests or the first error encountered.
+    pub fn split_with<F>(&self, test_lists: &[Vec<String>], mut transform: F) -> Result<Vec<Self>>
+    where
+        F: FnMut(usize, &mut Self) -> Result<()>,
+    {
+        self.split_into(test_lists)
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut config)| {
+                transform(index, &mut config)?;
+                if let Err(err) = config.validate() {
+                    bail!("Sub-suite {} is invalid after transform: {}", index, err);
+                }
+                Ok(config)
+            })
+            .collect()
+    }
+
+    /// Create a new resmoke suite configuration with the given description.
+    ///
+    /// # Arguments
+    ///
+    /// * `desc` - Description of the new configuration.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration with the given description.
+    pub fn with_description(&self, desc: &str) -> Self {
+        let mut config = self.clone();
+        config.description = Some(desc.to_string());
+        config
+    }
+
+    /// Create a new resmoke suite configuration based on this one that also excludes tests
+    /// matching the given glob patterns.
+    ///
+    /// Each pattern is validated before being added to `exclude_files`, so malformed patterns
+    /// are reported here rather than by resmoke.
+    ///
+    /// # Arguments
+    ///
+    /// * `globs` - Glob patterns of tests to exclude.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration excluding the given patterns, or an error if any pattern is
+    /// malformed.
+    pub fn with_exclude_globs(&self, globs: &[String]) -> Result<Self> {
+        for glob in globs {
+            if let Err(err) = glob::Pattern::new(glob) {
+                bail!("Invalid glob pattern '{}': {}", glob, err);
+            }
+        }
+
+        Ok(self.with_new_tests(None, Some(globs)))
+    }
+
+    /// Get the root tests declared by this configuration.
+    ///
+    /// # Returns
+    ///
+    /// The inline roots (a single `test` is treated as one inline root), the path to the root
+    /// file, or `RootTests::Unspecified` if neither is set.
+    pub fn root_tests(&self) -> RootTests<'_> {
+        match (&self.selector.test_root, &self.selector.test) {
+            (Some(TestRoot::Roots { roots }), _) => RootTests::Inline(roots),
+            (Some(TestRoot::Root { root }), _) => RootTests::File(root),
+            (None, Some(test)) => RootTests::Inline(std::slice::from_ref(test)),
+            (None, None) => RootTests::Unspecified,
+        }
+    }
+
+    /// Create a new resmoke suite configuration with the given test kind.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Test kind of the new configuration.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration with the given test kind.
+    pub fn with_test_kind(&self, kind: &str) -> Self {
+        let mut config = self.clone();
+        config.test_kind = kind.to_string();
+        config
+    }
+
+    /// Merge the description of an override suite into this configuration's description.
+    ///
+    /// If only one of the descriptions is present, it is used. If both are present, the
+    /// override description is appended to this configuration's description on a new line.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Description of the override suite.
+    pub fn merge_description(&mut self, other: Option<&str>) {
+        if let Some(other) = other {
+            self.description = Some(match &self.description {
+                Some(base) => format!("{}\n{}", base, other),
+                None => other.to_string(),
+            });
+        }
+    }
+
+    /// Create a new resmoke suite configuration recording the base suite it descended from.
+    ///
+    /// The origin is stored on the first line of the description as `[origin: <base_name>]`,
+    /// so it survives serialization and can be read back with `origin`. Any origin already
+    /// recorded is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_name` - Name of the base suite the configuration descended from.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration recording the given origin.
+    pub fn with_origin(&self, base_name: &str) -> Self {
+        let stamp = format!("{}{}{}", ORIGIN_PREFIX, base_name, ORIGIN_SUFFIX);
+        let desc = match self.description_without_origin() {
+            Some(desc) => format!("{}\n{}", stamp, desc),
+            None => stamp,
+        };
+        self.with_description(&desc)
+    }
+
+    /// Get the name of the base suite this configuration descended from.
+    ///
+    /// # Returns
+    ///
+    /// Name of the base suite if one was recorded with `with_origin`.
+    pub fn origin(&self) -> Option<&str> {
+        let first_line = self.description.as_deref()?.lines().next()?;
+        let rest = first_line.strip_prefix(ORIGIN_PREFIX)?;
+        rest.find(ORIGIN_SUFFIX).map(|end| &rest[..end])
+    }
+
+    /// Get the description of this configuration without any recorded origin.
+    ///
+    /// # Returns
+    ///
+    /// Description following the recorded origin, or the whole description if none was recorded.
+    fn description_without_origin(&self) -> Option<&str> {
+        let desc = self.description.as_deref()?;
+        if self.origin().is_none() {
+            return Some(desc);
+        }
+        desc.split_once('\n').map(|(_, rest)| rest)
+    }
+
+    /// Create a new resmoke suite configuration describing it as a sub-suite of this one.
+    ///
+    /// The description will be `"<base> (subtask N)"`, or `"subtask N"` if this configuration
+    /// has no description.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the sub-suite.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration with a description identifying the sub-suite.
+    pub fn describe_sub_suite(&self, index: usize) -> Self {
+        let desc = match &self.description {
+            Some(base) => format!("{} (subtask {})", base, index),
+            None => format!("subtask {}", index),
+        };
+        self.with_description(&desc)
+    }
+
+    /// Create a new resmoke suite configuration with the `group_size` scaled by the given factor.
+    ///
+    /// The scaled group size is rounded up and will be at least 1. If no `group_size` is set,
+    /// it will remain unset. The `group_count_multiplier` is left as-is, so resmoke will still
+    /// apply it to the number of groups created from the scaled group size.
+    ///
+    /// # Arguments
+    ///
+    /// * `factor` - Factor to scale the group size by.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration with the scaled group size.
+    pub fn scale_group_size(&self, factor: f64) -> Self {
+        let mut config = self.clone();
+        config.selector.group_size = self
+            .selector
+            .group_size
+            .map(|group_size| ((group_size as f64 * factor).ceil() as usize).max(1));
+        config
+    }
+
+    /// Create a new resmoke suite configuration that also selects tests with the given tag.
+    ///
+    /// The tag is appended to any existing `include_with_any_tags`, unless it is already
+    /// present.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - Tag to add to `include_with_any_tags`.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration with the tag added.
+    pub fn add_include_with_any_tag(&self, tag: &str) -> Self {
+        let mut config = self.clone();
+        let tags = config
+            .selector
+            .include_with_any_tags
+            .get_or_insert_with(Vec::new);
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+        config
+    }
+
+    /// Create a new resmoke suite configuration that also excludes tests with any of the
+    /// given tags.
+    ///
+    /// `exclude_with_any_tags` is a set, so the order of the tags is not preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - Tags to add to `exclude_with_any_tags`.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration excluding the union of the existing and given tags.
+    pub fn exclude_tags_union(&self, tags: &HashSet<String>) -> Self {
+        let mut config = self.clone();
+        config
+            .selector
+            .exclude_with_any_tags
+            .get_or_insert_with(HashSet::new)
+            .extend(tags.iter().cloned());
+        config
+    }
+
+    /// Split this configuration into a suite for tests with the given tag and a suite for all
+    /// other tests.
+    ///
+    /// This can be used to run resource heavy tests (e.g. `requires_sharding`) in a separate
+    /// sub-suite from the rest of the tests. The tag is required on top of the existing tag
+    /// filters of the tagged half, so every test selected by this configuration is selected by
+    /// exactly one of the halves. Since resmoke does not accept `include_tags` together with
+    /// `exclude_tags`, an `exclude_tags` expression is folded into `include_tags` as a `$not`
+    /// on the tagged half.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - Tag to split the tests on.
+    ///
+    /// # Returns
+    ///
+    /// A configuration selecting only tests with the tag and a configuration excluding them.
+    pub fn split_by_tag(&self, tag: &str) -> (Self, Self) {
+        let mut tagged = self.clone();
+        let selector = &mut tagged.selector;
+        if let Some(exclude_tags) = selector.exclude_tags.take() {
+            let not_excluded = TagExpression::Not {
+                not: Box::new(exclude_tags),
+            };
+            selector.include_tags = Some(match selector.include_tags.take() {
+                Some(include_tags) => include_tags.and(not_excluded),
+                None => not_excluded,
+            });
+        }
+        selector.add_required_tag(tag);
+
+        (tagged, self.exclude_tags_union(&hashset! {tag.to_string()}))
+    }
+
+    /// Compute a fingerprint of this configuration for caching.
+    ///
+    /// The fingerprint is the SHA-256 hash of a canonical serialization of the configuration
+    /// with all mapping keys sorted, so configurations that differ only in field ordering
+    /// have the same fingerprint.
+    ///
+    /// # Returns
+    ///
+    /// Hex encoded SHA-256 hash of the configuration or an error if it could not be serialized.
+    pub fn fingerprint(&self) -> Result<String, SuiteError> {
+        let to_serialize_error = |err: serde_yaml::Error| SuiteError::Serialize {
+            source: Box::new(err),
+        };
+        let value = serde_yaml::to_value(self).map_err(to_serialize_error)?;
+        let canonical = canonicalize_value(&value)
+            .and_then(|value| serde_yaml::to_string(&value))
+            .map_err(to_serialize_error)?;
+        Ok(Sha256::digest(canonical.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect())
+    }
+
+    /// Create a suite configuration that repeatedly runs the changed tests selected by this one.
+    ///
+    /// Changed tests that this configuration's roots and file filters would not select are
+    /// dropped. The tags of the changed tests are not known here, so the tag filters are kept on
+    /// the new configuration for resmoke to apply. Since resmoke suite files cannot express
+    /// repetition, the repeat count is returned alongside the configuration for the task
+    /// builder to pass to resmoke with `BurnInSuite::resmoke_args`.
+    ///
+    /// Which tests a `root` file selects is not known without reading it, so configurations
+    /// reading their roots from a file are rejected. Resolve the file with
+    /// `TestRoot::resolve_roots` first.
+    ///
+    /// # Arguments
+    ///
+    /// * `changed_tests` - Tests touched by the change being tested.
+    /// * `repeat` - Number of times each test should be run, at least once.
+    ///
+    /// # Returns
+    ///
+    /// The configuration running the selected changed tests and the number of times to run them,
+    /// `None` if none of the changed tests are selected, or an error if the roots come from a
+    /// root file.
+    pub fn for_burn_in(
+        &self,
+        changed_tests: &[String],
+        repeat: usize,
+    ) -> Result<Option<BurnInSuite>, SuiteValidationError> {
+        self.check_inline_roots()?;
+        let selected_tests: Vec<&String> = changed_tests
+            .iter()
+            .filter(|test| self.selector.selects_path(test))
+            .collect();
+        if selected_tests.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(BurnInSuite {
+            config: self.with_new_tests_iter(Some(selected_tests), None::<&[String]>),
+            repeat: repeat.max(1),
+        }))
+    }
+
+    /// Create a new resmoke suite configuration with its exclusions applied to its roots.
+    ///
+    /// The roots are replaced with the tests from `all_tests` that this configuration selects,
+    /// as determined by `ResmokeSelector::selects_path`, and `exclude_files` is cleared. This is
+    /// for consumers that do not support `exclude_files`. Tag filters and `include_files` are
+    /// left in place. Any single `test` is replaced by the new roots.
+    ///
+    /// Which tests a `root` file selects is not known without reading it, so configurations
+    /// reading their roots from a file are rejected. Resolve the file with
+    /// `TestRoot::resolve_roots` first.
+    ///
+    /// # Arguments
+    ///
+    /// * `all_tests` - Every test that could be selected, e.g. all tests in the repository.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration with the selected tests as its roots, in the order of
+    /// `all_tests` with duplicates removed, or an error if the roots come from a root file.
+    pub fn materialize_excludes(&self, all_tests: &[String]) -> Result<Self, SuiteValidationError> {
+        self.check_inline_roots()?;
+        let mut seen = HashSet::new();
+        let roots = all_tests
+            .iter()
+            .filter(|test| self.selector.selects_path(test))
+            .filter(|test| seen.insert(test.as_str()))
+            .cloned()
+            .collect();
+
+        let mut config = self.clone();
+        config.selector.test_root = Some(TestRoot::Roots { roots });
+        config.selector.test = None;
+        config.selector.exclude_files = None;
+        Ok(config)
+    }
+
+    /// Check that the roots of this configuration are not read from a root file.
+    ///
+    /// # Returns
+    ///
+    /// An error naming the root file if the roots are read from one.
+    fn check_inline_roots(&self) -> Result<(), SuiteValidationError> {
+        match &self.selector.test_root {
+            Some(TestRoot::Root { root }) => {
+                Err(SuiteValidationError::UnresolvedRootFile(root.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Create a new resmoke suite configuration with its paths sorted alphabetically.
+    ///
+    /// Inline `roots` and `exclude_files` are sorted so the generated file does not depend on
+    /// the order tests were distributed in. resmoke may run tests in the order of their roots,
+    /// so this is only used when requested.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration with sorted `roots` and `exclude_files`.
+    pub fn with_sorted_paths(&self) -> Self {
+        let mut config = self.clone();
+        if let Some(TestRoot::Roots { roots }) = &mut config.selector.test_root {
+            roots.sort();
+        }
+        if let Some(exclude_files) = &mut config.selector.exclude_files {
+            exclude_files.sort();
+        }
+        config
+    }
+
+    /// Scale the size of the fixture down with the number of tests this suite runs.
+    ///
+    /// Sharded cluster fixtures have their `num_shards` scaled, other fixtures have their
+    /// `num_nodes` scaled. The fixture never grows past the size set in the original suite, even
+    /// if the policy would use more nodes, since the suite may not support a larger fixture.
+    /// Fixtures that set neither, and suites whose number of tests is not known from an inline
+    /// list of roots, are left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Policy determining how many nodes to use for the number of tests.
+    ///
+    /// # Returns
+    ///
+    /// Error if the fixture configuration could not be read or written.
+    pub fn scale_fixture(&mut self, policy: FixturePolicy) -> Result<()> {
+        let test_count = match self.selector.root_count() {
+            Some(test_count) => test_count,
+            None => return Ok(()),
+        };
+        if let Some(mut fixture) = self.executor.fixture_config()? {
+            let node_count = policy.node_count(test_count);
+            if let Some(num_shards) = fixture.num_shards() {
+                fixture.set_num_shards(node_count.min(num_shards));
+            } else if let Some(num_nodes) = fixture.num_nodes() {
+                fixture.set_num_nodes(node_count.min(num_nodes));
+            } else {
+                return Ok(());
+            }
+            self.executor.set_fixture_config(fixture)?;
+        }
+        Ok(())
+    }
+
+    /// Create an equivalent resmoke suite configuration in a canonical form, so configurations
+    /// that only differ in formatting compare equal.
+    ///
+    /// Selector lists where order has no meaning, `exclude_files`, `include_files` and
+    /// `include_with_any_tags`, are sorted with duplicates removed, and `include_tags` and
+    /// `exclude_tags` are put in canonical form with `TagExpression::canonicalize`. Fields where
+    /// order is significant, such as `roots` and the executor's `hooks`, are left as they are.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration in canonical form.
+    pub fn canonicalize(&self) -> Self {
+        fn sorted_set(items: &mut Option<Vec<String>>) {
+            if let Some(items) = items {
+                items.sort();
+                items.dedup();
+            }
+        }
+
+        let mut config = self.clone();
+        let selector = &mut config.selector;
+        sorted_set(&mut selector.exclude_files);
+        sorted_set(&mut selector.include_files);
+        sorted_set(&mut selector.include_with_any_tags);
+        selector.include_tags = selector
+            .include_tags
+            .as_ref()
+            .map(TagExpression::canonicalize);
+        selector.exclude_tags = selector
+            .exclude_tags
+            .as_ref()
+            .map(TagExpression::canonicalize);
+        config
+    }
+
+    /// Create the configuration for a suite that is not split.
+    ///
+    /// The configuration is validated and put in canonical form as generated sub-suites are,
+    /// but selects exactly the same tests as this configuration.
+    ///
+    /// # Returns
+    ///
+    /// The single configuration to run the suite with, or an error describing the first
+    /// problem found in this configuration.
+    pub fn passthrough(&self) -> Result<Self, SuiteValidationError> {
+        self.validate()?;
+        Ok(self.canonicalize())
+    }
+
+    /// Move the inline roots of this configuration to a separate root file if there are more
+    /// than the given number of them.
+    ///
+    /// The root file is a sibling of the suite file named after the suite file with a
+    /// `_roots.txt` suffix in place of its extension, e.g. the roots for `suite_0.yml` are
+    /// written to `suite_0_roots.txt`. All other fields of the selector are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `suite_path` - Path the suite configuration will be written to.
+    /// * `max_inline_roots` - Maximum number of roots to keep inline.
+    ///
+    /// # Returns
+    ///
+    /// The configuration reading its roots from the root file along with the path and contents
+    /// of the root file, or `None` if the roots do not need to be moved.
+    pub fn with_spilled_roots(
+        &self,
+        suite_path: &Path,
+        max_inline_roots: usize,
+    ) -> Option<SpilledRoots> {
+        let roots = match &self.selector.test_root {
+            Some(TestRoot::Roots { roots }) if roots.len() > max_inline_roots => roots,
+            _ => return None,
+        };
+
+        let stem = suite_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let roots_path = suite_path.with_file_name(format!("{}_roots.txt", stem));
+        let roots_contents: String = roots.iter().map(|root| format!("{}\n", root)).collect();
+
+        let mut config = self.clone();
+        config.selector.test_root = Some(TestRoot::Root {
+            root: normalize_test_path(&roots_path.to_string_lossy()),
+        });
+        Some(SpilledRoots {
+            config,
+            roots_path,
+            roots_contents,
+        })
+    }
+
+    /// Create a new resmoke suite configuration with the given tag file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to tag file to use, or `None` to clear the tag file.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration using the given tag file.
+    pub fn with_tag_file(&self, path: Option<&str>) -> Self {
+        let mut config = self.clone();
+        config.selector.tag_file = path.map(|p| p.to_string());
+        config
+    }
+
+    /// Create a new resmoke suite configuration based on this one that reads its tests from
+    /// the given root file.
+    ///
+    /// Like providing `run_tests` to `with_new_tests`, the root file is expected to list the
+    /// exact tests to run, so any `exclude_files` are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to a file containing the list of tests to run.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration with a selector that reads tests from the given file.
+    pub fn with_root_file(&self, path: &str) -> Self {
+        let mut config = self.as_generated_suite();
+        config.selector.exclude_files = None;
+        config.selector.test = None;
+        config.selector.test_root = Some(TestRoot::Root {
+            root: path.to_string(),
+        });
+        config
+    }
+
+    /// Create a new resmoke suite configuration based on this one but running certain tests,
+    /// rejecting an empty list of tests.
+    ///
+    /// Resmoke treats empty roots as a suite with no tests, which would pass without running
+    /// anything. Otherwise this behaves the same as `with_new_tests`.
+    ///
+    /// # Arguments
+    ///
+    /// * `run_tests` - When provided, the new configuration should only run these tests.
+    /// * `exclude_tests` - When provided, the new configuration should exclude these tests.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration or an error if `run_tests` would be used and is empty.
+    pub fn try_with_new_tests(
+        &self,
+        run_tests: Option<&[String]>,
+        exclude_tests: Option<&[String]>,
+    ) -> Result<Self, SuiteValidationError> {
+        if exclude_tests.is_none() && run_tests.is_some_and(|tests| tests.is_empty()) {
+            return Err(SuiteValidationError::EmptyTestList);
+        }
+        Ok(self.with_new_tests_iter(run_tests.map(|t| t.iter()), exclude_tests.map(|t| t.iter())))
+    }
+
+    /// Create a new resmoke suite configuration based on this one that runs the given tests
+    /// while keeping the original `exclude_files`.
+    ///
+    /// This is useful when the given tests are glob patterns, since resmoke will expand them
+    /// and the original exclusions still need to be applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `run_tests` - Tests the new configuration should run.
+    ///
+    /// # Returns
+    ///
+    /// New resmoke configuration running the given tests with the original exclusions.
+    pub fn with_run_tests_keeping_exclusions(&self, run_tests: &[String]) -> Self {
+        let mut config = self.as_generated_suite();
+        config.selector.test = None;
+        config.selector.test_root = Some(TestRoot::Roots {
+            roots: run_tests.iter().map(|s| normalize_test_path(s)).collect(),
+        });
+        config
+    }
+}
+
+/// Definition of a matrix suite: a base suite with overrides applied on top of it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MatrixSuiteConfig {
+    /// Name of the suite the matrix suite is based on.
+    pub base_suite: String,
+    /// Description of the matrix suite.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Mappings to apply on top of the base suite, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<Value>,
+}
+
+impl FromStr for MatrixSuiteConfig {
+    type Err = SuiteError;
+
+    /// Read matrix suite configuration from the given string.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let matrix_suite_config: Result<Self, serde_yaml::Error> = serde_yaml::from_str(s);
+        matrix_suite_config.map_err(|err| {
+            error!(yaml = s, "Failed to parse yaml for MatrixSuiteConfig",);
+            SuiteError::from_yaml(None, err)
+        })
+    }
+}
+
+impl MatrixSuiteConfig {
+    /// Expand this matrix suite into a concrete suite configuration.
+    ///
+    /// Each override is applied to the base suite in order, the same way resmoke expands
+    /// matrix suites. Within an override:
+    ///
+    /// * `selector` - Combined with the current selector using `ResmokeSelector::overlay`.
+    /// * `executor` - Merged into the current executor, with values from the override winning.
+    /// * `description` - Combined with the current description using `merge_description`.
+    /// * `test_kind` - Replaces the current test kind.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - Configuration of the base suite.
+    ///
+    /// # Returns
+    ///
+    /// Expanded suite configuration or an error if an override could not be applied.
+    pub fn expand(&self, base: &ResmokeSuiteConfig) -> Result<ResmokeSuiteConfig> {
+        let mut config = base.clone();
+        for override_value in &self.overrides {
+            let mapping = match override_value.as_mapping() {
+                Some(mapping) => mapping,
+                None => bail!(
+                    "Override for matrix suite based on '{}' is not a mapping: {:?}",
+                    self.base_suite,
+                    override_value
+                ),
+            };
+
+            for (key, value) in mapping {
+                match key.as_str() {
+                    Some("selector") => {
+                        let selector: ResmokeSelector = serde_yaml::from_value(value.clone())?;
+                        config.selector = config.selector.overlay(&selector);
+                    }
+                    Some("executor") => {
+                        let mut executor = serde_yaml::to_value(&config.executor)?;
+                        merge_values(&mut executor, value);
+                        config.executor = serde_yaml::from_value(executor)?;
+                    }
+                    Some("description") => config.merge_description(value.as_str()),
+                    Some("test_kind") => match value.as_str() {
+                        Some(test_kind) => config.test_kind = test_kind.to_string(),
+                        None => bail!("Override 'test_kind' is not a string: {:?}", value),
+                    },
+                    _ => bail!(
+                        "Unknown key in override for matrix suite based on '{}': {:?}",
+                        self.base_suite,
+                        key
+                    ),
+                }
+            }
+        }
+
+        config.merge_description(self.description.as_deref());
+        config.matrix_suite = Some(true);
+        Ok(config)
+    }
+}
+
+/// A test along with the tags it is marked with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaggedTest {
+    /// Path to the test.
+    pub path: String,
+    /// Tags the test is marked with.
+    pub tags: HashSet<String>,
+}
+
+/// Partition the given tests based on whether they have the given tag.
+///
+/// # Arguments
+///
+/// * `tests` - Tests to partition.
+/// * `tag` - Tag to partition on.
+///
+/// # Returns
+///
+/// The paths of tests with the tag and the paths of the remaining tests without it, in the
+/// given order.
+pub fn partition_by_tag(tests: &[TaggedTest], tag: &str) -> (Vec<String>, Vec<String>) {
+    let (tagged, untagged): (Vec<&TaggedTest>, Vec<&TaggedTest>) =
+        tests.iter().partition(|test| test.tags.contains(tag));
+    (
+        tagged.into_iter().map(|test| test.path.clone()).collect(),
+        untagged.into_iter().map(|test| test.path.clone()).collect(),
+    )
+}
+
+/// Tests partitioned by a list of tags.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagPartition {
+    /// Each tag with the paths of the tests assigned to it, in the order the tags were given.
+    pub partitions: Vec<(String, Vec<String>)>,
+    /// Paths of the tests that have none of the tags.
+    pub remainder: Vec<String>,
+}
+
+impl TagPartition {
+    /// Get the lists of tests of every partition, followed by the remainder if it has any tests.
+    ///
+    /// Together the lists contain every partitioned test exactly once, so they can be passed to
+    /// `ResmokeSuiteConfig::split_into` without dropping any tests.
+    ///
+    /// # Returns
+    ///
+    /// The non-empty lists of tests in this partition.
+    pub fn test_lists(&self) -> Vec<Vec<String>> {
+        self.partitions
+            .iter()
+            .map(|(_, tests)| tests)
+            .chain(std::iter::once(&self.remainder))
+            .filter(|tests| !tests.is_empty())
+            .cloned()
+            .collect()
+    }
+
+    /// Create a catch-all sub-suite running the tests that matched none of the tags.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Configuration of the suite the tests were partitioned from.
+    ///
+    /// # Returns
+    ///
+    /// A configuration running the remainder tests, or `None` if there are no remainder tests.
+    pub fn remainder_suite(&self, config: &ResmokeSuiteConfig) -> Option<ResmokeSuiteConfig> {
+        if self.remainder.is_empty() {
+            None
+        } else {
+            Some(config.with_new_tests(Some(&self.remainder), None))
+        }
+    }
+}
+
+/// Partition the given tests by the first of the given tags they have.
+///
+/// Every test is assigned to exactly one partition: the partition of the first tag in `tags`
+/// that it has, or the remainder if it has none of them. No test is dropped.
+///
+/// # Arguments
+///
+/// * `tests` - Tests to partition.
+/// * `tags` - Tags to partition on, in order of priority.
+///
+/// # Returns
+///
+/// The tests assigned to each tag and the remaining tests, each in the given order.
+pub fn partition_by_tags(tests: &[TaggedTest], tags: &[&str]) -> TagPartition {
+    let mut partition = TagPartition {
+        partitions: tags.iter().map(|tag| (tag.to_string(), vec![])).collect(),
+        remainder: vec![],
+    };
+    for test in tests {
+        match tags.iter().position(|tag| test.tags.contains(*tag)) {
+            Some(index) => partition.partitions[index].1.push(test.path.clone()),
+            None => partition.remainder.push(test.path.clone()),
+        }
+    }
+    partition
+}
+
+/// Difference between the roots of two suite configurations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SuiteDiff {
+    /// Both suites list their roots inline.
+    Known {
+        /// Roots in the new suite that are not in the old suite, in the new suite's order.
+        added: Vec<String>,
+        /// Roots in the old suite that are not in the new suite, in the old suite's order.
+        removed: Vec<String>,
+    },
+    /// At least one of the suites reads its roots from a file, so the difference is unknown.
+    Unknown,
+}
+
+/// Compute which roots were added and removed between two suite configurations.
+///
+/// A suite using a single `test` is treated as having that test as its only root, and a
+/// suite with no roots is treated as having no roots.
+///
+/// # Arguments
+///
+/// * `old` - Configuration before the change.
+/// * `new` - Configuration after the change.
+///
+/// # Returns
+///
+/// The roots that were added and removed, or `SuiteDiff::Unknown` if either suite reads its
+/// roots from a file.
+pub fn suite_test_diff(old: &ResmokeSuiteConfig, new: &ResmokeSuiteConfig) -> SuiteDiff {
+    fn inline_roots(config: &ResmokeSuiteConfig) -> Option<&[String]> {
+        match config.root_tests() {
+            RootTests::Inline(roots) => Some(roots),
+            RootTests::File(_) => None,
+            RootTests::Unspecified => Some(&[]),
+        }
+    }
+
+    match (inline_roots(old), inline_roots(new)) {
+        (Some(old_roots), Some(new_roots)) => {
+            let old_set: HashSet<&String> = old_roots.iter().collect();
+            let new_set: HashSet<&String> = new_roots.iter().collect();
+            SuiteDiff::Known {
+                added: new_roots
+                    .iter()
+                    .filter(|root| !old_set.contains(root))
+                    .cloned()
+                    .collect(),
+                removed: old_roots
+                    .iter()
+                    .filter(|root| !new_set.contains(root))
+                    .cloned()
+                    .collect(),
+            }
+        }
+        _ => SuiteDiff::Unknown,
+    }
+}
+
+/// Load the resmoke suite configurations in the given files in parallel.
+///
+/// The files are split into one chunk per available CPU and each chunk is parsed on its own
+/// scoped thread. Results are joined back in chunk order, so no thread pool dependency is needed
+/// to keep them in input order.
+///
+/// This is meant for tooling that works on suite files directly. Task generation does not read
+/// suite files itself; it asks resmoke for each suite's configuration through
+/// `TestDiscovery::get_suite_config`, which already resolves suite names and overrides.
+///
+/// # Arguments
+///
+/// * `paths` - Paths to files containing suite configurations.
+///
+/// # Returns
+///
+/// The result of loading each file, in the same order as `paths`.
+pub fn load_suites(paths: &[PathBuf]) -> Vec<Result<ResmokeSuiteConfig, SuiteError>> {
+    if paths.is_empty() {
+        return vec![];
+    }
+
+    let n_threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let chunk_size = paths.len().div_ceil(n_threads);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|path| ResmokeSuiteConfig::from_file(path))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Suite loading thread panicked"))
+            .collect()
+    })
+}
+
+/// Maximum number of suite files `load_suites_async` reads at once.
+const MAX_CONCURRENT_SUITE_READS: usize = 32;
+
+/// Load the resmoke suite configurations in the given files without blocking the async runtime.
+///
+/// The files are read concurrently with tokio, at most `MAX_CONCURRENT_SUITE_READS` at a time
+/// so large lists of suites do not exhaust file handles. Parsing happens once each file has
+/// been read.
+///
+/// # Arguments
+///
+/// * `paths` - Paths to files containing suite configurations.
+///
+/// # Returns
+///
+/// The result of loading each file, in the same order as `paths`.
+pub async fn load_suites_async(paths: &[PathBuf]) -> Vec<Result<ResmokeSuiteConfig, SuiteError>> {
+    stream::iter(paths)
+        .map(|path| async move {
+            match tokio::fs::read_to_string(path).await {
+                Ok(contents) => ResmokeSuiteConfig::from_file_contents(path, &contents),
+                Err(source) => Err(SuiteError::Io {
+                    path: Some(path.to_path_buf()),
+                    source,
+                }),
+            }
+        })
+        .buffered(MAX_CONCURRENT_SUITE_READS)
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryInto;
+
+    use maplit::{btreemap, hashset};
+    use rstest::rstest;
+    use serde_yaml::value::Tag;
+    use tempdir::TempDir;
+
+    use super::*;
+
+    // ResmokeExecutor extra keys tests
+    #[test]
+    fn test_executor_should_preserve_unknown_keys() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+              custom_option:
+                enabled: true
+                values:
+                  - 1
+                  - 2
+        ";
+        let config = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let round_tripped = ResmokeSuiteConfig::from_str(
+            &config
+                .with_new_tests(None, Some(&["jstests/core/a.js".to_string()]))
+                .to_string(),
+        )
+        .unwrap();
+
+        let expected: Value = serde_yaml::from_str("{enabled: true, values: [1, 2]}").unwrap();
+        assert_eq!(config.executor.extra.get("custom_option"), Some(&expected));
+        assert_eq!(round_tripped.executor.extra, config.executor.extra);
+        assert!(round_tripped.executor.fixture.is_some());
+    }
+
+    // ResmokeExecutor config_variables tests
+    #[test]
+    fn test_executor_should_round_trip_config_variables() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              config_variables:
+                num_nodes: 3
+                storage_engine: wiredTiger
+              fixture:
+                class: MyFixture
+        ";
+        let config = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let round_tripped = ResmokeSuiteConfig::from_str(&config.to_string()).unwrap();
+
+        let expected: Value =
+            serde_yaml::from_str("{num_nodes: 3, storage_engine: wiredTiger}").unwrap();
+        assert_eq!(config.executor.config_variables.as_deref(), Some(&expected));
+        assert!(!config.executor.extra.contains_key("config_variables"));
+        assert_eq!(
+            round_tripped.executor.config_variables,
+            config.executor.config_variables
+        );
+    }
+
+    // ResmokeSelector extra keys tests
+    #[test]
+    fn test_selector_should_preserve_unknown_keys_through_with_new_tests() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_files:
+                - jstests/core/a.js
+              made_up_option:
+                - first
+                - second
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let config = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let expected: Value = serde_yaml::from_str("[first, second]").unwrap();
+        assert_eq!(
+            config.selector.extra,
+            btreemap! {"made_up_option".to_string() => expected}
+        );
+
+        let run_tests = vec!["jstests/core/b.js".to_string()];
+        let exclude_tests = vec!["jstests/core/c.js".to_string()];
+        for new_config in [
+            config.with_new_tests(Some(&run_tests), None),
+            config.with_new_tests(None, Some(&exclude_tests)),
+        ]
+        .iter()
+        {
+            let round_tripped = ResmokeSuiteConfig::from_str(&new_config.to_string()).unwrap();
+
+            assert_eq!(round_tripped.selector.extra, config.selector.extra);
+            assert!(round_tripped.selector.test_root.is_some());
+        }
+    }
+
+    // PartialEq tests
+    #[test]
+    fn test_suite_config_should_equal_clone_but_not_modified_version() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_with_any_tags:
+                - requires_sharding
+
+            executor:
+              hooks:
+                - class: ValidateCollections
+              fixture:
+                class: MyFixture
+        ";
+        let config = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let mut modified_selector = config.clone();
+        modified_selector.selector.add_forbidden_tag("slow");
+        let mut modified_executor = config.clone();
+        modified_executor.executor.retain_hooks(|_| false);
+
+        assert_eq!(config, config.clone());
+        assert_eq!(config, ResmokeSuiteConfig::from_str(config_yaml).unwrap());
+        assert_ne!(config, modified_selector);
+        assert_ne!(config, modified_executor);
+        assert_ne!(config, config.with_test_kind("cpp_unit_test"));
+    }
+
+    // Default tests
+    #[test]
+    fn test_default_selector_and_executor_should_serialize_empty() {
+        assert_eq!(
+            serde_yaml::to_value(ResmokeSelector::default()).unwrap(),
+            Value::Mapping(Mapping::new())
+        );
+        assert_eq!(
+            serde_yaml::to_value(ResmokeExecutor::default()).unwrap(),
+            Value::Mapping(Mapping::new())
+        );
+    }
+
+    #[test]
+    fn test_default_suite_config_should_only_serialize_required_fields() {
+        let config = ResmokeSuiteConfig {
+            test_kind: "js_test".to_string(),
+            ..Default::default()
+        };
+
+        let value = serde_yaml::to_value(&config).unwrap();
+
+        let keys: Vec<&str> = value
+            .as_mapping()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str().unwrap())
+            .collect();
+        assert_eq!(keys, vec!["test_kind", "selector", "executor"]);
+        assert_eq!(value["selector"], Value::Mapping(Mapping::new()));
+        assert_eq!(value["executor"], Value::Mapping(Mapping::new()));
+        assert_eq!(ResmokeSuiteConfig::default().test_kind, "");
+    }
+
+    // with_new_tests tests
+    #[test]
+    fn test_with_new_tests_should_support_include_files_only_selectors() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              include_files:
+                - jstests/core/*.js
+              exclude_files:
+                - jstests/core/a.js
+
+            executor:
+              fixture:
+                class: MongoDFixture
+        ";
+        let exclude_test_list = vec!["jstests/core/b.js".to_string()];
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_config = resmoke_suite.with_new_tests(None, Some(&exclude_test_list));
+
+        assert!(new_config.selector.test_root.is_none());
+        assert!(new_config.selector.test.is_none());
+        assert_eq!(
+            new_config.selector.include_files,
+            Some(vec!["jstests/core/*.js".to_string()])
+        );
+        assert_eq!(
+            new_config.selector.exclude_files,
+            Some(vec![
+                "jstests/core/a.js".to_string(),
+                "jstests/core/b.js".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_with_new_tests_can_add_tests_to_exclude_list() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+              exclude_files:
+                - jstests/auth/repl.js
+                - jstests/core/add1.js
+        
+            executor:
+              config:
+                value
+              fixture:
+                class: MyFixture
+                num_nodes: 3
+        ";
+
+        let exclude_test_list = vec!["test0.js".to_string(), "test1.js".to_string()];
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_config = resmoke_suite.with_new_tests(None, Some(&exclude_test_list));
+
+        assert!(new_config.selector.exclude_files.is_some());
+        if let Some(excluded_files) = new_config.selector.exclude_files {
+            for test in exclude_test_list {
+                assert!(excluded_files.contains(&test));
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_new_tests_can_add_tests_to_test_root() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+              exclude_files:
+                - jstests/auth/repl.js
+                - jstests/core/add1.js
+        
+            executor:
+              config:
+                value
+              fixture:
+                class: MyFixture
+                num_nodes: 3
+        ";
+
+        let new_test_list = vec!["test0.js".to_string(), "test1.js".to_string()];
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_config = resmoke_suite.with_new_tests(Some(&new_test_list), None);
+
+        if let Some(TestRoot::Roots { roots: test_roots }) = new_config.selector.test_root {
+            for test in new_test_list {
+                assert!(test_roots.contains(&test));
+            }
+        } else {
+            panic!(
+                "New test root is not expected: {:?}",
+                new_config.selector.test_root
+            );
+        }
+    }
+
+    #[test]
+    fn test_with_new_tests_should_preserve_include_files() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+              include_files:
+                - jstests/auth/basic*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let new_test_list = vec![
+            "jstests/auth/basic0.js".to_string(),
+            "jstests/auth/other.js".to_string(),
+        ];
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_config = resmoke_suite.with_new_tests(Some(&new_test_list), None);
+
+        assert_eq!(
+            new_config.selector.include_files,
+            Some(vec!["jstests/auth/basic*.js".to_string()])
+        );
+        if let Some(TestRoot::Roots { roots: test_roots }) = new_config.selector.test_root {
+            assert_eq!(test_roots, new_test_list);
+        } else {
+            panic!(
+                "New test root is not expected: {:?}",
+                new_config.selector.test_root
+            );
+        }
+    }
+
+    #[test]
+    fn test_with_new_tests_should_dedup_run_tests() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let tests = vec![
+            "test1.js".to_string(),
+            "test0.js".to_string(),
+            "test1.js".to_string(),
+            "dir\\test2.js".to_string(),
+            "dir/test2.js".to_string(),
+        ];
+
+        let new_config = resmoke_suite.with_new_tests(Some(&tests), None);
+
+        assert_eq!(
+            new_config.selector.test_root,
+            Some(TestRoot::Roots {
+                roots: vec![
+                    "test1.js".to_string(),
+                    "test0.js".to_string(),
+                    "dir/test2.js".to_string()
+                ]
+            })
+        );
+        assert_eq!(new_config.to_string().matches("test1.js").count(), 1);
+    }
+
+    // with_new_tests_iter tests
+    #[test]
+    fn test_with_new_tests_iter_should_match_slice_version() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+              exclude_files:
+                - jstests/auth/repl.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let tests = vec![
+            "jstests\\auth\\test0.js".to_string(),
+            "test1.js".to_string(),
+        ];
+        let test_names = ["jstests\\auth\\test0.js", "test1.js"];
+
+        let run_config =
+            resmoke_suite.with_new_tests_iter(Some(test_names.iter()), None::<&[String]>);
+        let exclude_config =
+            resmoke_suite.with_new_tests_iter(None::<&[String]>, Some(test_names.iter()));
+        let unchanged_config =
+            resmoke_suite.with_new_tests_iter(None::<&[String]>, None::<&[String]>);
+        let mixed_config = resmoke_suite.with_new_tests_iter(Some(&tests), Some(test_names));
+
+        assert_eq!(
+            run_config.to_string(),
+            resmoke_suite.with_new_tests(Some(&tests), None).to_string()
+        );
+        assert_eq!(
+            exclude_config.to_string(),
+            resmoke_suite.with_new_tests(None, Some(&tests)).to_string()
+        );
+        assert_eq!(
+            unchanged_config.to_string(),
+            resmoke_suite.with_new_tests(None, None).to_string()
+        );
+        assert_eq!(
+            mixed_config.to_string(),
+            resmoke_suite
+                .with_new_tests(Some(&tests), Some(&tests))
+                .to_string()
+        );
+    }
+
+    // with_run_tests_keeping_exclusions tests
+    #[test]
+    fn test_with_run_tests_keeping_exclusions_should_keep_exclude_files() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/**/*.js
+              exclude_files:
+                - jstests/core/txns/*
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let new_test_list = vec!["jstests/core/*.js".to_string()];
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_config = resmoke_suite.with_run_tests_keeping_exclusions(&new_test_list);
+
+        assert_eq!(
+            new_config.selector.exclude_files,
+            Some(vec!["jstests/core/txns/*".to_string()])
+        );
+        if let Some(TestRoot::Roots { roots: test_roots }) = new_config.selector.test_root {
+            assert_eq!(test_roots, new_test_list);
+        } else {
+            panic!(
+                "New test root is not expected: {:?}",
+                new_config.selector.test_root
+            );
+        }
+    }
+
+    // TagExpression tests
+    #[test]
+    fn test_flat_tag_expression_should_round_trip() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              include_tags: requires_fcv_80
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let round_trip = ResmokeSuiteConfig::from_str(&resmoke_suite.to_string()).unwrap();
+
+        assert_eq!(
+            resmoke_suite.selector.include_tags,
+            Some(TagExpression::Flat("requires_fcv_80".to_string()))
+        );
+        assert_eq!(
+            round_trip.selector.include_tags,
+            resmoke_suite.selector.include_tags
+        );
+    }
+
+    #[test]
+    fn test_nested_tag_expression_should_round_trip() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_tags:
+                $anyOf:
+                  - assumes_standalone_mongod
+                  - $allOf:
+                      - requires_sharding
+                      - $not: requires_fcv_80
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let round_trip = ResmokeSuiteConfig::from_str(&resmoke_suite.to_string()).unwrap();
+
+        assert_eq!(
+            resmoke_suite.selector.exclude_tags,
+            Some(TagExpression::AnyOf {
+                any_of: vec![
+                    TagExpression::Flat("assumes_standalone_mongod".to_string()),
+                    TagExpression::AllOf {
+                        all_of: vec![
+                            TagExpression::Flat("requires_sharding".to_string()),
+                            TagExpression::Not {
+                                not: Box::new(TagExpression::Flat("requires_fcv_80".to_string())),
+                            },
+                        ],
+                    },
+                ],
+            })
+        );
+        assert_eq!(
+            round_trip.selector.exclude_tags,
+            resmoke_suite.selector.exclude_tags
+        );
+    }
+
+    #[test]
+    fn test_add_required_tag_should_combine_with_existing_tags() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              include_tags: tag_0
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        resmoke_suite.selector.add_required_tag("tag_1");
+        resmoke_suite.selector.add_required_tag("tag_2");
+
+        assert_eq!(
+            resmoke_suite.selector.include_tags,
+            Some(TagExpression::AllOf {
+                all_of: vec![
+                    TagExpression::Flat("tag_0".to_string()),
+                    TagExpression::Flat("tag_1".to_string()),
+                    TagExpression::Flat("tag_2".to_string()),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_add_forbidden_tag_with_no_existing_tags() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        resmoke_suite.selector.add_forbidden_tag("tag_0");
+
+        assert_eq!(
+            resmoke_suite.selector.exclude_tags,
+            Some(TagExpression::Flat("tag_0".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_add_forbidden_tag_should_combine_with_existing_tags() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_tags: tag_0
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        resmoke_suite.selector.add_forbidden_tag("tag_1");
+
+        assert_eq!(
+            resmoke_suite.selector.exclude_tags,
+            Some(TagExpression::AnyOf {
+                any_of: vec![
+                    TagExpression::Flat("tag_0".to_string()),
+                    TagExpression::Flat("tag_1".to_string()),
+                ],
+            })
+        );
+    }
+
+    // normalize_paths tests
+    #[rstest]
+    #[case("jstests/core/a.js", "jstests/core/a.js")]
+    #[case("./jstests/core/a.js", "jstests/core/a.js")]
+    #[case(".\\jstests\\core\\a.js", "jstests/core/a.js")]
+    #[case("jstests/./core//a.js", "jstests/core/a.js")]
+    #[case("jstests/auth/../core/*.js", "jstests/core/*.js")]
+    #[case("../other/a.js", "../other/a.js")]
+    #[case("/repo/jstests/core/**/*.js", "jstests/core/**/*.js")]
+    #[case("/elsewhere/jstests/a.js", "/elsewhere/jstests/a.js")]
+    fn test_normalize_paths_should_use_canonical_form(#[case] path: &str, #[case] expected: &str) {
+        let mut selector = ResmokeSelector {
+            test_root: Some(TestRoot::Roots {
+                roots: vec![path.to_string()],
+            }),
+            test: Some(path.to_string()),
+            include_files: Some(vec![path.to_string()]),
+            exclude_files: Some(vec![path.to_string()]),
+            ..Default::default()
+        };
+
+        selector.normalize_paths(Path::new("/repo"));
+
+        assert_eq!(
+            selector.test_root,
+            Some(TestRoot::Roots {
+                roots: vec![expected.to_string()]
+            })
+        );
+        assert_eq!(selector.test.as_deref(), Some(expected));
+        assert_eq!(selector.include_files, Some(vec![expected.to_string()]));
+        assert_eq!(selector.exclude_files, Some(vec![expected.to_string()]));
+    }
+
+    #[test]
+    fn test_normalize_paths_should_normalize_root_file() {
+        let mut selector = ResmokeSelector {
+            test_root: Some(TestRoot::Root {
+                root: "./suites/roots.txt".to_string(),
+            }),
+            ..Default::default()
+        };
+
+        selector.normalize_paths(Path::new("/repo"));
+
+        assert_eq!(
+            selector.test_root,
+            Some(TestRoot::Root {
+                root: "suites/roots.txt".to_string()
+            })
+        );
+    }
+
+    // apply tests
+    #[test]
+    fn test_apply_should_apply_ops_in_sequence() {
+        let mut selector = ResmokeSelector::builder()
+            .test("jstests/core/a.js")
+            .exclude_files(vec!["jstests/core/b.js".to_string()])
+            .build();
+        let ops = vec![
+            SelectorOp::SetRoots(vec!["jstests/core/**/*.js".to_string()]),
+            SelectorOp::AddExclude("jstests/core/c.js".to_string()),
+            SelectorOp::AddExclude("jstests/core/b.js".to_string()),
+            SelectorOp::ExcludeDir("jstests/core/txns/".to_string()),
+            SelectorOp::AddRequiredTag("tag_0".to_string()),
+            SelectorOp::AddRequiredTag("tag_1".to_string()),
+            SelectorOp::AddForbiddenTag("tag_2".to_string()),
+        ];
+
+        for op in ops {
+            selector.apply(op);
+        }
+
+        assert_eq!(selector.test, None);
+        assert_eq!(
+            selector.test_root,
+            Some(TestRoot::Roots {
+                roots: vec!["jstests/core/**/*.js".to_string()]
+            })
+        );
+        assert_eq!(
+            selector.exclude_files,
+            Some(vec![
+                "jstests/core/b.js".to_string(),
+                "jstests/core/c.js".to_string(),
+                "jstests/core/txns/**/*.js".to_string(),
+            ])
+        );
+        let tags: HashSet<String> = hashset! {"tag_0".to_string(), "tag_1".to_string()};
+        assert!(selector.selects("jstests/core/d.js", &tags));
+        assert!(!selector.selects("jstests/core/d.js", &hashset! {"tag_0".to_string()}));
+        assert!(!selector.selects("jstests/core/c.js", &tags));
+        assert!(!selector.selects("jstests/core/txns/e.js", &tags));
+    }
+
+    #[test]
+    fn test_apply_set_root_file_should_replace_roots() {
+        let mut selector = ResmokeSelector::builder()
+            .roots(vec!["jstests/core/a.js".to_string()])
+            .build();
+
+        selector.apply(SelectorOp::SetRootFile("roots.txt".to_string()));
+
+        assert_eq!(
+            selector.test_root,
+            Some(TestRoot::Root {
+                root: "roots.txt".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_selector_ops_should_round_trip_through_json() {
+        let ops = vec![
+            SelectorOp::AddExclude("jstests/core/c.js".to_string()),
+            SelectorOp::SetRoots(vec!["jstests/core/a.js".to_string()]),
+            SelectorOp::AddRequiredTag("tag_0".to_string()),
+        ];
+
+        let json = serde_json::to_string(&ops).unwrap();
+        let replayed: Vec<SelectorOp> = serde_json::from_str(&json).unwrap();
+
+        assert!(json.contains(r#"{"add_exclude":"jstests/core/c.js"}"#));
+        assert_eq!(replayed, ops);
+    }
+
+    // exclude_dir tests
+    #[rstest]
+    #[case("jstests/core/txns")]
+    #[case("jstests/core/txns/")]
+    #[case("jstests\\core\\txns\\")]
+    fn test_exclude_dir_should_add_a_single_glob(#[case] dir: &str) {
+        let mut selector = ResmokeSelector::builder()
+            .roots(vec!["jstests/core/**/*.js".to_string()])
+            .build();
+        selector.exclude_files = Some(vec!["jstests/core/a.js".to_string()]);
+
+        selector.exclude_dir(dir);
+        selector.exclude_dir(dir);
+
+        assert_eq!(
+            selector.exclude_files,
+            Some(vec![
+                "jstests/core/a.js".to_string(),
+                "jstests/core/txns/**/*.js".to_string()
+            ])
+        );
+        assert!(!selector.selects_path("jstests/core/txns/nested/b.js"));
+        assert!(selector.selects_path("jstests/core/b.js"));
+    }
+
+    // validate tests
+    #[test]
+    fn test_validate_should_accept_valid_config() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              include_tags: tag_0
+              include_with_any_tags:
+                - tag_1
+              exclude_with_any_tags:
+                - tag_2
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let resmoke_suite = ResmokeSuiteConfig::parse(config_yaml, true).unwrap();
+
+        assert_eq!(resmoke_suite.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_should_reject_include_and_exclude_tags() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              include_tags: tag_0
+              exclude_tags: tag_1
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        assert_eq!(
+            resmoke_suite.validate(),
+            Err(SuiteValidationError::ConflictingTagExpressions)
+        );
+    }
+
+    #[test]
+    fn test_validate_should_reject_overlapping_any_tags() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              include_with_any_tags:
+                - tag_1
+                - tag_0
+                - tag_2
+              exclude_with_any_tags:
+                - tag_0
+                - tag_1
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        assert_eq!(
+            resmoke_suite.validate(),
+            Err(SuiteValidationError::OverlappingAnyTags(vec![
+                "tag_0".to_string(),
+                "tag_1".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_validate_should_only_lint_excluded_roots() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+                - jstests/core/flaky.js
+                - jstests/core/other.js
+              exclude_files:
+                - jstests/core/flaky.js
+                - jstests/core/o*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        assert_eq!(resmoke_suite.validate(), Ok(()));
+        assert_eq!(
+            resmoke_suite.lint(DEFAULT_MAX_ANY_TAGS),
+            vec![SuiteLint::ExcludedRoots(vec![
+                "jstests/core/flaky.js".to_string()
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_parse_should_only_validate_when_requested() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              include_tags: tag_0
+              exclude_tags: tag_1
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        assert!(ResmokeSuiteConfig::parse(config_yaml, false).is_ok());
+        assert!(ResmokeSuiteConfig::parse(config_yaml, true).is_err());
+    }
+
+    // lint tests
+    fn build_suite_with_any_tags(include_count: usize, exclude_count: usize) -> ResmokeSuiteConfig {
+        let selector = ResmokeSelector::builder()
+            .roots(vec!["jstests/core/*.js".to_string()])
+            .include_with_any_tags((0..include_count).map(|i| format!("in_{}", i)).collect())
+            .exclude_with_any_tags((0..exclude_count).map(|i| format!("ex_{}", i)).collect())
+            .build();
+        ResmokeSuiteConfig {
+            test_kind: "js_test".to_string(),
+            selector,
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(3, 3)]
+    #[case(3, 2)]
+    fn test_lint_should_accept_tags_under_threshold(
+        #[case] include_count: usize,
+        #[case] exclude_count: usize,
+    ) {
+        let config = build_suite_with_any_tags(include_count, exclude_count);
+
+        assert!(config.lint(3).is_empty());
+    }
+
+    #[test]
+    fn test_lint_should_flag_tags_over_threshold() {
+        let config = build_suite_with_any_tags(2, 5);
+
+        assert_eq!(
+            config.lint(3),
+            vec![SuiteLint::TooManyAnyTags {
+                field: "exclude_with_any_tags",
+                count: 5,
+                max: 3,
+            }]
+        );
+        assert_eq!(config.lint(1).len(), 2);
+    }
+
+    #[test]
+    fn test_lint_should_use_default_threshold_in_validate() {
+        let config = build_suite_with_any_tags(0, DEFAULT_MAX_ANY_TAGS + 1);
+
+        assert_eq!(config.lint(DEFAULT_MAX_ANY_TAGS).len(), 1);
+        assert!(config.lint(DEFAULT_MAX_ANY_TAGS + 1).is_empty());
+        assert!(config.validate().is_ok());
+        assert!(config.validate_with_max_any_tags(1).is_ok());
+    }
+
+    // with_root_file tests
+    #[test]
+    fn test_with_root_file_should_replace_inline_roots() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/test0.js
+                - jstests/auth/test1.js
+              exclude_files:
+                - jstests/auth/repl.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_config = resmoke_suite.with_root_file("generated/test_list.txt");
+        let round_trip = ResmokeSuiteConfig::from_str(&new_config.to_string()).unwrap();
+
+        assert!(new_config.selector.exclude_files.is_none());
+        if let Some(TestRoot::Root { root }) = round_trip.selector.test_root {
+            assert_eq!(root, "generated/test_list.txt");
+        } else {
+            panic!(
+                "New test root is not expected: {:?}",
+                round_trip.selector.test_root
+            );
+        }
+    }
+
+    // split_iter tests
+    #[test]
+    fn test_split_iter_should_match_split_into() {
+        let config_yaml = "
+            description: Auth tests
+
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let test_lists = vec![
+            vec!["test0.js".to_string(), "test1.js".to_string()],
+            vec!["test2.js".to_string()],
+            vec![],
+        ];
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let mut sub_suites = resmoke_suite.split_iter(&test_lists);
+
+        assert_eq!(sub_suites.size_hint(), (3, Some(3)));
+        let first = sub_suites.next().unwrap();
+        assert_eq!(first, resmoke_suite.split_into(&test_lists)[0]);
+        let rest: Vec<_> = sub_suites.collect();
+        assert_eq!(rest, resmoke_suite.split_into(&test_lists)[1..]);
+    }
+
+    // split_into_roots tests
+    #[test]
+    fn test_split_into_roots_should_chunk_include_files_only_suite() {
+        let config_yaml = "
+            test_kind: js_test
+            description: Base suite
+
+            selector:
+              include_files:
+                - jstests/core/**/*.js
+              exclude_files:
+                - jstests/core/skip.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        assert!(resmoke_suite.selector.selects_only_by_include_files());
+        let test_lists = vec![
+            vec![
+                "jstests/core/a.js".to_string(),
+                "jstests/core/b.js".to_string(),
+            ],
+            vec!["jstests/core/nested/c.js".to_string()],
+        ];
+
+        let sub_suites = resmoke_suite.split_into_roots(&test_lists);
+
+        assert_eq!(sub_suites.len(), 2);
+        for (sub_suite, tests) in sub_suites.iter().zip(&test_lists) {
+            assert_eq!(
+                sub_suite.selector.test_root,
+                Some(TestRoot::Roots {
+                    roots: tests.clone()
+                })
+            );
+            assert_eq!(sub_suite.selector.include_files, None);
+            assert_eq!(sub_suite.selector.exclude_files, None);
+            assert!(!sub_suite.selector.selects_only_by_include_files());
+            assert_eq!(sub_suite.executor, resmoke_suite.executor);
+        }
+        assert_eq!(
+            sub_suites[1].description.as_deref(),
+            Some("Base suite (subtask 1)")
+        );
+    }
+
+    #[rstest]
+    #[case("roots: [jstests/core/*.js]", false)]
+    #[case("test: jstests/core/a.js", false)]
+    #[case(
+        "{roots: [jstests/core/*.js], include_files: [jstests/core/a*.js]}",
+        false
+    )]
+    #[case("include_files: [jstests/core/*.js]", true)]
+    fn test_selects_only_by_include_files(#[case] selector_yaml: &str, #[case] expected: bool) {
+        let selector: ResmokeSelector = serde_yaml::from_str(selector_yaml).unwrap();
+
+        assert_eq!(selector.selects_only_by_include_files(), expected);
+    }
+
+    // split_into tests
+    #[test]
+    fn test_split_into_should_create_a_suite_for_each_test_list() {
+        let config_yaml = "
+            description: Auth tests
+
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+              exclude_with_any_tags:
+                - tag_0
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let test_lists = vec![
+            vec!["test0.js".to_string(), "test1.js".to_string()],
+            vec!["test2.js".to_string()],
+            vec![],
+        ];
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let sub_suites = resmoke_suite.split_into(&test_lists);
+
+        assert_eq!(sub_suites.len(), 3);
+        for (index, sub_suite) in sub_suites.iter().enumerate() {
+            assert_eq!(sub_suite.test_kind, "js_test");
+            assert_eq!(
+                sub_suite.description,
+                Some(format!("Auth tests (subtask {})", index))
+            );
+            assert_eq!(
+                sub_suite.selector.exclude_with_any_tags,
+                resmoke_suite.selector.exclude_with_any_tags
+            );
+            if let Some(TestRoot::Roots { roots }) = &sub_suite.selector.test_root {
+                assert_eq!(roots, &test_lists[index]);
+            } else {
+                panic!(
+                    "New test root is not expected: {:?}",
+                    sub_suite.selector.test_root
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_with_should_only_transform_requested_sub_suites() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+
+            executor:
+              config:
+                shell_options:
+                  readMode: commands
+              fixture:
+                class: MyFixture
+        ";
+        let test_lists = vec![
+            vec!["test0.js".to_string(), "test1.js".to_string()],
+            vec!["test2.js".to_string()],
+        ];
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let sub_suites = resmoke_suite
+            .split_with(&test_lists, |index, config| {
+                if index == 0 {
+                    config.executor.merge_config(&serde_yaml::from_str(
+                        "shell_options: {setParameters: {logComponentVerbosity: '{verbosity: 2}'}}",
+                    )?);
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(sub_suites.len(), 2);
+        let shell_options_0 = &sub_suites[0].executor.config.as_ref().unwrap()["shell_options"];
+        assert_eq!(shell_options_0["readMode"], Value::from("commands"));
+        assert_eq!(
+            shell_options_0["setParameters"]["logComponentVerbosity"],
+            Value::from("{verbosity: 2}")
+        );
+        let shell_options_1 = &sub_suites[1].executor.config.as_ref().unwrap()["shell_options"];
+        assert_eq!(shell_options_1["readMode"], Value::from("commands"));
+        assert!(shell_options_1["setParameters"].is_null());
+    }
+
+    #[test]
+    fn test_split_with_should_propagate_errors() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let test_lists = vec![vec!["test0.js".to_string()], vec!["test1.js".to_string()]];
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let failing = resmoke_suite.split_with(&test_lists, |index, _config| {
+            if index == 1 {
+                bail!("Cannot transform sub-suite {}", index);
+            }
+            Ok(())
+        });
+        let invalid = resmoke_suite.split_with(&test_lists, |_index, config| {
+            config.selector.group_size = Some(0);
+            Ok(())
+        });
+
+        assert!(failing.is_err());
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_split_with_should_reject_sub_suites_of_invalid_suites() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+              group_size: 0
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let test_lists = vec![vec!["test0.js".to_string()]];
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let unchanged = resmoke_suite.split_with(&test_lists, |_index, _config| Ok(()));
+        let fixed = resmoke_suite.split_with(&test_lists, |_index, config| {
+            config.selector.group_size = None;
+            Ok(())
+        });
+
+        assert!(unchanged.is_err());
+        assert!(fixed.is_ok());
+    }
+
+    #[test]
+    fn test_split_with_should_accept_assigned_tests_that_are_excluded() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_files:
+                - jstests/core/flaky.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let test_lists = vec![vec![
+            "jstests/core/a.js".to_string(),
+            "jstests/core/flaky.js".to_string(),
+        ]];
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let sub_suites = resmoke_suite
+            .split_with(&test_lists, |_index, config| {
+                *config = resmoke_suite.with_run_tests_keeping_exclusions(&test_lists[0]);
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(
+            sub_suites[0].lint(DEFAULT_MAX_ANY_TAGS),
+            vec![SuiteLint::ExcludedRoots(vec![
+                "jstests/core/flaky.js".to_string()
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_split_into_without_description() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let test_lists = vec![vec!["test0.js".to_string()]];
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let sub_suites = resmoke_suite.split_into(&test_lists);
+
+        assert_eq!(sub_suites.len(), 1);
+        assert_eq!(sub_suites[0].description.as_deref(), Some("subtask 0"));
+    }
+
+    #[test]
+    fn test_with_new_tests_should_not_duplicate_excluded_tests() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+              exclude_files:
+                - jstests/auth/repl.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let exclude_test_list = vec!["test0.js".to_string(), "jstests/auth/repl.js".to_string()];
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_config = resmoke_suite
+            .with_new_tests(None, Some(&exclude_test_list))
+            .with_new_tests(None, Some(&exclude_test_list));
+
+        assert_eq!(
+            new_config.selector.exclude_files,
+            Some(vec![
+                "jstests/auth/repl.js".to_string(),
+                "test0.js".to_string()
+            ])
+        );
+    }
+
+    // ResmokeSelectorBuilder tests
+    #[test]
+    fn test_selector_builder_should_only_serialize_set_fields() {
+        let selector = ResmokeSelector::builder()
+            .roots(vec!["jstests/core/*.js".to_string()])
+            .exclude_files(vec!["jstests/core/txns/*.js".to_string()])
+            .build();
+
+        let selector_yaml = serde_yaml::to_string(&selector).unwrap();
+        let selector_value: Value = serde_yaml::from_str(&selector_yaml).unwrap();
+        let mut keys: Vec<&str> = selector_value
+            .as_mapping()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str().unwrap())
+            .collect();
+        keys.sort();
+
+        assert_eq!(keys, vec!["exclude_files", "roots"]);
+        assert!(selector.include_tags.is_none());
+        assert!(selector.group_size.is_none());
+    }
+
+    // scale_group_size tests
+    #[rstest]
+    #[case(None, 0.5, None)]
+    #[case(Some(4), 0.5, Some(2))]
+    #[case(Some(5), 0.5, Some(3))]
+    #[case(Some(3), 0.1, Some(1))]
+    #[case(Some(3), 0.0, Some(1))]
+    fn test_scale_group_size(
+        #[case] group_size: Option<usize>,
+        #[case] factor: f64,
+        #[case] expected_group_size: Option<usize>,
+    ) {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              group_count_multiplier: 2.5
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        resmoke_suite.selector.group_size = group_size;
+
+        let new_config = resmoke_suite.scale_group_size(factor);
+
+        assert_eq!(new_config.selector.group_size, expected_group_size);
+        assert_eq!(new_config.selector.group_count_multiplier, Some(2.5));
+    }
+
+    // to_string tests
+    #[test]
+    fn test_to_string_should_be_stable_across_round_trips() {
+        let config_yaml = "
+            matrix_suite: false
+            description: A realistic suite
+            test_kind: js_test
+            selector:
+              roots:
+                - jstests/core/**/*.js
+              exclude_files:
+                - jstests/core/txns/*.js
+              exclude_with_any_tags:
+                - tag_c
+                - tag_a
+                - tag_b
+              group_size: 2
+              group_count_multiplier: 1.5
+              include_with_any_tags:
+                - tag_d
+              include_files:
+                - jstests/core/basic*.js
+              include_tags:
+                $allOf:
+                  - tag_e
+                  - tag_f
+              tag_file: etc/tags.yml
+            executor:
+              archive:
+                hooks:
+                  - ValidateCollections
+                tests: true
+              hooks:
+                - CheckReplDBHash
+                - class: ValidateCollections
+                  shell_options:
+                    global_vars:
+                      TestData:
+                        skipValidationOnNamespaceNotFound: false
+              config:
+                shell_options:
+                  eval: await import('jstests/libs/override_methods/check_uuids.js');
+              fixture:
+                class: ReplicaSetFixture
+                num_nodes: 3
+                mongod_options:
+                  set_parameters:
+                    enableTestCommands: 1
+        ";
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let first_pass = resmoke_suite.to_string();
+        let second_pass = ResmokeSuiteConfig::from_str(&first_pass)
+            .unwrap()
+            .to_string();
+
+        assert_eq!(first_pass, second_pass);
+        let original: Value = serde_yaml::from_str(config_yaml).unwrap();
+        let serialized: Value = serde_yaml::from_str(&first_pass).unwrap();
+        let exclude_with_any_tags: Vec<&str> = serialized["selector"]["exclude_with_any_tags"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|t| t.as_str().unwrap())
+            .collect();
+        assert_eq!(exclude_with_any_tags, vec!["tag_a", "tag_b", "tag_c"]);
+        assert_eq!(original["executor"], serialized["executor"]);
+        let keys: Vec<&str> = serialized
+            .as_mapping()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str().unwrap())
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "matrix_suite",
+                "description",
+                "test_kind",
+                "selector",
+                "executor"
+            ]
+        );
+    }
+
+    // try_to_string tests
+    #[test]
+    fn test_try_to_string_should_return_error_on_unserializable_config() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let nested_tag = Value::Tagged(Box::new(TaggedValue {
+            tag: Tag::new("!inner"),
+            value: Value::from(1),
+        }));
+        resmoke_suite.executor.config = Some(Box::new(Value::Tagged(Box::new(TaggedValue {
+            tag: Tag::new("!outer"),
+            value: nested_tag,
+        }))));
+
+        assert!(resmoke_suite.try_to_string().is_err());
+    }
+
+    // ArchiveConfig tests
+    #[test]
+    fn test_archive_config_should_preserve_unknown_keys() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              archive:
+                hooks:
+                  - ValidateCollections
+                tests: true
+                extra_setting:
+                  nested: value
+              fixture:
+                class: MyFixture
+        ";
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let mut archive = resmoke_suite.executor.archive_config().unwrap().unwrap();
+        assert_eq!(
+            archive.hooks(),
+            Some(&ArchiveSelection::Only(vec![
+                "ValidateCollections".to_string()
+            ]))
+        );
+        assert_eq!(archive.tests(), Some(&ArchiveSelection::All(true)));
+        archive.set_tests(ArchiveSelection::All(false));
+        resmoke_suite.executor.set_archive_config(archive).unwrap();
+
+        let round_trip = ResmokeSuiteConfig::from_str(&resmoke_suite.to_string()).unwrap();
+        let archive = round_trip.executor.archive.unwrap();
+        assert_eq!(archive["tests"], Value::from(false));
+        assert_eq!(archive["hooks"][0], Value::from("ValidateCollections"));
+        assert_eq!(archive["extra_setting"]["nested"], Value::from("value"));
+    }
+
+    #[test]
+    fn test_archive_config_should_be_none_with_no_archive() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        assert!(resmoke_suite.executor.archive_config().unwrap().is_none());
+    }
+
+    // FixtureConfig tests
+    #[test]
+    fn test_fixture_config_for_standalone_fixture() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MongoDFixture
+                mongod_options:
+                  set_parameters:
+                    enableTestCommands: 1
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let fixture = resmoke_suite.executor.fixture_config().unwrap().unwrap();
+
+        assert_eq!(fixture.class(), Some("MongoDFixture"));
+        assert_eq!(fixture.num_nodes(), None);
+    }
+
+    #[test]
+    fn test_fixture_config_for_replica_set_fixture_should_preserve_unknown_keys() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: ReplicaSetFixture
+                num_nodes: 3
+                mongod_options:
+                  set_parameters:
+                    enableTestCommands: 1
+        ";
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let mut fixture = resmoke_suite.executor.fixture_config().unwrap().unwrap();
+        assert_eq!(fixture.class(), Some("ReplicaSetFixture"));
+        assert_eq!(fixture.num_nodes(), Some(3));
+        fixture.set_num_nodes(5);
+        resmoke_suite.executor.set_fixture_config(fixture).unwrap();
+
+        let fixture = resmoke_suite.executor.fixture.unwrap();
+        assert_eq!(fixture["num_nodes"], Value::from(5));
+        assert_eq!(
+            fixture["mongod_options"]["set_parameters"]["enableTestCommands"],
+            Value::from(1)
+        );
+    }
+
+    // FixturePolicy tests
+    #[rstest]
+    #[case(0, 1)]
+    #[case(1, 1)]
+    #[case(20, 1)]
+    #[case(21, 2)]
+    #[case(40, 2)]
+    #[case(41, 3)]
+    #[case(1000, 3)]
+    fn test_fixture_policy_node_count_should_stay_in_bounds(
+        #[case] test_count: usize,
+        #[case] expected_nodes: u32,
+    ) {
+        let policy = FixturePolicy::default();
+
+        assert_eq!(policy.node_count(test_count), expected_nodes);
+    }
+
+    #[test]
+    fn test_fixture_policy_max_nodes_should_take_precedence() {
+        let policy = FixturePolicy {
+            min_nodes: 4,
+            max_nodes: 2,
+            tests_per_node: 0,
+        };
+
+        assert_eq!(policy.node_count(0), 2);
+        assert_eq!(policy.node_count(100), 2);
+    }
+
+    // scale_fixture tests
+    fn build_suite_with_fixture(n_tests: usize, fixture: &str) -> ResmokeSuiteConfig {
+        let roots: Vec<String> = (0..n_tests)
+            .map(|i| format!("\n                - jstests/core/test_{}.js", i))
+            .collect();
+        let config_yaml = format!(
+            "
+            test_kind: js_test
+
+            selector:
+              roots:{}
+
+            executor:
+              fixture:{}
+            ",
+            roots.join(""),
+            fixture
+        );
+        ResmokeSuiteConfig::from_str(&config_yaml).unwrap()
+    }
+
+    #[rstest]
+    #[case(1, 2)]
+    #[case(5, 3)]
+    #[case(50, 3)]
+    fn test_scale_fixture_should_keep_num_nodes_in_bounds(
+        #[case] n_tests: usize,
+        #[case] expected_nodes: u32,
+    ) {
+        let mut resmoke_suite = build_suite_with_fixture(
+            n_tests,
+            "
+                class: ReplicaSetFixture
+                num_nodes: 3
+                mongod_options:
+                  set_parameters:
+                    enableTestCommands: 1",
+        );
+        let policy = FixturePolicy {
+            min_nodes: 2,
+            max_nodes: 5,
+            tests_per_node: 2,
+        };
+
+        resmoke_suite.scale_fixture(policy).unwrap();
+
+        let fixture = resmoke_suite.executor.fixture_config().unwrap().unwrap();
+        assert_eq!(fixture.num_nodes(), Some(expected_nodes));
+        assert_eq!(
+            resmoke_suite.executor.fixture.unwrap()["mongod_options"]["set_parameters"]
+                ["enableTestCommands"],
+            Value::from(1)
+        );
+    }
+
+    #[test]
+    fn test_scale_fixture_should_scale_shards_of_sharded_fixtures() {
+        let mut resmoke_suite = build_suite_with_fixture(
+            30,
+            "
+                class: ShardedClusterFixture
+                num_shards: 2
+                num_rs_nodes_per_shard: 3",
+        );
+
+        resmoke_suite
+            .scale_fixture(FixturePolicy::default())
+            .unwrap();
+
+        let fixture = resmoke_suite.executor.fixture_config().unwrap().unwrap();
+        assert_eq!(fixture.num_shards(), Some(2));
+        assert_eq!(fixture.num_nodes(), None);
+        assert_eq!(
+            resmoke_suite.executor.fixture.unwrap()["num_rs_nodes_per_shard"],
+            Value::from(3)
+        );
+    }
+
+    #[test]
+    fn test_scale_fixture_should_not_change_fixtures_without_a_size() {
+        let mut resmoke_suite = build_suite_with_fixture(
+            30,
+            "
+                class: MongoDFixture",
+        );
+        let original = resmoke_suite.clone();
+
+        resmoke_suite
+            .scale_fixture(FixturePolicy::default())
+            .unwrap();
+
+        assert_eq!(resmoke_suite, original);
+    }
+
+    // hook_names tests
+    #[test]
+    fn test_hook_names_should_support_string_and_mapping_hooks() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              hooks:
+                - CheckReplDBHash
+                - class: ContinuousStepdown
+                  stepdown_interval_ms: 8000
+                - class: CleanEveryN
+                  n: 20
+              fixture:
+                class: ReplicaSetFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        assert_eq!(
+            resmoke_suite.executor.hook_names(),
+            vec!["CheckReplDBHash", "ContinuousStepdown", "CleanEveryN"]
+        );
+        assert!(resmoke_suite.executor.has_hook("CheckReplDBHash"));
+        assert!(resmoke_suite.executor.has_hook("ContinuousStepdown"));
+        assert!(!resmoke_suite.executor.has_hook("ValidateCollections"));
+    }
+
+    #[test]
+    fn test_hook_names_with_no_hooks() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MongoDFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        assert!(resmoke_suite.executor.hook_names().is_empty());
+        assert!(!resmoke_suite.executor.has_hook("CheckReplDBHash"));
+    }
+
+    // retain_hooks tests
+    #[test]
+    fn test_retain_hooks_should_remove_matching_hooks() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              hooks:
+                - CheckReplDBHash
+                - class: ContinuousStepdown
+                  stepdown_interval_ms: 8000
+                - ValidateCollections
+                - class: CheckReplDBHash
+                  n: 20
+              fixture:
+                class: ReplicaSetFixture
+        ";
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        resmoke_suite
+            .executor
+            .retain_hooks(|name| name != "CheckReplDBHash");
+
+        assert_eq!(
+            resmoke_suite.executor.hook_names(),
+            vec!["ContinuousStepdown", "ValidateCollections"]
+        );
+        let hooks = resmoke_suite.executor.hooks.unwrap();
+        assert_eq!(hooks[0]["stepdown_interval_ms"], Value::from(8000));
+    }
+
+    #[test]
+    fn test_retain_hooks_with_no_hooks() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MongoDFixture
+        ";
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        resmoke_suite.executor.retain_hooks(|_| false);
+
+        assert!(resmoke_suite.executor.hooks.is_none());
+    }
+
+    #[test]
+    fn test_with_new_tests_should_clear_matrix_suite() {
+        let config_yaml = "
+            matrix_suite: true
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let new_test_list = vec!["test0.js".to_string()];
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_config = resmoke_suite.with_new_tests(Some(&new_test_list), None);
+
+        assert_eq!(resmoke_suite.matrix_suite, Some(true));
+        assert!(new_config.matrix_suite.is_none());
+        assert!(!new_config.to_string().contains("matrix_suite"));
+    }
+
+    // add_include_with_any_tag tests
+    #[test]
+    fn test_add_include_with_any_tag_with_no_existing_tags() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let new_config = resmoke_suite.add_include_with_any_tag("featureFlagX");
+
+        assert!(resmoke_suite.selector.include_with_any_tags.is_none());
+        assert_eq!(
+            new_config.selector.include_with_any_tags,
+            Some(vec!["featureFlagX".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_add_include_with_any_tag_with_existing_tags() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              include_with_any_tags:
+                - featureFlagA
+                - featureFlagB
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let new_config = resmoke_suite
+            .add_include_with_any_tag("featureFlagC")
+            .add_include_with_any_tag("featureFlagA");
+
+        assert_eq!(
+            new_config.selector.include_with_any_tags,
+            Some(vec![
+                "featureFlagA".to_string(),
+                "featureFlagB".to_string(),
+                "featureFlagC".to_string(),
+            ])
+        );
+    }
+
+    // exclude_tags_union tests
+    #[test]
+    fn test_exclude_tags_union_should_include_existing_and_new_tags() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_with_any_tags:
+                - requires_fcv_70
+                - tag_0
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_tags = hashset! {"requires_fcv_70".to_string(), "tag_1".to_string()};
+
+        let new_config = resmoke_suite.exclude_tags_union(&new_tags);
+
+        let expected_tags = hashset! {
+            "requires_fcv_70".to_string(),
+            "tag_0".to_string(),
+            "tag_1".to_string(),
+        };
+        assert_eq!(
+            new_config.selector.exclude_with_any_tags,
+            Some(expected_tags)
+        );
+        assert_eq!(
+            resmoke_suite
+                .selector
+                .exclude_with_any_tags
+                .as_ref()
+                .map(|t| t.len()),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_exclude_tags_union_with_no_existing_tags() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_tags = hashset! {"tag_0".to_string()};
+
+        let new_config = resmoke_suite.exclude_tags_union(&new_tags);
+
+        assert_eq!(new_config.selector.exclude_with_any_tags, Some(new_tags));
+    }
+
+    // from_str_strict tests
+    #[test]
+    fn test_from_str_strict_should_reject_unknown_keys() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            selectors:
+              exclude_files:
+                - jstests/core/txns/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        assert!(ResmokeSuiteConfig::from_str(config_yaml).is_ok());
+        let error = ResmokeSuiteConfig::from_str_strict(config_yaml).unwrap_err();
+        assert!(matches!(
+            error,
+            SuiteError::Validation(SuiteValidationError::UnknownKeys(ref keys))
+                if keys == &vec!["selectors".to_string()]
+        ));
+        assert!(error.to_string().contains("selectors"));
+        assert!(matches!(
+            ResmokeSuiteConfig::from_str_strict("test_kind: [\n").unwrap_err(),
+            SuiteError::Parse { .. }
+        ));
+    }
+
+    #[test]
+    fn test_from_str_strict_should_accept_known_keys() {
+        let config_yaml = "
+            matrix_suite: true
+            description: Suite description
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        assert!(ResmokeSuiteConfig::from_str_strict(config_yaml).is_ok());
+    }
+
+    #[test]
+    fn test_with_new_tests_should_replace_single_test() {
+        let config_yaml = "
+            test_kind: benchmark_test
+
+            selector:
+              test: build/benchmarks/my_benchmark
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let new_test_list = vec!["build/benchmarks/other_benchmark".to_string()];
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_config = resmoke_suite.with_new_tests(Some(&new_test_list), None);
+
+        assert!(new_config.selector.test.is_none());
+        if let Some(TestRoot::Roots { roots }) = new_config.selector.test_root {
+            assert_eq!(roots, new_test_list);
+        } else {
+            panic!(
+                "New test root is not expected: {:?}",
+                new_config.selector.test_root
+            );
+        }
+    }
+
+    #[test]
+    fn test_with_new_tests_should_keep_single_test_when_excluding() {
+        let config_yaml = "
+            test_kind: benchmark_test
+
+            selector:
+              test: build/benchmarks/my_benchmark
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let exclude_test_list = vec!["build/benchmarks/other_benchmark".to_string()];
+
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_config = resmoke_suite.with_new_tests(None, Some(&exclude_test_list));
+
+        assert_eq!(
+            new_config.selector.test.as_deref(),
+            Some("build/benchmarks/my_benchmark")
+        );
+        assert!(new_config.selector.test_root.is_none());
+    }
+
+    // root_count tests
+    #[test]
+    fn test_root_count_for_each_test_root() {
+        let roots_selector = ResmokeSelector::builder()
+            .roots(vec!["test0.js".to_string(), "test1.js".to_string()])
+            .build();
+        let root_selector = ResmokeSelector::builder().root("test_list.txt").build();
+        let no_root_selector = ResmokeSelector::builder().test("test0.js").build();
+
+        assert_eq!(roots_selector.root_count(), Some(2));
+        assert_eq!(root_selector.root_count(), None);
+        assert_eq!(no_root_selector.root_count(), None);
+    }
+
+    // overlay tests
+    #[test]
+    fn test_overlay_should_combine_exclusions() {
+        let base = ResmokeSelector::builder()
+            .roots(vec!["jstests/core/*.js".to_string()])
+            .exclude_files(vec![
+                "jstests/core/a.js".to_string(),
+                "jstests/core/b.js".to_string(),
+            ])
+            .exclude_with_any_tags(hashset! {"tag_0".to_string(), "tag_1".to_string()})
+            .include_tags(TagExpression::Flat("include_0".to_string()))
+            .exclude_tags(TagExpression::Flat("exclude_0".to_string()))
+            .build();
+        let override_selector = ResmokeSelector::builder()
+            .exclude_files(vec![
+                "jstests/core/b.js".to_string(),
+                "jstests/core/c.js".to_string(),
+            ])
+            .exclude_with_any_tags(hashset! {"tag_1".to_string(), "tag_2".to_string()})
+            .include_tags(TagExpression::Flat("include_1".to_string()))
+            .exclude_tags(TagExpression::Flat("exclude_1".to_string()))
+            .build();
+
+        let selector = base.overlay(&override_selector);
+
+        assert_eq!(selector.root_count(), Some(1));
+        assert_eq!(
+            selector.exclude_files,
+            Some(vec![
+                "jstests/core/a.js".to_string(),
+                "jstests/core/b.js".to_string(),
+                "jstests/core/c.js".to_string()
+            ])
+        );
+        assert_eq!(
+            selector.exclude_with_any_tags,
+            Some(hashset! {"tag_0".to_string(), "tag_1".to_string(), "tag_2".to_string()})
+        );
+        assert_eq!(
+            selector.include_tags,
+            Some(TagExpression::AllOf {
+                all_of: vec![
+                    TagExpression::Flat("include_0".to_string()),
+                    TagExpression::Flat("include_1".to_string())
+                ]
+            })
+        );
+        assert_eq!(
+            selector.exclude_tags,
+            Some(TagExpression::AnyOf {
+                any_of: vec![
+                    TagExpression::Flat("exclude_0".to_string()),
+                    TagExpression::Flat("exclude_1".to_string())
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_overlay_should_prefer_override_roots() {
+        let base = ResmokeSelector::builder()
+            .roots(vec!["jstests/core/*.js".to_string()])
+            .include_tags(TagExpression::Flat("include_0".to_string()))
+            .group_size(5)
+            .build();
+        let override_selector = ResmokeSelector::builder()
+            .roots(vec![
+                "jstests/auth/*.js".to_string(),
+                "jstests/auth/extra/*.js".to_string(),
+            ])
+            .build();
+
+        let selector = base.overlay(&override_selector);
+
+        assert_eq!(selector.root_count(), Some(2));
+        assert_eq!(
+            selector.include_tags,
+            Some(TagExpression::Flat("include_0".to_string()))
+        );
+        assert_eq!(selector.group_size, Some(5));
+        assert_eq!(selector.exclude_files, None);
+    }
+
+    // referenced_tags tests
+    #[test]
+    fn test_referenced_tags_should_include_all_tag_fields() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              include_tags:
+                $allOf:
+                  - tag_0
+                  - $not: tag_1
+              exclude_tags: tag_2 && !tag_3
+              include_with_any_tags:
+                - tag_4
+                - tag_0
+              exclude_with_any_tags:
+                - tag_5
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let tags = resmoke_suite.selector.referenced_tags();
+
+        assert_eq!(
+            tags.into_iter().collect::<Vec<_>>(),
+            vec!["tag_0", "tag_1", "tag_2", "tag_3", "tag_4", "tag_5"]
+        );
+    }
+
+    #[test]
+    fn test_referenced_tags_should_be_empty_without_tags() {
+        let selector = ResmokeSelector::builder()
+            .roots(vec!["a.js".to_string()])
+            .build();
+
+        assert!(selector.referenced_tags().is_empty());
+    }
+
+    // uses_external_tag_file tests
+    #[test]
+    fn test_uses_external_tag_file_should_be_true_with_tag_file() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              tag_file: etc/tags.yml
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        assert!(resmoke_suite.selector.uses_external_tag_file());
+    }
+
+    #[rstest]
+    #[case(None)]
+    #[case(Some(""))]
+    #[case(Some("  "))]
+    fn test_uses_external_tag_file_should_be_false_without_tag_file(
+        #[case] tag_file: Option<&str>,
+    ) {
+        let mut selector = ResmokeSelector::builder()
+            .roots(vec!["jstests/core/*.js".to_string()])
+            .include_with_any_tags(vec!["tag_0".to_string()])
+            .build();
+        selector.tag_file = tag_file.map(|f| f.to_string());
+
+        assert!(!selector.uses_external_tag_file());
+    }
+
+    // effective_includes tests
+    #[test]
+    fn test_effective_includes_should_combine_roots_and_file_filters() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+                - jstests/core/txns/*.js
+              include_files:
+                - jstests/core/txns/*.js
+              exclude_files:
+                - jstests/core/txns/slow.js
+              exclude_with_any_tags:
+                - requires_sharding
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let selection = resmoke_suite.selector.effective_includes();
+
+        assert_eq!(
+            selection,
+            EffectiveSelection {
+                candidates: Some(TestRoot::Roots {
+                    roots: vec![
+                        "jstests/core/*.js".to_string(),
+                        "jstests/core/txns/*.js".to_string()
+                    ]
+                }),
+                include_files: Some(vec!["jstests/core/txns/*.js".to_string()]),
+                exclude_files: vec!["jstests/core/txns/slow.js".to_string()],
+                tag_filter: TagFilter {
+                    include_tags: None,
+                    exclude_tags: None,
+                    include_with_any_tags: None,
+                    exclude_with_any_tags: Some(hashset! {"requires_sharding".to_string()}),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_effective_includes_should_use_single_test_as_candidate() {
+        let selector = ResmokeSelector::builder().test("jstests/core/a.js").build();
+
+        let selection = selector.effective_includes();
+
+        assert_eq!(
+            selection.candidates,
+            Some(TestRoot::Roots {
+                roots: vec!["jstests/core/a.js".to_string()]
+            })
+        );
+        assert_eq!(selection.include_files, None);
+        assert!(selection.exclude_files.is_empty());
+    }
+
+    // selects tests
+    fn build_selects_selector() -> ResmokeSelector {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+                - jstests/core/txns/**/*.js
+              exclude_files:
+                - jstests/core/txns/slow.js
+              exclude_with_any_tags:
+                - requires_sharding
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        ResmokeSuiteConfig::from_str(config_yaml).unwrap().selector
+    }
+
+    #[rstest]
+    #[case("jstests/core/a.js", true)]
+    #[case("jstests\\core\\a.js", true)]
+    #[case("jstests/core/txns/a.js", true)]
+    #[case("jstests/core/txns/nested/a.js", true)]
+    #[case("jstests/core/txns/slow.js", false)]
+    #[case("jstests/core/nested/a.js", false)]
+    #[case("jstests/auth/a.js", false)]
+    fn test_selects_should_apply_file_globs(#[case] test_path: &str, #[case] expected: bool) {
+        let selector = build_selects_selector();
+
+        assert_eq!(selector.selects(test_path, &hashset! {}), expected);
+    }
+
+    #[test]
+    fn test_selects_should_respect_include_files() {
+        let mut selector = build_selects_selector();
+        selector.include_files = Some(vec!["jstests/core/txns/**/*.js".to_string()]);
+
+        assert!(selector.selects("jstests/core/txns/a.js", &hashset! {}));
+        assert!(!selector.selects("jstests/core/a.js", &hashset! {}));
+    }
+
+    #[test]
+    fn test_selects_should_not_select_without_candidates() {
+        let selector = ResmokeSelector::default();
+
+        assert!(!selector.selects("jstests/core/a.js", &hashset! {}));
+    }
+
+    #[test]
+    fn test_selects_should_consider_any_test_a_candidate_of_root_file() {
+        let selector = ResmokeSelector {
+            test_root: Some(TestRoot::Root {
+                root: "roots.txt".to_string(),
+            }),
+            ..Default::default()
+        };
+
+        assert!(selector.selects("jstests/core/a.js", &hashset! {}));
+    }
+
+    #[rstest]
+    #[case(vec![], true)]
+    #[case(vec!["requires_sharding"], false)]
+    #[case(vec!["other_tag"], true)]
+    fn test_selects_should_apply_exclude_with_any_tags(
+        #[case] tags: Vec<&str>,
+        #[case] expected: bool,
+    ) {
+        let selector = build_selects_selector();
+        let tags = tags.into_iter().map(|t| t.to_string()).collect();
+
+        assert_eq!(selector.selects("jstests/core/a.js", &tags), expected);
+    }
+
+    #[rstest]
+    #[case(vec![], false)]
+    #[case(vec!["tag_0"], true)]
+    #[case(vec!["tag_1"], true)]
+    #[case(vec!["tag_2"], false)]
+    fn test_selects_should_apply_include_with_any_tags(
+        #[case] tags: Vec<&str>,
+        #[case] expected: bool,
+    ) {
+        let mut selector = build_selects_selector();
+        selector.include_with_any_tags = Some(vec!["tag_0".to_string(), "tag_1".to_string()]);
+        let tags = tags.into_iter().map(|t| t.to_string()).collect();
+
+        assert_eq!(selector.selects("jstests/core/a.js", &tags), expected);
+    }
+
+    #[rstest]
+    #[case(Some("tag_0"), None, vec!["tag_0"], true)]
+    #[case(Some("tag_0"), None, vec!["tag_1"], false)]
+    #[case(None, Some("tag_0"), vec!["tag_0"], false)]
+    #[case(None, Some("tag_0"), vec!["tag_1"], true)]
+    #[case(Some("tag_0 && !tag_1"), None, vec!["tag_0"], true)]
+    #[case(Some("tag_0 && !tag_1"), None, vec!["tag_0", "tag_1"], false)]
+    #[case(Some("tag_0 || (tag_1 && tag_2)"), None, vec!["tag_1", "tag_2"], true)]
+    #[case(Some("tag_0 || (tag_1 && tag_2)"), None, vec!["tag_1"], false)]
+    #[case(Some("(tag_0"), None, vec!["tag_0"], false)]
+    #[case(Some("(tag_0"), None, vec!["(tag_0"], true)]
+    fn test_selects_should_evaluate_flat_tag_expressions(
+        #[case] include_tags: Option<&str>,
+        #[case] exclude_tags: Option<&str>,
+        #[case] tags: Vec<&str>,
+        #[case] expected: bool,
+    ) {
+        let mut selector = build_selects_selector();
+        selector.include_tags = include_tags.map(|t| TagExpression::Flat(t.to_string()));
+        selector.exclude_tags = exclude_tags.map(|t| TagExpression::Flat(t.to_string()));
+        let tags = tags.into_iter().map(|t| t.to_string()).collect();
+
+        assert_eq!(selector.selects("jstests/core/a.js", &tags), expected);
+    }
+
+    #[test]
+    fn test_selects_should_evaluate_structured_tag_expressions() {
+        let mut selector = build_selects_selector();
+        selector.include_tags = Some(TagExpression::Flat("tag_0".to_string()).and(
+            TagExpression::Not {
+                not: Box::new(TagExpression::Flat("tag_1".to_string())),
+            },
+        ));
+
+        assert!(selector.selects("jstests/core/a.js", &hashset! {"tag_0".to_string()}));
+        assert!(!selector.selects(
+            "jstests/core/a.js",
+            &hashset! {"tag_0".to_string(), "tag_1".to_string()}
+        ));
+    }
+
+    // TestRoot::from_lines tests
+    #[test]
+    fn test_test_root_from_lines_should_skip_blank_lines_and_comments() {
+        let test_list = "
+            # Tests generated by another tool.
+            jstests/core/test0.js
+              jstests/core/test1.js	
+
+            #jstests/core/disabled.js
+            jstests/core/test2.js
+        ";
+
+        let test_root = TestRoot::from_lines(test_list);
+
+        if let TestRoot::Roots { roots } = test_root {
+            assert_eq!(
+                roots,
+                vec![
+                    "jstests/core/test0.js",
+                    "jstests/core/test1.js",
+                    "jstests/core/test2.js"
+                ]
+            );
+        } else {
+            panic!("Unexpected test root: {:?}", test_root);
+        }
+    }
+
+    // missing_roots tests
+    fn build_repo_with_tests(tests: &[&str]) -> TempDir {
+        let tmp_dir = TempDir::new("missing_roots").unwrap();
+        for test in tests {
+            let path = tmp_dir.path().join(test);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        tmp_dir
+    }
+
+    #[test]
+    fn test_missing_roots_should_report_roots_not_on_disk() {
+        let repo = build_repo_with_tests(&["jstests/core/a.js"]);
+        let selector = ResmokeSelector::builder()
+            .roots(vec![
+                "jstests/core/a.js".to_string(),
+                "jstests/core/deleted.js".to_string(),
+                "jstests/core/*.js".to_string(),
+                "jstests/missing/**/*.js".to_string(),
+            ])
+            .build();
+
+        assert_eq!(
+            selector.missing_roots(repo.path()),
+            vec!["jstests/core/deleted.js"]
+        );
+    }
+
+    #[test]
+    fn test_missing_roots_should_be_empty_if_all_roots_exist() {
+        let repo = build_repo_with_tests(&["jstests/core/a.js", "jstests/core/b.js"]);
+        let selector = ResmokeSelector::builder()
+            .roots(vec![
+                "jstests/core/a.js".to_string(),
+                "jstests/core/b.js".to_string(),
+            ])
+            .build();
+
+        assert!(selector.missing_roots(repo.path()).is_empty());
+        assert!(ResmokeSelector::default()
+            .missing_roots(repo.path())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_missing_roots_should_check_single_test_and_root_files() {
+        let repo = build_repo_with_tests(&["jstests/core/a.js"]);
+        std::fs::write(
+            repo.path().join("roots.txt"),
+            "jstests/core/a.js\njstests/core/deleted.js\n",
+        )
+        .unwrap();
+
+        let selector = ResmokeSelector::builder()
+            .test("jstests/core/gone.js")
+            .build();
+        assert_eq!(
+            selector.missing_roots(repo.path()),
+            vec!["jstests/core/gone.js"]
+        );
+
+        let selector = ResmokeSelector::builder().root("roots.txt").build();
+        assert_eq!(
+            selector.missing_roots(repo.path()),
+            vec!["jstests/core/deleted.js"]
+        );
+
+        let selector = ResmokeSelector::builder().root("missing_roots.txt").build();
+        assert_eq!(
+            selector.missing_roots(repo.path()),
+            vec!["missing_roots.txt"]
+        );
+    }
+
+    // TestRoot::resolve_roots tests
+    #[test]
+    fn test_resolve_roots_should_read_root_file() {
+        let tmp_dir = TempDir::new("resmoke_suite").unwrap();
+        std::fs::write(
+            tmp_dir.path().join("test_list.txt"),
+            "# Tests to run.\njstests/core/test0.js\n\njstests/core/test1.js\n",
+        )
+        .unwrap();
+        let test_root = TestRoot::Root {
+            root: "test_list.txt".to_string(),
+        };
+
+        let roots = test_root.resolve_roots(tmp_dir.path()).unwrap();
+
+        assert_eq!(
+            roots,
+            vec!["jstests/core/test0.js", "jstests/core/test1.js"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_roots_should_return_inline_roots() {
+        let test_root = TestRoot::Roots {
+            roots: vec![
+                "jstests/core/test0.js".to_string(),
+                "jstests/core/*.js".to_string(),
+            ],
+        };
+
+        let roots = test_root.resolve_roots(Path::new("unused")).unwrap();
+
+        assert_eq!(roots, vec!["jstests/core/test0.js", "jstests/core/*.js"]);
+    }
+
+    #[test]
+    fn test_resolve_roots_should_fail_on_missing_root_file() {
+        let tmp_dir = TempDir::new("resmoke_suite").unwrap();
+        let test_root = TestRoot::Root {
+            root: "missing.txt".to_string(),
+        };
+
+        assert!(test_root.resolve_roots(tmp_dir.path()).is_err());
+    }
+
+    // to_json tests
+    #[rstest]
+    #[case(TestRoot::Roots { roots: vec!["jstests/core/test0.js".to_string(), "jstests/core/test1.js".to_string()] })]
+    #[case(TestRoot::Root { root: "jstests/core/test_list.txt".to_string() })]
+    fn test_json_and_yaml_should_round_trip_test_roots(#[case] test_root: TestRoot) {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_files:
+                - jstests/core/txns/*.js
+              include_tags:
+                $allOf:
+                  - tag_0
+                  - tag_1
+
+            executor:
+              hooks:
+                - CheckReplDBHash
+              fixture:
+                class: MyFixture
+                num_nodes: 3
+        ";
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        resmoke_suite.selector.test_root = Some(test_root);
+
+        let from_json = ResmokeSuiteConfig::from_json(&resmoke_suite.to_json().unwrap()).unwrap();
+        let from_yaml = ResmokeSuiteConfig::from_str(&resmoke_suite.to_string()).unwrap();
+
+        for config in [from_json, from_yaml] {
+            assert_eq!(config.to_string(), resmoke_suite.to_string());
+            match (
+                &config.selector.test_root,
+                &resmoke_suite.selector.test_root,
+            ) {
+                (Some(TestRoot::Roots { roots }), Some(TestRoot::Roots { roots: expected })) => {
+                    assert_eq!(roots, expected)
+                }
+                (Some(TestRoot::Root { root }), Some(TestRoot::Root { root: expected })) => {
+                    assert_eq!(root, expected)
+                }
+                (actual, expected) => {
+                    panic!("Test root {:?} does not match {:?}", actual, expected)
+                }
+            }
+        }
+    }
+
+    // with_description tests
+    #[rstest]
+    #[case(Some("Auth tests"), 3, "Auth tests (subtask 3)")]
+    #[case(None, 7, "subtask 7")]
+    fn test_describe_sub_suite(
+        #[case] base_description: Option<&str>,
+        #[case] index: usize,
+        #[case] expected_description: &str,
+    ) {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        resmoke_suite.description = base_description.map(|d| d.to_string());
+
+        let new_config = resmoke_suite.describe_sub_suite(index);
+
+        assert_eq!(
+            new_config.description.as_deref(),
+            Some(expected_description)
+        );
+        assert_eq!(resmoke_suite.description.as_deref(), base_description);
+    }
+
+    #[test]
+    fn test_with_description_should_replace_description() {
+        let config_yaml = "
+            description: Auth tests
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let new_config = resmoke_suite.with_description("Some other tests");
+
+        assert_eq!(new_config.description.as_deref(), Some("Some other tests"));
+    }
+
+    // with_test_kind tests
+    #[test]
+    fn test_with_test_kind_should_only_replace_test_kind() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/txns/*.js
+              exclude_files:
+                - jstests/core/txns/slow.js
+
+            executor:
+              config:
+                shell_options:
+                  readMode: commands
+              fixture:
+                class: ReplicaSetFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let new_config = resmoke_suite.with_test_kind("multi_stmt_txn_passthrough");
+
+        assert_eq!(new_config.test_kind, "multi_stmt_txn_passthrough");
+        assert_eq!(resmoke_suite.test_kind, "js_test");
+        assert_eq!(
+            serde_yaml::to_value(&new_config.selector).unwrap(),
+            serde_yaml::to_value(&resmoke_suite.selector).unwrap()
+        );
+        assert_eq!(
+            serde_yaml::to_value(&new_config.executor).unwrap(),
+            serde_yaml::to_value(&resmoke_suite.executor).unwrap()
+        );
+    }
+
+    // with_origin tests
+    #[rstest]
+    #[case(None, "[origin: core]")]
+    #[case(Some("Core tests"), "[origin: core]\nCore tests")]
+    #[case(Some("[origin: old_core]\nCore tests"), "[origin: core]\nCore tests")]
+    #[case(Some("[origin: old_core]"), "[origin: core]")]
+    fn test_with_origin_should_stamp_description(
+        #[case] base_description: Option<&str>,
+        #[case] expected_description: &str,
+    ) {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        resmoke_suite.description = base_description.map(|d| d.to_string());
+
+        let new_config = resmoke_suite.with_origin("core");
+
+        assert_eq!(
+            new_config.description.as_deref(),
+            Some(expected_description)
+        );
+    }
+
+    #[test]
+    fn test_origin_should_survive_serialization() {
+        let config_yaml = "
+            description: Core tests
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        assert_eq!(resmoke_suite.origin(), None);
+
+        let new_config = resmoke_suite
+            .with_origin("core")
+            .with_new_tests(Some(&["jstests/core/a.js".to_string()]), None)
+            .describe_sub_suite(2);
+        let round_tripped = ResmokeSuiteConfig::from_str(&new_config.to_string()).unwrap();
+
+        assert_eq!(round_tripped.origin(), Some("core"));
+    }
+
+    // to_string_with_exclude_reasons tests
+    #[test]
+    fn test_to_string_with_exclude_reasons_should_add_comments() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_files:
+                - jstests/core/x.js
+                - jstests/core/y.js
+                - jstests/core/z.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let config = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let reasons = btreemap! {
+            "jstests/core/x.js".to_string() => "flaky BF-1234".to_string(),
+            "jstests/core/z.js".to_string() => "too slow\nsee BF-5678".to_string(),
+            "jstests/core/*.js".to_string() => "not excluded".to_string(),
+        };
+
+        let contents = config.to_string_with_exclude_reasons(&reasons).unwrap();
+
+        assert!(contents.contains("- jstests/core/x.js  # excluded: flaky BF-1234\n"));
+        assert!(contents.contains("- jstests/core/y.js\n"));
+        assert!(contents.contains("- jstests/core/z.js  # excluded: too slow see BF-5678\n"));
+        assert!(!contents.contains("not excluded"));
+        let stripped: String = contents
+            .lines()
+            .map(|line| match line.find("  #") {
+                Some(index) => &line[..index],
+                None => line,
+            })
+            .map(|line| format!("{}\n", line))
+            .collect();
+        assert_eq!(stripped, config.to_string());
+        let round_tripped = ResmokeSuiteConfig::from_str(&contents).unwrap();
+        assert_eq!(
+            round_tripped.selector.exclude_files,
+            config.selector.exclude_files
+        );
+    }
+
+    // SuiteError tests
+    #[test]
+    fn test_from_str_should_return_parse_errors() {
+        let err = ResmokeSuiteConfig::from_str("test_kind: js_test\nselector: [\n").unwrap_err();
+
+        match &err {
+            SuiteError::Parse {
+                path, line, column, ..
+            } => {
+                assert_eq!(path, &None);
+                assert!(line.is_some());
+                assert!(column.is_some());
+            }
+            _ => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_to_json_should_return_serialize_errors() {
+        let mut config = ResmokeSuiteConfig {
+            test_kind: "js_test".to_string(),
+            ..Default::default()
+        };
+        let mut mapping = Mapping::new();
+        mapping.insert(Value::Sequence(vec![Value::from(1)]), Value::from(true));
+        config.executor.config = Some(Box::new(Value::Mapping(mapping)));
+
+        let err = config.to_json().unwrap_err();
+
+        assert!(matches!(err, SuiteError::Serialize { .. }));
+        let source = std::error::Error::source(&err).unwrap();
+        assert!(source.downcast_ref::<serde_json::Error>().is_some());
+    }
+
+    #[test]
+    fn test_parse_should_return_validation_errors() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              group_size: 0
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let err = ResmokeSuiteConfig::parse(config_yaml, true).unwrap_err();
+
+        assert!(matches!(
+            err,
+            SuiteError::Validation(SuiteValidationError::InvalidGroupSize)
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors_should_keep_source_and_report_location_once() {
+        let err = ResmokeSuiteConfig::from_str("test_kind: js_test\nselector: [\n").unwrap_err();
+
+        let source = std::error::Error::source(&err).unwrap();
+        assert!(source.downcast_ref::<serde_yaml::Error>().is_some());
+        assert_eq!(err.to_string().matches("line").count(), 1);
+    }
+
+    // from_file tests
+    #[test]
+    fn test_from_file_should_load_suite() {
+        let tmp_dir = TempDir::new("resmoke_suite").unwrap();
+        let path = tmp_dir.path().join("suite.yml");
+        std::fs::write(
+            &path,
+            "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+            ",
+        )
+        .unwrap();
+
+        let resmoke_suite = ResmokeSuiteConfig::from_file(&path).unwrap();
+
+        assert_eq!(resmoke_suite.test_kind, "js_test");
+    }
+
+    #[test]
+    fn test_from_file_should_report_path_and_location_of_parse_errors() {
+        let tmp_dir = TempDir::new("resmoke_suite").unwrap();
+        let path = tmp_dir.path().join("suite.yml");
+        std::fs::write(&path, "test_kind: js_test\nselector: [\n").unwrap();
+
+        let err = ResmokeSuiteConfig::from_file(&path).unwrap_err();
+
+        match &err {
+            SuiteError::Parse {
+                path: err_path,
+                line,
+                column,
+                ..
+            } => {
+                assert_eq!(err_path.as_ref(), Some(&path));
+                assert!(line.is_some());
+                assert!(column.is_some());
+            }
+            _ => panic!("Unexpected error: {:?}", err),
+        }
+        assert!(err.to_string().contains(path.to_str().unwrap()));
+    }
+
+    #[test]
+    fn test_from_file_should_report_path_of_missing_file() {
+        let tmp_dir = TempDir::new("resmoke_suite").unwrap();
+        let path = tmp_dir.path().join("missing.yml");
+
+        let err = ResmokeSuiteConfig::from_file(&path).unwrap_err();
+
+        match &err {
+            SuiteError::Io { path: err_path, .. } => assert_eq!(err_path.as_ref(), Some(&path)),
+            _ => panic!("Unexpected error: {:?}", err),
+        }
+        assert!(err.to_string().contains(path.to_str().unwrap()));
+    }
+
+    // load_suites tests
+    /// Write suite files to the given directory, with the chosen files containing invalid yaml.
+    fn write_suite_files(dir: &Path, n: usize, is_invalid: impl Fn(usize) -> bool) -> Vec<PathBuf> {
+        (0..n)
+            .map(|i| {
+                let path = dir.join(format!("suite_{}.yml", i));
+                let contents = if is_invalid(i) {
+                    "test_kind: js_test\nselector: [\n".to_string()
+                } else {
+                    format!(
+                        "
+                        test_kind: js_test
+
+                        selector:
+                          roots:
+                            - jstests/suite_{}/*.js
+
+                        executor:
+                          fixture:
+                            class: MyFixture
+                        ",
+                        i
+                    )
+                };
+                std::fs::write(&path, contents).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_load_suites_should_match_serial_loading() {
+        let tmp_dir = TempDir::new("resmoke_suite").unwrap();
+        let paths = write_suite_files(tmp_dir.path(), 20, |i| i % 7 == 3);
+
+        let parallel = load_suites(&paths);
+        let serial: Vec<_> = paths
+            .iter()
+            .map(|path| ResmokeSuiteConfig::from_file(path))
+            .collect();
+
+        assert_eq!(parallel.len(), serial.len());
+        for ((path, parallel), serial) in paths.iter().zip(parallel).zip(serial) {
+            match (parallel, serial) {
+                (Ok(parallel), Ok(serial)) => assert_eq!(parallel.to_string(), serial.to_string()),
+                (Err(parallel), Err(serial)) => {
+                    assert_eq!(parallel.path(), Some(path.as_path()));
+                    assert_eq!(parallel.to_string(), serial.to_string());
+                }
+                (parallel, serial) => panic!(
+                    "Mismatched results for {:?}: {:?} vs {:?}",
+                    path, parallel, serial
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_suites_should_handle_no_paths() {
+        assert!(load_suites(&[]).is_empty());
+    }
+
+    // load_suites_async tests
+    #[tokio::test]
+    async fn test_load_suites_async_should_match_sync_loading() {
+        let tmp_dir = TempDir::new("resmoke_suite").unwrap();
+        let mut paths =
+            write_suite_files(tmp_dir.path(), MAX_CONCURRENT_SUITE_READS + 8, |i| i == 4);
+        paths.push(tmp_dir.path().join("missing.yml"));
+
+        let async_results = load_suites_async(&paths).await;
+        let sync_results = load_suites(&paths);
+
+        assert_eq!(async_results.len(), sync_results.len());
+        for ((path, async_result), sync_result) in paths.iter().zip(async_results).zip(sync_results)
+        {
+            match (async_result, sync_result) {
+                (Ok(async_config), Ok(sync_config)) => {
+                    assert_eq!(async_config.to_string(), sync_config.to_string())
+                }
+                (Err(async_err), Err(sync_err)) => {
+                    assert_eq!(async_err.path(), Some(path.as_path()));
+                    assert_eq!(async_err.to_string(), sync_err.to_string());
+                }
+                (async_result, sync_result) => panic!(
+                    "Mismatched results for {:?}: {:?} vs {:?}",
+                    path, async_result, sync_result
+                ),
+            }
+        }
+    }
+
+    // merge_description tests
+    #[rstest]
+    #[case(None, Some("Override tests"), Some("Override tests"))]
+    #[case(Some("Base tests"), None, Some("Base tests"))]
+    #[case(
+        Some("Base tests"),
+        Some("Override tests"),
+        Some("Base tests\nOverride tests")
+    )]
+    #[case(None, None, None)]
+    fn test_merge_description(
+        #[case] base_description: Option<&str>,
+        #[case] override_description: Option<&str>,
+        #[case] expected_description: Option<&str>,
+    ) {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        resmoke_suite.description = base_description.map(|d| d.to_string());
+
+        resmoke_suite.merge_description(override_description);
+
+        assert_eq!(resmoke_suite.description.as_deref(), expected_description);
+    }
+
+    // merge_config tests
+    #[test]
+    fn test_merge_config_should_merge_nested_mappings() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              config:
+                shell_options:
+                  global_vars:
+                    TestData:
+                      existingFlag: true
+                      overriddenFlag: 1
+                  nodb: ''
+              fixture:
+                class: MyFixture
+        ";
+        let extra: Value = serde_yaml::from_str(
+            "
+            shell_options:
+              global_vars:
+                TestData:
+                  overriddenFlag: 2
+                  setParameters:
+                    diagnosticFlag: true
+            ",
+        )
+        .unwrap();
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        resmoke_suite.executor.merge_config(&extra);
+
+        let config = resmoke_suite.executor.config.unwrap();
+        let test_data = &config["shell_options"]["global_vars"]["TestData"];
+        assert_eq!(test_data["existingFlag"], Value::from(true));
+        assert_eq!(test_data["overriddenFlag"], Value::from(2));
+        assert_eq!(
+            test_data["setParameters"]["diagnosticFlag"],
+            Value::from(true)
+        );
+        assert_eq!(config["shell_options"]["nodb"], Value::from(""));
+    }
+
+    #[test]
+    fn test_merge_config_should_replace_scalars_and_sequences() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              config:
+                shell_options:
+                  eval: original
+                  args:
+                    - a
+                    - b
+              fixture:
+                class: MyFixture
+        ";
+        let extra: Value = serde_yaml::from_str(
+            "
+            shell_options:
+              eval:
+                nested: value
+              args:
+                - c
+            ",
+        )
+        .unwrap();
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        resmoke_suite.executor.merge_config(&extra);
+
+        let config = resmoke_suite.executor.config.unwrap();
+        assert_eq!(
+            config["shell_options"]["eval"]["nested"],
+            Value::from("value")
+        );
+        assert_eq!(
+            config["shell_options"]["args"],
+            Value::Sequence(vec![Value::from("c")])
+        );
+    }
+
+    #[test]
+    fn test_merge_config_with_no_existing_config() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let extra: Value = serde_yaml::from_str("shell_options: {nodb: ''}").unwrap();
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        resmoke_suite.executor.merge_config(&extra);
+
+        assert_eq!(resmoke_suite.executor.config.as_deref(), Some(&extra));
+    }
+
+    // try_with_new_tests tests
+    #[test]
+    fn test_try_with_new_tests_should_reject_empty_test_list() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_test_list = vec!["test0.js".to_string()];
+
+        assert_eq!(
+            resmoke_suite
+                .try_with_new_tests(Some(&[]), None)
+                .unwrap_err(),
+            SuiteValidationError::EmptyTestList
+        );
+        assert!(resmoke_suite
+            .try_with_new_tests(Some(&new_test_list), None)
+            .is_ok());
+        assert!(resmoke_suite.try_with_new_tests(None, Some(&[])).is_ok());
+        assert_eq!(
+            resmoke_suite
+                .with_new_tests(Some(&[]), None)
+                .selector
+                .test_root,
+            Some(TestRoot::Roots { roots: vec![] })
+        );
+        assert_eq!(
+            resmoke_suite
+                .try_with_new_tests(Some(&new_test_list), None)
+                .unwrap(),
+            resmoke_suite.with_new_tests(Some(&new_test_list), None)
+        );
+    }
+
+    // run_command tests
+    #[test]
+    fn test_run_command_should_run_suite_file_with_extra_args() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_with_any_tags:
+                - requires_sharding
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let args = resmoke_suite.run_command(
+            "generated_resmoke_config/core_0.yml",
+            &[
+                "--originSuite=core".to_string(),
+                "--storageEngine=wiredTiger".to_string(),
+            ],
+        );
+
+        assert_eq!(
+            args,
+            vec![
+                "run",
+                "--suites=generated_resmoke_config/core_0.yml",
+                "--originSuite=core",
+                "--storageEngine=wiredTiger",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_command_without_extra_args() {
+        let resmoke_suite = ResmokeSuiteConfig::default();
+
+        assert_eq!(
+            resmoke_suite.run_command("suite.yml", &[]),
+            vec!["run", "--suites=suite.yml"]
+        );
+    }
+
+    // to_string_with_header tests
+    #[test]
+    fn test_to_string_with_header_should_add_comments() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let contents = resmoke_suite
+            .to_string_with_header("generated from core by mongo-task-generator\n\nsecond line")
+            .unwrap();
+
+        assert!(contents.starts_with(
+            "# generated from core by mongo-task-generator\n#\n# second line\ntest_kind: js_test\n"
+        ));
+        let parsed = ResmokeSuiteConfig::from_str(&contents).unwrap();
+        assert_eq!(parsed.to_string(), resmoke_suite.to_string());
+    }
+
+    #[test]
+    fn test_with_new_tests_should_normalize_path_separators() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/auth/*.js
+              exclude_files:
+                - jstests/auth/repl.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let test_list = vec![
+            "jstests\\auth\\repl.js".to_string(),
+            "jstests\\auth/other.js".to_string(),
+        ];
+
+        let excluded = resmoke_suite.with_new_tests(None, Some(&test_list));
+        let included = resmoke_suite.with_new_tests(Some(&test_list), None);
+
+        assert_eq!(
+            excluded.selector.exclude_files,
+            Some(vec![
+                "jstests/auth/repl.js".to_string(),
+                "jstests/auth/other.js".to_string()
+            ])
+        );
+        if let Some(TestRoot::Roots { roots }) = included.selector.test_root {
+            assert_eq!(roots, vec!["jstests/auth/repl.js", "jstests/auth/other.js"]);
+        } else {
+            panic!(
+                "New test root is not expected: {:?}",
+                included.selector.test_root
+            );
+        }
+    }
+
+    // fingerprint tests
+    #[test]
+    fn test_fingerprint_should_ignore_field_ordering() {
+        let config_yaml_0 = "
+            test_kind: js_test
+            description: Core tests
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_files:
+                - jstests/core/a.js
+              exclude_with_any_tags:
+                - tag_0
+                - tag_1
+
+            executor:
+              config:
+                shell_options:
+                  readMode: commands
+                  eval: load('override.js')
+              fixture:
+                class: MongoDFixture
+                num_nodes: 3
+        ";
+        let config_yaml_1 = "
+            executor:
+              fixture:
+                num_nodes: 3
+                class: MongoDFixture
+              config:
+                shell_options:
+                  eval: load('override.js')
+                  readMode: commands
+
+            selector:
+              exclude_with_any_tags:
+                - tag_1
+                - tag_0
+              exclude_files:
+                - jstests/core/a.js
+              roots:
+                - jstests/core/*.js
+
+            description: Core tests
+            test_kind: js_test
+        ";
+        let config_0 = ResmokeSuiteConfig::from_str(config_yaml_0).unwrap();
+        let config_1 = ResmokeSuiteConfig::from_str(config_yaml_1).unwrap();
+
+        assert_eq!(
+            config_0.fingerprint().unwrap(),
+            config_1.fingerprint().unwrap()
+        );
+        assert_eq!(config_0.fingerprint().unwrap().len(), 64);
+    }
+
+    #[test]
+    fn test_fingerprint_should_change_with_content() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MongoDFixture
+        ";
+        let config = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_config = config.with_new_tests(None, Some(&["jstests/core/a.js".to_string()]));
+
+        assert_eq!(
+            config.fingerprint().unwrap(),
+            config.clone().fingerprint().unwrap()
+        );
+        assert_ne!(
+            config.fingerprint().unwrap(),
+            new_config.fingerprint().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_should_return_errors_for_unserializable_keys() {
+        let mut config = ResmokeSuiteConfig {
+            test_kind: "js_test".to_string(),
+            ..Default::default()
+        };
+        let nested_tag = Value::Tagged(Box::new(TaggedValue {
+            tag: Tag::new("!outer"),
+            value: Value::Tagged(Box::new(TaggedValue {
+                tag: Tag::new("!inner"),
+                value: Value::from(1),
+            })),
+        }));
+        let mut mapping = Mapping::new();
+        mapping.insert(nested_tag, Value::from(true));
+        let value = Value::Mapping(mapping);
+        assert!(canonicalize_value(&value).is_err());
+        config.executor.config = Some(Box::new(value));
+
+        assert!(matches!(
+            config.fingerprint().unwrap_err(),
+            SuiteError::Serialize { .. }
+        ));
+    }
+
+    // root_tests tests
+    #[rstest]
+    #[case::inline_roots("roots:\n    - a.js\n    - b.js", RootTests::Inline(&["a.js".to_string(), "b.js".to_string()]))]
+    #[case::single_test("test: a.js", RootTests::Inline(&["a.js".to_string()]))]
+    #[case::root_file("root: test_list.txt", RootTests::File("test_list.txt"))]
+    #[case::no_roots("include_files:\n    - a.js", RootTests::Unspecified)]
+    fn test_root_tests_for_each_variant(#[case] selector_yaml: &str, #[case] expected: RootTests) {
+        let config_yaml = format!(
+            "
+test_kind: js_test
+selector:
+  {}
+executor:
+  fixture:
+    class: MyFixture
+",
+            selector_yaml
+        );
+        let config = ResmokeSuiteConfig::from_str(&config_yaml).unwrap();
+
+        assert_eq!(config.root_tests(), expected);
+    }
+
+    // passthrough tests
+    #[test]
+    fn test_passthrough_should_preserve_selection() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+                - jstests/auth/*.js
+              exclude_files:
+                - jstests/core/d.js
+                - jstests/core/c.js
+                - jstests/core/d.js
+              exclude_with_any_tags:
+                - requires_sharding
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let passthrough = resmoke_suite.passthrough().unwrap();
+
+        assert_eq!(
+            passthrough.selector.test_root,
+            resmoke_suite.selector.test_root
+        );
+        assert_eq!(
+            passthrough.selector.exclude_files,
+            Some(vec![
+                "jstests/core/c.js".to_string(),
+                "jstests/core/d.js".to_string()
+            ])
+        );
+        for test in ["core/a", "core/c", "core/d", "auth/a", "noPassthrough/a"] {
+            let test_path = format!("jstests/{}.js", test);
+            for tags in [hashset! {}, hashset! {"requires_sharding".to_string()}] {
+                assert_eq!(
+                    passthrough.selector.selects(&test_path, &tags),
+                    resmoke_suite.selector.selects(&test_path, &tags)
+                );
+            }
+        }
+        assert_eq!(passthrough.executor, resmoke_suite.executor);
+        assert_eq!(passthrough, resmoke_suite.canonicalize());
+    }
+
+    #[test]
+    fn test_passthrough_should_reject_invalid_config() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              include_tags: tag_0
+              exclude_tags: tag_1
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        assert_eq!(
+            resmoke_suite.passthrough(),
+            Err(SuiteValidationError::ConflictingTagExpressions)
+        );
+    }
+
+    // canonicalize tests
+    #[test]
+    fn test_canonicalize_should_make_equivalent_configs_equal() {
+        let config_a = ResmokeSuiteConfig::from_str(
+            "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+                - jstests/core/txns/*.js
+              exclude_files:
+                - b.js
+                - a.js
+                - b.js
+              include_with_any_tags:
+                - tag_1
+                - tag_0
+              include_tags:
+                $allOf:
+                  - tag_2
+                  - $allOf:
+                      - tag_3
+                      - $not:
+                          $not: tag_4
+              exclude_tags:
+                $anyOf:
+                  - tag_5
+
+            executor:
+              fixture:
+                class: MyFixture
+            ",
+        )
+        .unwrap();
+        let config_b = ResmokeSuiteConfig::from_str(
+            "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+                - jstests/core/txns/*.js
+              exclude_files:
+                - a.js
+                - b.js
+              include_with_any_tags:
+                - tag_0
+                - tag_1
+              include_tags:
+                $allOf:
+                  - tag_4
+                  - tag_3
+                  - tag_2
+              exclude_tags: tag_5
+
+            executor:
+              fixture:
+                class: MyFixture
+            ",
+        )
+        .unwrap();
+
+        assert_ne!(config_a, config_b);
+        assert_eq!(config_a.canonicalize(), config_b.canonicalize());
+        assert_eq!(
+            config_a.canonicalize().selector.exclude_files,
+            Some(vec!["a.js".to_string(), "b.js".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_should_keep_order_significant_fields() {
+        let config = ResmokeSuiteConfig::from_str(
+            "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - c.js
+                - a.js
+
+            executor:
+              hooks:
+                - class: ValidateCollections
+                - class: CleanEveryN
+              fixture:
+                class: MyFixture
+            ",
+        )
+        .unwrap();
+
+        let canonical = config.canonicalize();
+
+        assert_eq!(canonical, config);
+        assert_eq!(
+            canonical.executor.hook_names(),
+            vec!["ValidateCollections", "CleanEveryN"]
+        );
+    }
+
+    #[rstest]
+    #[case(TagExpression::Flat(" tag_0 ".to_string()), TagExpression::Flat("tag_0".to_string()))]
+    #[case(
+        TagExpression::Flat("tag_0".to_string()).or(TagExpression::Flat("tag_0".to_string())),
+        TagExpression::Flat("tag_0".to_string())
+    )]
+    #[case(
+        TagExpression::Flat("tag_1".to_string())
+            .or(TagExpression::Flat("tag_0".to_string()).or(TagExpression::Flat("tag_2".to_string()))),
+        TagExpression::AnyOf {
+            any_of: vec![
+                TagExpression::Flat("tag_0".to_string()),
+                TagExpression::Flat("tag_1".to_string()),
+                TagExpression::Flat("tag_2".to_string()),
+            ]
+        }
+    )]
+    fn test_tag_expression_canonicalize(
+        #[case] expression: TagExpression,
+        #[case] expected: TagExpression,
+    ) {
+        assert_eq!(expression.canonicalize(), expected);
+    }
+
+    // for_burn_in tests
+    fn build_burn_in_base_suite() -> ResmokeSuiteConfig {
+        ResmokeSuiteConfig::from_str(
+            "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_files:
+                - jstests/core/excluded.js
+              exclude_with_any_tags:
+                - requires_sharding
+
+            executor:
+              fixture:
+                class: MyFixture
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_for_burn_in_should_only_run_selected_changed_tests() {
+        let changed_tests = vec![
+            "jstests/core/a.js".to_string(),
+            "jstests/core/excluded.js".to_string(),
+            "jstests/auth/b.js".to_string(),
+            "jstests\\core\\c.js".to_string(),
+        ];
+
+        let burn_in = build_burn_in_base_suite()
+            .for_burn_in(&changed_tests, 3)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            burn_in.config.selector.test_root,
+            Some(TestRoot::Roots {
+                roots: vec![
+                    "jstests/core/a.js".to_string(),
+                    "jstests/core/c.js".to_string()
+                ]
+            })
+        );
+        assert_eq!(burn_in.config.selector.exclude_files, None);
+        assert_eq!(
+            burn_in.config.selector.exclude_with_any_tags,
+            Some(hashset! {"requires_sharding".to_string()})
+        );
+    }
+
+    #[test]
+    fn test_for_burn_in_should_be_none_when_no_changed_tests_are_selected() {
+        let changed_tests = vec![
+            "jstests/core/excluded.js".to_string(),
+            "jstests/auth/b.js".to_string(),
+        ];
+
+        let burn_in = build_burn_in_base_suite().for_burn_in(&changed_tests, 3);
+
+        assert_eq!(burn_in.unwrap(), None);
+    }
+
+    #[test]
+    fn test_for_burn_in_should_reject_root_file_suites() {
+        let resmoke_suite = ResmokeSuiteConfig::from_str(
+            "
+            test_kind: js_test
+
+            selector:
+              root: suites/roots.txt
+
+            executor:
+              fixture:
+                class: MyFixture
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            resmoke_suite
+                .for_burn_in(&["jstests/core/a.js".to_string()], 3)
+                .unwrap_err(),
+            SuiteValidationError::UnresolvedRootFile("suites/roots.txt".to_string())
+        );
+    }
+
+    #[rstest]
+    #[case(5, 5, "--repeatTests=5")]
+    #[case(1, 1, "--repeatTests=1")]
+    #[case(0, 1, "--repeatTests=1")]
+    fn test_for_burn_in_should_report_repeat_count(
+        #[case] repeat: usize,
+        #[case] expected_repeat: usize,
+        #[case] expected_args: &str,
+    ) {
+        let burn_in = build_burn_in_base_suite()
+            .for_burn_in(&["jstests/core/a.js".to_string()], repeat)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(burn_in.repeat, expected_repeat);
+        assert_eq!(burn_in.resmoke_args(), expected_args);
+    }
+
+    #[test]
+    fn test_burn_in_run_command_should_include_repeat_count() {
+        let burn_in = build_burn_in_base_suite()
+            .for_burn_in(&["jstests/core/a.js".to_string()], 3)
+            .unwrap()
+            .unwrap();
+
+        let args = burn_in.run_command(
+            "generated_resmoke_config/burn_in.yml",
+            &["--jobs=4".to_string()],
+        );
+
+        assert_eq!(
+            args,
+            vec![
+                "run",
+                "--suites=generated_resmoke_config/burn_in.yml",
+                "--repeatTests=3",
+                "--jobs=4",
+            ]
+        );
+    }
+
+    // materialize_excludes tests
+    #[test]
+    fn test_materialize_excludes_should_remove_excluded_roots() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/a.js
+                - jstests/core/txns/b.js
+                - jstests/core/c.js
+                - jstests/core/txns/d.js
+              exclude_files:
+                - jstests/core/txns/*.js
+                - jstests/core/c.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let all_tests: Vec<String> = vec![
+            "jstests/core/a.js",
+            "jstests/core/txns/b.js",
+            "jstests/core/c.js",
+            "jstests/core/txns/d.js",
+            "jstests/core/e.js",
+        ]
+        .into_iter()
+        .map(|t| t.to_string())
+        .collect();
+
+        let materialized = resmoke_suite.materialize_excludes(&all_tests).unwrap();
+
+        assert_eq!(
+            materialized.selector.test_root,
+            Some(TestRoot::Roots {
+                roots: vec!["jstests/core/a.js".to_string()]
+            })
+        );
+        assert_eq!(materialized.selector.exclude_files, None);
+        assert_eq!(materialized.executor, resmoke_suite.executor);
+    }
+
+    #[test]
+    fn test_materialize_excludes_should_expand_root_globs() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/**/*.js
+              exclude_files:
+                - jstests/core/txns/**/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let all_tests: Vec<String> = vec![
+            "jstests/core/b.js",
+            "jstests/core/txns/nested/c.js",
+            "jstests/auth/d.js",
+            "jstests/core/nested/a.js",
+            "jstests/core/b.js",
+        ]
+        .into_iter()
+        .map(|t| t.to_string())
+        .collect();
+
+        let materialized = resmoke_suite.materialize_excludes(&all_tests).unwrap();
+
+        assert_eq!(
+            materialized.selector.test_root,
+            Some(TestRoot::Roots {
+                roots: vec![
+                    "jstests/core/b.js".to_string(),
+                    "jstests/core/nested/a.js".to_string()
+                ]
+            })
+        );
+        assert_eq!(materialized.selector.exclude_files, None);
+    }
+
+    #[test]
+    fn test_materialize_excludes_should_reject_root_file_suites() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              root: suites/roots.txt
+              exclude_files:
+                - jstests/core/txns/**/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let all_tests = vec!["jstests/core/a.js".to_string()];
+
+        assert_eq!(
+            resmoke_suite.materialize_excludes(&all_tests).unwrap_err(),
+            SuiteValidationError::UnresolvedRootFile("suites/roots.txt".to_string())
+        );
+    }
+
+    // with_sorted_paths tests
+    #[rstest]
+    #[case(true, vec!["a.js", "b.js", "c.js"], vec!["d.js", "e.js"])]
+    #[case(false, vec!["c.js", "a.js", "b.js"], vec!["e.js", "d.js"])]
+    fn test_with_sorted_paths_should_only_sort_when_requested(
+        #[case] sort_paths: bool,
+        #[case] expected_roots: Vec<&str>,
+        #[case] expected_excludes: Vec<&str>,
+    ) {
+        let config = ResmokeSuiteConfig::from_str(
+            "
             test_kind: js_test
 
             selector:
               roots:
-                - jstests/auth/*.js
+                - c.js
+                - a.js
+                - b.js
               exclude_files:
-                - jstests/auth/repl.js
-                - jstests/core/add1.js
-        
+                - e.js
+                - d.js
+
+            executor:
+              fixture:
+                class: MyFixture
+            ",
+        )
+        .unwrap();
+
+        let config = if sort_paths {
+            config.with_sorted_paths()
+        } else {
+            config
+        };
+        let round_tripped = ResmokeSuiteConfig::from_str(&config.to_string()).unwrap();
+
+        assert_eq!(
+            round_tripped.selector.test_root,
+            Some(TestRoot::Roots {
+                roots: expected_roots.iter().map(|s| s.to_string()).collect()
+            })
+        );
+        assert_eq!(
+            round_tripped.selector.exclude_files,
+            Some(expected_excludes.iter().map(|s| s.to_string()).collect())
+        );
+    }
+
+    // with_spilled_roots tests
+    #[rstest]
+    #[case(2, false)]
+    #[case(3, false)]
+    #[case(4, true)]
+    #[case(10, true)]
+    fn test_with_spilled_roots_should_only_spill_above_threshold(
+        #[case] n_roots: usize,
+        #[case] expected_spill: bool,
+    ) {
+        let roots: Vec<String> = (0..n_roots)
+            .map(|i| format!("jstests/core/test_{}.js", i))
+            .collect();
+        let config = ResmokeSuiteConfig::from_str(
+            "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_files:
+                - jstests/core/a.js
+
+            executor:
+              fixture:
+                class: MyFixture
+            ",
+        )
+        .unwrap()
+        .with_new_tests(Some(&roots), None)
+        .with_new_tests(None, Some(&["jstests/core/b.js".to_string()]));
+
+        let spilled = config.with_spilled_roots(Path::new("target/suite_0.yml"), 3);
+
+        assert_eq!(spilled.is_some(), expected_spill);
+        if let Some(spilled) = spilled {
+            assert_eq!(
+                spilled.roots_path,
+                PathBuf::from("target/suite_0_roots.txt")
+            );
+            assert_eq!(spilled.roots_contents, format!("{}\n", roots.join("\n")));
+            assert_eq!(
+                spilled.config.selector.test_root,
+                Some(TestRoot::Root {
+                    root: "target/suite_0_roots.txt".to_string()
+                })
+            );
+            assert_eq!(
+                spilled.config.selector.exclude_files,
+                Some(vec!["jstests/core/b.js".to_string()])
+            );
+        }
+    }
+
+    #[test]
+    fn test_with_spilled_roots_should_ignore_root_files() {
+        let config = ResmokeSuiteConfig::from_str(
+            "
+            test_kind: js_test
+
+            selector:
+              root: test_list.txt
+
+            executor:
+              fixture:
+                class: MyFixture
+            ",
+        )
+        .unwrap();
+
+        assert!(config
+            .with_spilled_roots(Path::new("target/suite_0.yml"), 0)
+            .is_none());
+    }
+
+    // with_exclude_globs tests
+    #[test]
+    fn test_with_exclude_globs_should_add_valid_globs() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/**/*.js
+              exclude_files:
+                - jstests/core/a.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let new_config = resmoke_suite
+            .with_exclude_globs(&["jstests/core/txns/*".to_string()])
+            .unwrap();
+
+        assert_eq!(
+            new_config.selector.exclude_files,
+            Some(vec![
+                "jstests/core/a.js".to_string(),
+                "jstests/core/txns/*".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_with_exclude_globs_should_reject_malformed_globs() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/**/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let result = resmoke_suite.with_exclude_globs(&[
+            "jstests/core/txns/*".to_string(),
+            "jstests/core/[txns/*".to_string(),
+        ]);
+
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("jstests/core/[txns/*"));
+    }
+
+    // MatrixSuiteConfig tests
+    #[test]
+    fn test_matrix_suite_expand_should_apply_override() {
+        let base_yaml = "
+            description: Core tests
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_files:
+                - jstests/core/a.js
+
             executor:
               config:
-                value
+                shell_options:
+                  readMode: commands
+              fixture:
+                class: MongoDFixture
+        ";
+        let matrix_yaml = "
+            base_suite: core
+            description: Core tests with extra exclusions
+            overrides:
+              - selector:
+                  exclude_files:
+                    - jstests/core/b.js
+                executor:
+                  config:
+                    shell_options:
+                      eval: load('jstests/libs/override.js')
+        ";
+        let base = ResmokeSuiteConfig::from_str(base_yaml).unwrap();
+        let matrix_suite = MatrixSuiteConfig::from_str(matrix_yaml).unwrap();
+
+        let config = matrix_suite.expand(&base).unwrap();
+
+        assert_eq!(matrix_suite.base_suite, "core");
+        assert_eq!(config.matrix_suite, Some(true));
+        assert_eq!(
+            config.description.as_deref(),
+            Some("Core tests\nCore tests with extra exclusions")
+        );
+        assert_eq!(config.selector.root_count(), Some(1));
+        assert_eq!(
+            config.selector.exclude_files,
+            Some(vec![
+                "jstests/core/a.js".to_string(),
+                "jstests/core/b.js".to_string()
+            ])
+        );
+        let executor_config = config.executor.config.unwrap();
+        assert_eq!(
+            executor_config["shell_options"]["readMode"],
+            Value::from("commands")
+        );
+        assert_eq!(
+            executor_config["shell_options"]["eval"],
+            Value::from("load('jstests/libs/override.js')")
+        );
+    }
+
+    #[test]
+    fn test_matrix_suite_expand_should_fail_on_unknown_override_keys() {
+        let base_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MongoDFixture
+        ";
+        let matrix_yaml = "
+            base_suite: core
+            overrides:
+              - unknown_key: value
+        ";
+        let base = ResmokeSuiteConfig::from_str(base_yaml).unwrap();
+        let matrix_suite = MatrixSuiteConfig::from_str(matrix_yaml).unwrap();
+
+        assert!(matrix_suite.expand(&base).is_err());
+    }
+
+    // suite_test_diff tests
+    fn build_suite_with_roots(roots: &[&str]) -> ResmokeSuiteConfig {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
               fixture:
                 class: MyFixture
-                num_nodes: 3
         ";
+        let roots: Vec<String> = roots.iter().map(|r| r.to_string()).collect();
+        ResmokeSuiteConfig::from_str(config_yaml)
+            .unwrap()
+            .with_new_tests(Some(&roots), None)
+    }
 
-        let new_test_list = vec!["test0.js".to_string(), "test1.js".to_string()];
+    #[rstest]
+    #[case::added_only(vec!["a.js"], vec!["a.js", "b.js"], vec!["b.js"], vec![])]
+    #[case::removed_only(vec!["a.js", "b.js"], vec!["b.js"], vec![], vec!["a.js"])]
+    #[case::mixed(
+        vec!["a.js", "b.js", "c.js"],
+        vec!["c.js", "d.js", "a.js"],
+        vec!["d.js"],
+        vec!["b.js"]
+    )]
+    #[case::unchanged(vec!["a.js", "b.js"], vec!["b.js", "a.js"], vec![], vec![])]
+    fn test_suite_test_diff_should_report_added_and_removed_roots(
+        #[case] old_roots: Vec<&str>,
+        #[case] new_roots: Vec<&str>,
+        #[case] expected_added: Vec<&str>,
+        #[case] expected_removed: Vec<&str>,
+    ) {
+        let old = build_suite_with_roots(&old_roots);
+        let new = build_suite_with_roots(&new_roots);
+
+        let diff = suite_test_diff(&old, &new);
+
+        assert_eq!(
+            diff,
+            SuiteDiff::Known {
+                added: expected_added.iter().map(|s| s.to_string()).collect(),
+                removed: expected_removed.iter().map(|s| s.to_string()).collect(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_suite_test_diff_should_be_unknown_for_root_files() {
+        let old = build_suite_with_roots(&["a.js"]);
+        let new = old.with_root_file("test_list.txt");
+
+        assert_eq!(suite_test_diff(&old, &new), SuiteDiff::Unknown);
+        assert_eq!(suite_test_diff(&new, &old), SuiteDiff::Unknown);
+    }
+
+    // partition_by_tag tests
+    fn build_tagged_test(path: &str, tags: &[&str]) -> TaggedTest {
+        TaggedTest {
+            path: path.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[rstest]
+    #[case(vec![&["requires_sharding"][..], &["requires_sharding", "slow"][..]], vec!["test_0.js", "test_1.js"], vec![])]
+    #[case(vec![&[][..], &["slow"][..]], vec![], vec!["test_0.js", "test_1.js"])]
+    #[case(vec![&["slow"][..], &["requires_sharding"][..], &[][..], &["requires_sharding"][..]], vec!["test_1.js", "test_3.js"], vec!["test_0.js", "test_2.js"])]
+    fn test_partition_by_tag(
+        #[case] test_tags: Vec<&[&str]>,
+        #[case] expected_tagged: Vec<&str>,
+        #[case] expected_untagged: Vec<&str>,
+    ) {
+        let tests: Vec<TaggedTest> = test_tags
+            .iter()
+            .enumerate()
+            .map(|(i, tags)| build_tagged_test(&format!("test_{}.js", i), tags))
+            .collect();
+
+        let (tagged, untagged) = partition_by_tag(&tests, "requires_sharding");
+
+        assert_eq!(tagged, expected_tagged);
+        assert_eq!(untagged, expected_untagged);
+    }
+
+    // partition_by_tags tests
+    #[test]
+    fn test_partition_by_tags_should_assign_tests_to_first_matching_tag() {
+        let tests = vec![
+            build_tagged_test("test_0.js", &["slow"]),
+            build_tagged_test("test_1.js", &["requires_sharding", "slow"]),
+            build_tagged_test("test_2.js", &[]),
+            build_tagged_test("test_3.js", &["requires_sharding"]),
+            build_tagged_test("test_4.js", &["other"]),
+        ];
+
+        let partition = partition_by_tags(&tests, &["requires_sharding", "slow"]);
+
+        assert_eq!(
+            partition.partitions,
+            vec![
+                (
+                    "requires_sharding".to_string(),
+                    vec!["test_1.js".to_string(), "test_3.js".to_string()]
+                ),
+                ("slow".to_string(), vec!["test_0.js".to_string()]),
+            ]
+        );
+        assert_eq!(partition.remainder, vec!["test_2.js", "test_4.js"]);
+    }
+
+    #[rstest]
+    #[case(vec![])]
+    #[case(vec!["requires_sharding"])]
+    #[case(vec!["requires_sharding", "slow", "unused"])]
+    fn test_partition_by_tags_should_not_drop_any_tests(#[case] tags: Vec<&str>) {
+        let tests: Vec<TaggedTest> = (0..10)
+            .map(|i| match i % 3 {
+                0 => build_tagged_test(&format!("test_{}.js", i), &["slow"]),
+                1 => build_tagged_test(&format!("test_{}.js", i), &["requires_sharding"]),
+                _ => build_tagged_test(&format!("test_{}.js", i), &[]),
+            })
+            .collect();
+
+        let partition = partition_by_tags(&tests, &tags);
+
+        let mut partitioned: Vec<String> = partition.test_lists().into_iter().flatten().collect();
+        partitioned.sort();
+        let mut expected: Vec<String> = tests.iter().map(|t| t.path.clone()).collect();
+        expected.sort();
+        assert_eq!(partitioned, expected);
+    }
+
+    #[test]
+    fn test_tag_partition_test_lists_should_skip_empty_lists() {
+        let tests = vec![build_tagged_test("test_0.js", &["slow"])];
+
+        let partition = partition_by_tags(&tests, &["requires_sharding", "slow"]);
+
+        assert_eq!(partition.test_lists(), vec![vec!["test_0.js".to_string()]]);
+    }
+
+    #[test]
+    fn test_tag_partition_remainder_suite_should_run_remainder_tests() {
+        let resmoke_suite = ResmokeSuiteConfig::from_str(
+            "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ",
+        )
+        .unwrap();
+        let tests = vec![
+            build_tagged_test("test_0.js", &["slow"]),
+            build_tagged_test("test_1.js", &[]),
+        ];
+
+        let partition = partition_by_tags(&tests, &["slow"]);
+        let remainder_suite = partition.remainder_suite(&resmoke_suite).unwrap();
+
+        assert_eq!(
+            remainder_suite.selector.test_root,
+            Some(TestRoot::Roots {
+                roots: vec!["test_1.js".to_string()]
+            })
+        );
+        assert!(partition_by_tags(&tests[..1], &["slow"])
+            .remainder_suite(&resmoke_suite)
+            .is_none());
+    }
+
+    #[test]
+    fn test_split_by_tag_should_include_and_exclude_tag() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let (tagged, untagged) = resmoke_suite.split_by_tag("requires_sharding");
+
+        assert_eq!(
+            tagged.selector.include_tags,
+            Some(TagExpression::Flat("requires_sharding".to_string()))
+        );
+        assert_eq!(tagged.selector.include_with_any_tags, None);
+        assert_eq!(tagged.selector.exclude_with_any_tags, None);
+        assert_eq!(untagged.selector.include_with_any_tags, None);
+        assert_eq!(
+            untagged.selector.exclude_with_any_tags,
+            Some(hashset! {"requires_sharding".to_string()})
+        );
+    }
+
+    #[rstest]
+    #[case("include_with_any_tags:\n                - x")]
+    #[case("exclude_tags: y")]
+    #[case("include_tags: x")]
+    fn test_split_by_tag_halves_should_not_overlap(#[case] tag_filter: &str) {
+        let config_yaml = format!(
+            "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              {}
+
+            executor:
+              fixture:
+                class: MyFixture
+            ",
+            tag_filter
+        );
+        let resmoke_suite = ResmokeSuiteConfig::from_str(&config_yaml).unwrap();
+
+        let (tagged, untagged) = resmoke_suite.split_by_tag("requires_sharding");
+
+        assert!(tagged.validate().is_ok());
+        assert!(untagged.validate().is_ok());
+        let all_tags = ["x", "y", "requires_sharding"];
+        for mask in 0..(1 << all_tags.len()) {
+            let tags: HashSet<String> = all_tags
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, tag)| tag.to_string())
+                .collect();
+            let test = "jstests/core/test_0.js";
+            let in_tagged = tagged.selector.selects(test, &tags);
+            let in_untagged = untagged.selector.selects(test, &tags);
+            assert!(!(in_tagged && in_untagged), "{:?} selected twice", tags);
+            assert_eq!(
+                in_tagged || in_untagged,
+                resmoke_suite.selector.selects(test, &tags),
+                "{:?}",
+                tags
+            );
+        }
+    }
+
+    // with_tag_file tests
+    #[rstest]
+    #[case(Some("generated/tags.yml"), Some("generated/tags.yml"))]
+    #[case(None, None)]
+    fn test_with_tag_file(#[case] path: Option<&str>, #[case] expected: Option<&str>) {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              tag_file: etc/tags.yml
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+
+        let new_config = resmoke_suite.with_tag_file(path);
+
+        assert_eq!(new_config.selector.tag_file.as_deref(), expected);
+        assert_eq!(
+            resmoke_suite.selector.tag_file.as_deref(),
+            Some("etc/tags.yml")
+        );
+    }
+
+    #[test]
+    fn test_with_new_tests_should_preserve_tag_file() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              tag_file: etc/tags.yml
 
+            executor:
+              fixture:
+                class: MyFixture
+        ";
         let resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        let new_test_list = vec!["test0.js".to_string()];
+
         let new_config = resmoke_suite.with_new_tests(Some(&new_test_list), None);
 
-        if let Some(TestRoot::Roots { roots: test_roots }) = new_config.selector.test_root {
-            for test in new_test_list {
-                assert!(test_roots.contains(&test));
+        assert_eq!(
+            new_config.selector.tag_file.as_deref(),
+            Some("etc/tags.yml")
+        );
+    }
+
+    #[rstest]
+    #[case(Some(1), Some(1.5), true)]
+    #[case(None, None, true)]
+    #[case(Some(0), None, false)]
+    #[case(None, Some(0.0), false)]
+    #[case(None, Some(-1.0), false)]
+    #[case(None, Some(f64::NAN), false)]
+    #[case(None, Some(f64::INFINITY), false)]
+    fn test_validate_group_settings(
+        #[case] group_size: Option<usize>,
+        #[case] group_count_multiplier: Option<f64>,
+        #[case] is_valid: bool,
+    ) {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+        let mut resmoke_suite = ResmokeSuiteConfig::from_str(config_yaml).unwrap();
+        resmoke_suite.selector.group_size = group_size;
+        resmoke_suite.selector.group_count_multiplier = group_count_multiplier;
+
+        let result = resmoke_suite.validate();
+
+        assert_eq!(result.is_ok(), is_valid);
+        match result {
+            Err(SuiteValidationError::InvalidGroupSize) => assert_eq!(group_size, Some(0)),
+            Err(SuiteValidationError::InvalidGroupCountMultiplier(_)) => {
+                assert!(group_count_multiplier.is_some())
             }
-        } else {
-            panic!(
-                "New test root is not expected: {:?}",
-                new_config.selector.test_root
-            );
+            Err(err) => panic!("Unexpected validation error: {}", err),
+            Ok(_) => (),
         }
     }
+
+    // TryFrom tests
+    #[test]
+    fn test_try_from_str_and_string() {
+        let config_yaml = "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+
+            executor:
+              fixture:
+                class: MyFixture
+        ";
+
+        let from_str: ResmokeSuiteConfig = config_yaml.try_into().unwrap();
+        let from_string = ResmokeSuiteConfig::try_from(config_yaml.to_string()).unwrap();
+
+        assert_eq!(from_str.test_kind, "js_test");
+        assert_eq!(from_str.to_string(), from_string.to_string());
+    }
+
+    #[test]
+    fn test_try_from_should_fail_on_malformed_yaml() {
+        let config_yaml = "
+            test_kind: js_test
+            selector: [
+        ";
+
+        let from_str: Result<ResmokeSuiteConfig, _> = config_yaml.try_into();
+        let from_string = ResmokeSuiteConfig::try_from(config_yaml.to_string());
+
+        assert!(from_str.is_err());
+        assert!(from_string.is_err());
+    }
 }
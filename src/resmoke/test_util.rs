@@ -0,0 +1,136 @@
+//! Helpers for building resmoke suite configurations in tests.
+//!
+//! Only available with the `test-util` feature.
+
+use std::str::FromStr;
+
+use crate::resmoke::resmoke_suite::{ResmokeSuiteConfig, TestRoot};
+
+const SAMPLE_JS_TEST_SUITE: &str = "
+test_kind: js_test
+
+selector:
+  roots:
+    - jstests/core/**/*.js
+  exclude_files:
+    - jstests/core/txns/**/*.js
+  exclude_with_any_tags:
+    - requires_sharding
+
+executor:
+  archive:
+    hooks:
+      - ValidateCollections
+  config:
+    shell_options:
+      crashOnInvalidBSONError: ''
+      objcheck: ''
+  hooks:
+    - class: ValidateCollections
+    - class: CleanEveryN
+      n: 20
+  fixture:
+    class: MongoDFixture
+    mongod_options:
+      set_parameters:
+        enableTestCommands: 1
+";
+
+const SAMPLE_SHARDED_SUITE: &str = "
+test_kind: js_test
+
+selector:
+  roots:
+    - jstests/sharding/**/*.js
+  exclude_files:
+    - jstests/sharding/libs/*.js
+  exclude_with_any_tags:
+    - does_not_support_stepdowns
+
+executor:
+  config:
+    shell_options:
+      nodb: ''
+  hooks:
+    - class: CheckReplDBHash
+    - class: ValidateCollections
+  fixture:
+    class: ShardedClusterFixture
+    num_shards: 2
+    mongos_options:
+      set_parameters:
+        enableTestCommands: 1
+    mongod_options:
+      set_parameters:
+        enableTestCommands: 1
+    num_rs_nodes_per_shard: 1
+";
+
+/// A standalone `js_test` suite modelled on resmoke's `core` suite.
+pub fn sample_js_test_suite() -> ResmokeSuiteConfig {
+    ResmokeSuiteConfig::from_str(SAMPLE_JS_TEST_SUITE).unwrap()
+}
+
+/// A `js_test` suite running against a sharded cluster, modelled on resmoke's `sharding` suite.
+pub fn sample_sharded_suite() -> ResmokeSuiteConfig {
+    ResmokeSuiteConfig::from_str(SAMPLE_SHARDED_SUITE).unwrap()
+}
+
+/// Create a `js_test` suite running exactly the given tests.
+///
+/// # Arguments
+///
+/// * `roots` - Tests the suite should run.
+///
+/// # Returns
+///
+/// The sample `js_test` suite with its roots replaced by `roots` and no excluded files.
+pub fn suite_with_roots(roots: &[&str]) -> ResmokeSuiteConfig {
+    let mut suite = sample_js_test_suite();
+    suite.selector.test_root = Some(TestRoot::Roots {
+        roots: roots.iter().map(|root| root.to_string()).collect(),
+    });
+    suite.selector.exclude_files = None;
+    suite
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resmoke::resmoke_suite::RootTests;
+
+    use super::*;
+
+    #[test]
+    fn test_sample_suites_should_round_trip() {
+        for suite in [sample_js_test_suite(), sample_sharded_suite()].iter() {
+            suite.validate().unwrap();
+
+            let reparsed = ResmokeSuiteConfig::from_str(&suite.to_string()).unwrap();
+
+            assert_eq!(reparsed.to_string(), suite.to_string());
+        }
+    }
+
+    #[test]
+    fn test_sample_sharded_suite_should_use_sharded_fixture() {
+        let suite = sample_sharded_suite();
+
+        let fixture = suite.executor.fixture_config().unwrap().unwrap();
+
+        assert_eq!(fixture.class(), Some("ShardedClusterFixture"));
+    }
+
+    #[test]
+    fn test_suite_with_roots_should_run_given_tests() {
+        let suite = suite_with_roots(&["jstests/core/a.js", "jstests/core/b.js"]);
+        let reparsed = ResmokeSuiteConfig::from_str(&suite.to_string()).unwrap();
+
+        match reparsed.root_tests() {
+            RootTests::Inline(roots) => {
+                assert_eq!(roots, &["jstests/core/a.js", "jstests/core/b.js"])
+            }
+            root_tests => panic!("Unexpected root tests: {:?}", root_tests),
+        }
+        assert!(reparsed.selector.exclude_files.is_none());
+    }
+}
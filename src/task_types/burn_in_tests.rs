@@ -315,6 +315,7 @@ impl BurnInServiceImpl {
             mv_exclude_tags: suite_info.multiversion_tags.clone(),
             is_enterprise: false,
             platform: None,
+            estimated_runtime: None,
         };
 
         self.gen_resmoke_task_service.build_resmoke_sub_task(
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use anyhow::Result;
 use maplit::hashmap;
@@ -21,7 +21,10 @@ use crate::{
     utils::task_name::name_generated_task,
 };
 
-use super::generated_suite::{GeneratedSubTask, GeneratedSuite};
+use super::{
+    generated_suite::{GeneratedSubTask, GeneratedSuite},
+    resmoke_tasks::TaskDecorator,
+};
 
 /// Parameters for how a fuzzer task should be generated.
 #[derive(Default, Debug, Clone)]
@@ -185,12 +188,19 @@ pub trait GenFuzzerService: Sync + Send {
 }
 
 /// Implementation of the GenFuzzerService.
-pub struct GenFuzzerServiceImpl {}
+pub struct GenFuzzerServiceImpl {
+    /// Hook to customize every generated sub-task.
+    task_decorator: Option<Arc<dyn TaskDecorator>>,
+}
 
 impl GenFuzzerServiceImpl {
     /// Create a new instance of the GenFuzzerService.
-    pub fn new() -> Self {
-        Self {}
+    ///
+    /// # Arguments
+    ///
+    /// * `task_decorator` - Hook to customize every generated sub-task.
+    pub fn new(task_decorator: Option<Arc<dyn TaskDecorator>>) -> Self {
+        Self { task_decorator }
     }
 }
 
@@ -237,6 +247,12 @@ impl GenFuzzerService for GenFuzzerServiceImpl {
                 .collect();
         }
 
+        if let Some(task_decorator) = &self.task_decorator {
+            for sub_task in sub_tasks.iter_mut() {
+                task_decorator.decorate(sub_task, None);
+            }
+        }
+
         Ok(Box::new(FuzzerTask {
             task_name: params.task_name.to_string(),
             sub_tasks,
@@ -311,6 +327,7 @@ fn build_fuzzer_sub_task(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::task_types::resmoke_tasks::SubSuite;
     use rstest::*;
     use shrub_rs::models::commands::EvgCommand;
 
@@ -509,4 +526,31 @@ mod tests {
             "archive_dist_test_debug"
         )
     }
+
+    // generate_fuzzer_task tests.
+    struct TagTaskDecorator;
+    impl TaskDecorator for TagTaskDecorator {
+        fn decorate(&self, task: &mut EvgTask, sub_suite: Option<&SubSuite>) {
+            assert!(sub_suite.is_none());
+            task.tags = Some(vec!["fuzzer".to_string()]);
+        }
+    }
+
+    #[test]
+    fn test_generate_fuzzer_task_should_decorate_every_sub_task() {
+        let gen_fuzzer_service = GenFuzzerServiceImpl::new(Some(Arc::new(TagTaskDecorator)));
+        let params = FuzzerGenTaskParams {
+            task_name: "my_fuzzer".to_string(),
+            num_tasks: 3,
+            ..Default::default()
+        };
+
+        let fuzzer_task = gen_fuzzer_service.generate_fuzzer_task(&params).unwrap();
+
+        let sub_tasks = fuzzer_task.sub_tasks();
+        assert_eq!(sub_tasks.len(), 3);
+        for sub_task in sub_tasks {
+            assert_eq!(sub_task.evg_task.tags, Some(vec!["fuzzer".to_string()]));
+        }
+    }
 }
@@ -11,11 +11,19 @@ use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 use anyhow::Result;
 use async_trait::async_trait;
+use serde::Serialize;
 use tokio::sync::{mpsc, oneshot};
+use tracing::error;
 
 use crate::{
-    resmoke::{resmoke_proxy::TestDiscovery, resmoke_suite::ResmokeSuiteConfig},
-    utils::{fs_service::FsService, task_name::name_generated_task},
+    resmoke::{
+        resmoke_proxy::TestDiscovery,
+        resmoke_suite::{FixturePolicy, ResmokeSuiteConfig},
+    },
+    utils::{
+        fs_service::FsService,
+        task_name::{name_generated_task, SuiteFileNaming},
+    },
 };
 
 use super::resmoke_tasks::{ResmokeSuiteGenerationInfo, SubSuite};
@@ -28,6 +36,209 @@ enum ResmokeConfigMessage {
 
     /// Wait for all in-flight config files to be written to disk.
     Flush(oneshot::Sender<Vec<String>>),
+
+    /// Get the statistics collected so far.
+    Stats(oneshot::Sender<GenerationStats>),
+
+    /// Get the manifest entries recorded so far.
+    Manifest(oneshot::Sender<Vec<ManifestSuite>>),
+
+    /// Write any files held back by `defer_writes` to disk.
+    Commit(oneshot::Sender<()>),
+}
+
+/// Options controlling how resmoke configuration files are written.
+#[derive(Clone, Debug, Default)]
+pub struct WriteConfigOptions {
+    /// Maximum number of inline roots a generated suite can have before its roots are written
+    /// to a separate file, or `None` to always keep roots inline.
+    pub max_inline_roots: Option<usize>,
+    /// Sort `roots` and `exclude_files` alphabetically before writing so the contents of
+    /// generated files do not depend on the order tests were distributed in.
+    pub sort_paths: bool,
+    /// Record the suites that were written so a manifest of them can be produced. This is
+    /// enabled automatically when a manifest file is requested.
+    pub record_manifest: bool,
+    /// Scheme used to name the generated suite files.
+    pub suite_file_naming: SuiteFileNaming,
+    /// Policy to scale the fixture of generated suites with their number of tests, or `None`
+    /// to keep the fixture of the original suite.
+    pub fixture_policy: Option<FixturePolicy>,
+    /// Hold generated files in memory until `commit` is called instead of writing them as they
+    /// are generated, so nothing is written if generation is abandoned.
+    pub defer_writes: bool,
+}
+
+/// Version of the manifest schema, incremented whenever the schema changes incompatibly.
+pub const MANIFEST_SCHEMA_VERSION: u32 = 1;
+
+/// Machine-readable record of the suites produced by a generation run.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GenerationManifest {
+    /// Version of the schema of this manifest.
+    pub schema_version: u32,
+    /// Generated tasks, sorted by task name.
+    pub suites: Vec<ManifestSuite>,
+}
+
+impl GenerationManifest {
+    /// Create a manifest of the given generated tasks.
+    ///
+    /// # Arguments
+    ///
+    /// * `suites` - Generated tasks to include, in any order.
+    ///
+    /// # Returns
+    ///
+    /// Manifest with the tasks sorted by task name.
+    pub fn new(mut suites: Vec<ManifestSuite>) -> Self {
+        suites.sort_by(|a, b| a.task_name.cmp(&b.task_name));
+        Self {
+            schema_version: MANIFEST_SCHEMA_VERSION,
+            suites,
+        }
+    }
+}
+
+/// Manifest entry for a generated task.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ManifestSuite {
+    /// Name of the generated task.
+    pub task_name: String,
+    /// Name of resmoke suite the generated task is based on.
+    pub origin_suite: String,
+    /// Sub-suites the task was split into.
+    pub sub_suites: Vec<ManifestSubSuite>,
+}
+
+/// Manifest entry for a generated sub-suite.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ManifestSubSuite {
+    /// Name of the generated sub-suite.
+    pub name: String,
+    /// Tests run by the sub-suite.
+    pub tests: Vec<String>,
+    /// Estimated runtime (in seconds) of the sub-suite, if historic data exists.
+    pub estimated_runtime: Option<f64>,
+}
+
+impl From<&ResmokeSuiteGenerationInfo> for ManifestSuite {
+    fn from(suite_info: &ResmokeSuiteGenerationInfo) -> Self {
+        let total_tasks = suite_info.sub_suites.len();
+        ManifestSuite {
+            task_name: suite_info.task_name.clone(),
+            origin_suite: suite_info.origin_suite.clone(),
+            sub_suites: suite_info
+                .sub_suites
+                .iter()
+                .map(|s| ManifestSubSuite {
+                    name: name_generated_task(
+                        &s.name,
+                        s.index,
+                        total_tasks,
+                        s.is_enterprise,
+                        s.platform.as_deref(),
+                    ),
+                    tests: s.test_list.clone(),
+                    estimated_runtime: s.estimated_runtime,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Statistics about the suites handled during a generation run.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct GenerationStats {
+    /// Number of distinct resmoke suites that were parsed successfully.
+    pub suites_parsed: usize,
+    /// Number of distinct resmoke suites that failed to parse.
+    pub suites_failed: usize,
+    /// Total number of sub-suites produced.
+    pub sub_suites: usize,
+    /// Total number of tests distributed across the sub-suites.
+    pub tests_distributed: usize,
+    /// Names of the resmoke suites that were parsed successfully, sorted.
+    pub parsed_suites: Vec<String>,
+    /// Names of the resmoke suites that failed to parse, sorted.
+    pub failed_suites: Vec<String>,
+}
+
+impl GenerationStats {
+    /// Average number of tests in each sub-suite, or 0 if no sub-suites were produced.
+    pub fn avg_tests_per_sub_suite(&self) -> f64 {
+        if self.sub_suites == 0 {
+            0.0
+        } else {
+            self.tests_distributed as f64 / self.sub_suites as f64
+        }
+    }
+
+    /// Add the sub-suites of the given generated task to these statistics.
+    ///
+    /// # Arguments
+    ///
+    /// * `suite_info` - Details about the generated task.
+    fn record_sub_suites(&mut self, suite_info: &ResmokeSuiteGenerationInfo) {
+        self.sub_suites += suite_info.sub_suites.len();
+        self.tests_distributed += suite_info
+            .sub_suites
+            .iter()
+            .map(|s| s.test_list.len())
+            .sum::<usize>();
+    }
+
+    /// Record that the given resmoke suite was parsed successfully.
+    ///
+    /// Suites are only counted once, no matter how many generated tasks use them.
+    ///
+    /// # Arguments
+    ///
+    /// * `suite_name` - Name of suite that was parsed.
+    fn record_parsed_suite(&mut self, suite_name: &str) {
+        insert_sorted(&mut self.parsed_suites, suite_name);
+        self.suites_parsed = self.parsed_suites.len();
+    }
+
+    /// Record that the given resmoke suite failed to parse.
+    ///
+    /// Suites are only counted once, no matter how many generated tasks use them.
+    ///
+    /// # Arguments
+    ///
+    /// * `suite_name` - Name of suite that failed to parse.
+    fn record_failed_suite(&mut self, suite_name: &str) {
+        insert_sorted(&mut self.failed_suites, suite_name);
+        self.suites_failed = self.failed_suites.len();
+    }
+
+    /// Add the given statistics to these statistics.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Statistics to add.
+    fn merge(&mut self, other: &GenerationStats) {
+        self.sub_suites += other.sub_suites;
+        self.tests_distributed += other.tests_distributed;
+        for suite_name in &other.parsed_suites {
+            self.record_parsed_suite(suite_name);
+        }
+        for suite_name in &other.failed_suites {
+            self.record_failed_suite(suite_name);
+        }
+    }
+}
+
+/// Insert a name into a sorted list of names if it is not already present.
+///
+/// # Arguments
+///
+/// * `names` - Sorted list of names to insert into.
+/// * `name` - Name to insert.
+fn insert_sorted(names: &mut Vec<String>, name: &str) {
+    if let Err(index) = names.binary_search_by(|n| n.as_str().cmp(name)) {
+        names.insert(index, name.to_string());
+    }
 }
 
 /// The actor implementation that performs actions based on received messages.
@@ -44,8 +255,20 @@ struct WriteConfigActorImpl {
     /// Directory to write generated files to.
     target_dir: String,
 
+    /// Options controlling how files are written.
+    options: WriteConfigOptions,
+
     /// Errors encountered during execution.
     errors: Vec<String>,
+
+    /// Statistics collected during execution.
+    stats: GenerationStats,
+
+    /// Manifest entries for the suites written, if requested.
+    manifest: Vec<ManifestSuite>,
+
+    /// Files waiting to be written when writes are deferred.
+    pending_files: Vec<(PathBuf, String)>,
 }
 
 impl WriteConfigActorImpl {
@@ -57,6 +280,7 @@ impl WriteConfigActorImpl {
     /// * `fs_service` - Service to work with the filesystem.
     /// * `receiver` - Mailbox to query for messages.
     /// * `target_dir` - Directory to write generated files to.
+    /// * `options` - Options controlling how files are written.
     ///
     /// # Returns
     ///
@@ -66,13 +290,18 @@ impl WriteConfigActorImpl {
         fs_service: Arc<dyn FsService>,
         receiver: mpsc::Receiver<ResmokeConfigMessage>,
         target_dir: String,
+        options: WriteConfigOptions,
     ) -> Self {
         WriteConfigActorImpl {
             test_discovery,
             fs_service,
             target_dir,
+            options,
             receiver,
             errors: vec![],
+            stats: GenerationStats::default(),
+            manifest: vec![],
+            pending_files: vec![],
         }
     }
 
@@ -92,6 +321,24 @@ impl WriteConfigActorImpl {
         match msg {
             ResmokeConfigMessage::SuiteFiles(suite_info) => self.write_suite_files(suite_info),
             ResmokeConfigMessage::Flush(sender) => sender.send(self.errors.clone()).unwrap(),
+            ResmokeConfigMessage::Stats(sender) => sender.send(self.stats.clone()).unwrap(),
+            ResmokeConfigMessage::Manifest(sender) => sender.send(self.manifest.clone()).unwrap(),
+            ResmokeConfigMessage::Commit(sender) => {
+                self.commit();
+                sender.send(()).unwrap()
+            }
+        }
+    }
+
+    /// Write all the files held back because writes are deferred.
+    ///
+    /// Errors are saved off to be reported on flush.
+    fn commit(&mut self) {
+        for (path, contents) in std::mem::take(&mut self.pending_files) {
+            if let Err(error) = self.fs_service.write_file(&path, &contents) {
+                self.errors
+                    .push(format!("ERROR: {}: {}", path.display(), error));
+            }
         }
     }
 
@@ -115,53 +362,110 @@ impl WriteConfigActorImpl {
     /// # Arguments
     ///
     /// * `suite_info` - Details about the generated task.
-    fn write_standard_suite(&self, suite_info: &ResmokeSuiteGenerationInfo) -> Result<()> {
+    fn write_standard_suite(&mut self, suite_info: &ResmokeSuiteGenerationInfo) -> Result<()> {
         let mut resmoke_config_cache = ResmokeConfigCache::new(self.test_discovery.clone());
 
         // Create suite files for all the sub-suites.
-        self.write_sub_suites(&suite_info.sub_suites, &mut resmoke_config_cache)?;
+        let result = self
+            .build_sub_suite_files(&suite_info.sub_suites, &mut resmoke_config_cache)
+            .and_then(|files| self.write_files(files));
+
+        for suite_name in &resmoke_config_cache.parsed_suites {
+            self.stats.record_parsed_suite(suite_name);
+        }
+        for suite_name in &resmoke_config_cache.failed_suites {
+            self.stats.record_failed_suite(suite_name);
+        }
+        if result.is_ok() {
+            self.stats.record_sub_suites(suite_info);
+            if self.options.record_manifest {
+                self.manifest.push(ManifestSuite::from(suite_info));
+            }
+        }
+        result
+    }
 
+    /// Write the given files, or hold them back until commit if writes are deferred.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - Paths and contents of the files to write.
+    fn write_files(&mut self, files: Vec<(PathBuf, String)>) -> Result<()> {
+        if self.options.defer_writes {
+            self.pending_files.extend(files);
+            return Ok(());
+        }
+        for (path, contents) in files {
+            self.fs_service.write_file(&path, &contents)?;
+        }
         Ok(())
     }
 
-    /// Write resmoke configurations for the given sub-suites.
+    /// Build the resmoke configuration files for the given sub-suites.
     ///
     /// # Arguments
     ///
-    /// * `sub_suites` - List of sub-suites to write configuration for.
+    /// * `sub_suites` - List of sub-suites to build configuration for.
     /// * `resmoke_config_cache` - Cache to get resmoke suite configurations.
-    fn write_sub_suites(
+    ///
+    /// # Returns
+    ///
+    /// Paths and contents of the files to write, including any spilled root files.
+    fn build_sub_suite_files(
         &self,
         sub_suites: &[SubSuite],
         resmoke_config_cache: &mut ResmokeConfigCache,
-    ) -> Result<()> {
+    ) -> Result<Vec<(PathBuf, String)>> {
         let total_tasks = sub_suites.len();
-        let results: Result<Vec<()>> = sub_suites
-            .iter()
-            .filter(|s| s.exclude_test_list.is_none())
-            .map(|s| {
-                let origin_config = resmoke_config_cache.get_config(&s.origin_suite)?;
-                let config = origin_config.with_new_tests(Some(&s.test_list), None);
-
-                let filename = format!(
-                    "{}.yml",
-                    name_generated_task(
-                        &s.name,
-                        s.index,
-                        total_tasks,
-                        s.is_enterprise,
-                        s.platform.as_deref()
-                    )
+        let mut files = vec![];
+        for s in sub_suites.iter().filter(|s| s.exclude_test_list.is_none()) {
+            let origin_config = resmoke_config_cache.get_config(&s.origin_suite)?;
+            let mut config = s.build_config(origin_config);
+
+            let filename = format!(
+                "{}.yml",
+                self.options.suite_file_naming.suite_file_name(
+                    &s.name,
+                    s.index,
+                    total_tasks,
+                    s.is_enterprise,
+                    s.platform.as_deref()
+                )
+            );
+            let mut path = PathBuf::from(&self.target_dir);
+            path.push(filename);
+
+            if self.options.sort_paths {
+                config = config.with_sorted_paths();
+            }
+
+            if let Some(fixture_policy) = self.options.fixture_policy {
+                config.scale_fixture(fixture_policy)?;
+            }
+
+            // Suites with a large number of roots read their roots from a separate file.
+            if let Some(max_inline_roots) = self.options.max_inline_roots {
+                if let Some(spilled) = config.with_spilled_roots(&path, max_inline_roots) {
+                    files.push((spilled.roots_path, spilled.roots_contents));
+                    config = spilled.config;
+                }
+            }
+
+            let contents = config.to_string_with_header(&format!(
+                "Generated from '{}' by mongo-task-generator.",
+                s.origin_suite
+            ));
+            if contents.is_err() {
+                error!(
+                    suite_name = s.name.as_str(),
+                    origin_suite = s.origin_suite.as_str(),
+                    "Failed to serialize resmoke suite configuration",
                 );
-                let mut path = PathBuf::from(&self.target_dir);
-                path.push(filename);
+            }
 
-                self.fs_service.write_file(&path, &config.to_string())?;
-                Ok(())
-            })
-            .collect();
-        results?;
-        Ok(())
+            files.push((path, contents?));
+        }
+        Ok(files)
     }
 }
 
@@ -172,6 +476,23 @@ pub trait ResmokeConfigActor: Sync + Send {
 
     /// Wait for all in-progress writes to be completed before returning.
     async fn flush(&mut self) -> Result<Vec<String>>;
+
+    /// Get statistics about the suites handled so far.
+    async fn stats(&mut self) -> Result<GenerationStats> {
+        Ok(GenerationStats::default())
+    }
+
+    /// Get a manifest of the suites written so far.
+    async fn manifest(&mut self) -> Result<GenerationManifest> {
+        Ok(GenerationManifest::new(vec![]))
+    }
+
+    /// Write any files held back because writes are deferred.
+    ///
+    /// Errors writing the files are reported by the next flush.
+    async fn commit(&mut self) -> Result<()> {
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -190,6 +511,7 @@ impl ResmokeConfigActorService {
     /// # Arguments
     ///
     /// * `target_dir` - Directory to write generated configuration file to.
+    /// * `options` - Options controlling how files are written.
     ///
     /// # Returns
     ///
@@ -199,6 +521,7 @@ impl ResmokeConfigActorService {
         fs_service: Arc<dyn FsService>,
         target_dir: &str,
         n_workers: usize,
+        options: WriteConfigOptions,
     ) -> Self {
         let senders_and_receivers = (0..n_workers).map(|_| mpsc::channel(100));
         let mut senders = vec![];
@@ -211,6 +534,7 @@ impl ResmokeConfigActorService {
                     fs_service.clone(),
                     receiver,
                     target_dir.to_string(),
+                    options.clone(),
                 );
                 tokio::spawn(async move { actor.run().await });
             });
@@ -253,6 +577,144 @@ impl ResmokeConfigActor for ResmokeConfigActorService {
         }
         Ok(errors)
     }
+
+    /// Get statistics about the suites handled so far, combined across all workers.
+    ///
+    /// # Returns
+    ///
+    /// Statistics collected by all workers.
+    async fn stats(&mut self) -> Result<GenerationStats> {
+        let mut stats = GenerationStats::default();
+        for sender in &self.senders {
+            let (send, recv) = oneshot::channel();
+            let msg = ResmokeConfigMessage::Stats(send);
+            sender.send(msg).await?;
+            stats.merge(&recv.await?);
+        }
+        Ok(stats)
+    }
+
+    /// Get a manifest of the suites written so far, combined across all workers.
+    ///
+    /// Suites are only recorded when the actor was created with `record_manifest` set.
+    ///
+    /// # Returns
+    ///
+    /// Manifest of the suites written by all workers.
+    async fn manifest(&mut self) -> Result<GenerationManifest> {
+        let mut suites = vec![];
+        for sender in &self.senders {
+            let (send, recv) = oneshot::channel();
+            let msg = ResmokeConfigMessage::Manifest(send);
+            sender.send(msg).await?;
+            suites.extend(recv.await?);
+        }
+        Ok(GenerationManifest::new(suites))
+    }
+
+    /// Write any files held back because writes are deferred, across all workers.
+    ///
+    /// Writes are only deferred when the actor was created with `defer_writes` set. Errors
+    /// writing the files are reported by the next flush.
+    async fn commit(&mut self) -> Result<()> {
+        for sender in &self.senders {
+            let (send, recv) = oneshot::channel();
+            let msg = ResmokeConfigMessage::Commit(send);
+            sender.send(msg).await?;
+            recv.await?;
+        }
+        Ok(())
+    }
+}
+
+/// Summary of how a generated task was split into sub-suites.
+#[derive(Clone, Debug, Serialize)]
+pub struct SuitePlan {
+    /// Name of the generated task.
+    pub task_name: String,
+    /// Name of resmoke suite the generated task is based on.
+    pub origin_suite: String,
+    /// Sub-suites the task was split into.
+    pub sub_suites: Vec<SubSuitePlan>,
+}
+
+/// Summary of a single generated sub-suite.
+#[derive(Clone, Debug, Serialize)]
+pub struct SubSuitePlan {
+    /// Name of the generated sub-suite.
+    pub name: String,
+    /// Number of tests run by the sub-suite.
+    pub test_count: usize,
+    /// Estimated runtime (in seconds) of the sub-suite, if historic data exists.
+    pub estimated_runtime: Option<f64>,
+}
+
+impl From<&ResmokeSuiteGenerationInfo> for SuitePlan {
+    fn from(suite_info: &ResmokeSuiteGenerationInfo) -> Self {
+        let total_tasks = suite_info.sub_suites.len();
+        SuitePlan {
+            task_name: suite_info.task_name.clone(),
+            origin_suite: suite_info.origin_suite.clone(),
+            sub_suites: suite_info
+                .sub_suites
+                .iter()
+                .map(|s| SubSuitePlan {
+                    name: name_generated_task(
+                        &s.name,
+                        s.index,
+                        total_tasks,
+                        s.is_enterprise,
+                        s.platform.as_deref(),
+                    ),
+                    test_count: s.test_list.len(),
+                    estimated_runtime: s.estimated_runtime,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Actor that records how generated tasks were split instead of writing any files.
+#[derive(Clone, Debug, Default)]
+pub struct DryRunConfigActor {
+    /// Plans for all the generated tasks received so far.
+    plans: Vec<SuitePlan>,
+
+    /// Statistics about the generated tasks received so far.
+    stats: GenerationStats,
+}
+
+impl DryRunConfigActor {
+    /// Create a new instance of the actor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the plans for all the generated tasks received so far.
+    pub fn plans(&self) -> &[SuitePlan] {
+        &self.plans
+    }
+}
+
+#[async_trait]
+impl ResmokeConfigActor for DryRunConfigActor {
+    /// Record the split plan for the given generated task.
+    async fn write_sub_suite(&mut self, gen_suite: &ResmokeSuiteGenerationInfo) {
+        self.plans.push(SuitePlan::from(gen_suite));
+        self.stats.record_sub_suites(gen_suite);
+    }
+
+    /// Nothing is written, so there are never any errors to report.
+    async fn flush(&mut self) -> Result<Vec<String>> {
+        Ok(vec![])
+    }
+
+    /// Get statistics about the generated tasks received so far.
+    ///
+    /// No suites are parsed while planning, so only sub-suite and test counts are reported.
+    async fn stats(&mut self) -> Result<GenerationStats> {
+        Ok(self.stats.clone())
+    }
 }
 
 /// A cache for querying resmoke suite configurations.
@@ -261,6 +723,10 @@ struct ResmokeConfigCache {
     test_discovery: Arc<dyn TestDiscovery>,
     /// Resmoke suite configurations that have already been queried.
     resmoke_configs: HashMap<String, ResmokeSuiteConfig>,
+    /// Names of suite configurations that were parsed successfully.
+    parsed_suites: Vec<String>,
+    /// Names of suite configurations that failed to parse.
+    failed_suites: Vec<String>,
 }
 
 impl ResmokeConfigCache {
@@ -269,6 +735,8 @@ impl ResmokeConfigCache {
         Self {
             test_discovery,
             resmoke_configs: HashMap::new(),
+            parsed_suites: vec![],
+            failed_suites: vec![],
         }
     }
 
@@ -283,7 +751,12 @@ impl ResmokeConfigCache {
     /// Resmoke suite configuration for given suite.
     pub fn get_config<'a>(&'a mut self, suite_name: &str) -> Result<&'a ResmokeSuiteConfig> {
         if !self.resmoke_configs.contains_key(suite_name) {
-            let config = self.test_discovery.get_suite_config(suite_name)?;
+            let config = self.test_discovery.get_suite_config(suite_name);
+            if config.is_err() {
+                self.failed_suites.push(suite_name.to_string());
+            }
+            let config = config?;
+            self.parsed_suites.push(suite_name.to_string());
             self.resmoke_configs.insert(suite_name.to_string(), config);
         }
 
@@ -300,7 +773,11 @@ mod tests {
 
     use anyhow::bail;
 
-    use crate::{resmoke::resmoke_suite::ResmokeSuiteConfig, task_types::resmoke_tasks::SubSuite};
+    use crate::{
+        resmoke::resmoke_suite::{ResmokeSuiteConfig, TestRoot},
+        task_types::resmoke_tasks::SubSuite,
+        utils::fs_service::InMemoryFsService,
+    };
 
     use super::*;
 
@@ -364,6 +841,12 @@ mod tests {
             }
         }
 
+        pub fn has_calls(&self, path: &str) -> bool {
+            let call_counts = self.call_counts.lock().unwrap();
+            let call_counts_table = call_counts.borrow();
+            call_counts_table.contains_key(path)
+        }
+
         pub fn get_call_counts(&self, path: &str) -> usize {
             let call_counts = self.call_counts.lock().unwrap();
             let call_counts_table = call_counts.borrow();
@@ -388,13 +871,23 @@ mod tests {
             }
             Ok(())
         }
+
+        fn create_dir_all(&self, _path: &std::path::Path) -> anyhow::Result<()> {
+            Ok(())
+        }
     }
 
     fn build_mock_service(fs_service: Arc<dyn FsService>) -> WriteConfigActorImpl {
         let test_discovery = Arc::new(MockTestDiscovery {});
         let (_tx, rx) = mpsc::channel(1);
 
-        WriteConfigActorImpl::new(test_discovery, fs_service, rx, "target".to_string())
+        WriteConfigActorImpl::new(
+            test_discovery,
+            fs_service,
+            rx,
+            "target".to_string(),
+            WriteConfigOptions::default(),
+        )
     }
 
     #[test]
@@ -429,12 +922,294 @@ mod tests {
         assert_eq!(fs_service.get_call_counts("target/suite_name_1.yml"), 1);
     }
 
+    #[test]
+    fn test_write_suite_files_should_use_suite_file_naming() {
+        let fs_service = Arc::new(MockFsService::new());
+        let test_discovery = Arc::new(MockTestDiscovery {});
+        let (_tx, rx) = mpsc::channel(1);
+        let mut resmoke_config_actor = WriteConfigActorImpl::new(
+            test_discovery,
+            fs_service.clone(),
+            rx,
+            "target".to_string(),
+            WriteConfigOptions {
+                suite_file_naming: SuiteFileNaming::ZeroPadded(3),
+                ..Default::default()
+            },
+        );
+        let suite_info = ResmokeSuiteGenerationInfo {
+            task_name: "my_task".to_string(),
+            origin_suite: "original_suite".to_string(),
+            require_multiversion_generate_tasks: false,
+            sub_suites: vec![
+                SubSuite {
+                    index: 0,
+                    name: "suite_name".to_string(),
+                    origin_suite: "suite".to_string(),
+                    test_list: vec!["test_0.js".to_string()],
+                    ..Default::default()
+                },
+                SubSuite {
+                    index: 1,
+                    name: "suite_name".to_string(),
+                    origin_suite: "suite".to_string(),
+                    test_list: vec!["test_1.js".to_string()],
+                    ..Default::default()
+                },
+            ],
+        };
+
+        resmoke_config_actor.write_suite_files(suite_info);
+
+        assert!(resmoke_config_actor.errors.is_empty());
+        assert_eq!(fs_service.get_call_counts("target/suite_name_000.yml"), 1);
+        assert_eq!(fs_service.get_call_counts("target/suite_name_001.yml"), 1);
+        assert!(!fs_service.has_calls("target/suite_name_0.yml"));
+    }
+
+    #[test]
+    fn test_write_suite_files_should_hold_back_deferred_writes_until_commit() {
+        let fs_service = Arc::new(MockFsService::new());
+        let test_discovery = Arc::new(MockTestDiscovery {});
+        let (_tx, rx) = mpsc::channel(1);
+        let mut resmoke_config_actor = WriteConfigActorImpl::new(
+            test_discovery,
+            fs_service.clone(),
+            rx,
+            "target".to_string(),
+            WriteConfigOptions {
+                defer_writes: true,
+                ..Default::default()
+            },
+        );
+        let suite_info = ResmokeSuiteGenerationInfo {
+            task_name: "my_task".to_string(),
+            origin_suite: "original_suite".to_string(),
+            require_multiversion_generate_tasks: false,
+            sub_suites: vec![SubSuite {
+                index: 0,
+                name: "suite_name".to_string(),
+                origin_suite: "suite".to_string(),
+                test_list: vec!["test_0.js".to_string()],
+                ..Default::default()
+            }],
+        };
+
+        resmoke_config_actor.write_suite_files(suite_info);
+
+        assert!(resmoke_config_actor.errors.is_empty());
+        assert!(!fs_service.has_calls("target/suite_name_0.yml"));
+
+        resmoke_config_actor.commit();
+
+        assert!(resmoke_config_actor.errors.is_empty());
+        assert_eq!(fs_service.get_call_counts("target/suite_name_0.yml"), 1);
+    }
+
+    #[test]
+    fn test_write_suite_files_should_spill_large_roots() {
+        let fs_service = Arc::new(MockFsService::new());
+        let test_discovery = Arc::new(MockTestDiscovery {});
+        let (_tx, rx) = mpsc::channel(1);
+        let mut resmoke_config_actor = WriteConfigActorImpl::new(
+            test_discovery,
+            fs_service.clone(),
+            rx,
+            "target".to_string(),
+            WriteConfigOptions {
+                max_inline_roots: Some(2),
+                ..Default::default()
+            },
+        );
+        let suite_info = ResmokeSuiteGenerationInfo {
+            task_name: "my_task".to_string(),
+            origin_suite: "original_suite".to_string(),
+            require_multiversion_generate_tasks: false,
+            sub_suites: vec![
+                SubSuite {
+                    index: 0,
+                    name: "suite_name".to_string(),
+                    origin_suite: "suite".to_string(),
+                    test_list: vec![
+                        "test_0.js".to_string(),
+                        "test_1.js".to_string(),
+                        "test_2.js".to_string(),
+                    ],
+                    ..Default::default()
+                },
+                SubSuite {
+                    index: 1,
+                    name: "suite_name".to_string(),
+                    origin_suite: "suite".to_string(),
+                    test_list: vec!["test_3.js".to_string()],
+                    ..Default::default()
+                },
+            ],
+        };
+
+        resmoke_config_actor.write_suite_files(suite_info);
+
+        assert!(resmoke_config_actor.errors.is_empty());
+        assert_eq!(fs_service.get_call_counts("target/suite_name_0.yml"), 1);
+        assert_eq!(
+            fs_service.get_call_counts("target/suite_name_0_roots.txt"),
+            1
+        );
+        assert_eq!(fs_service.get_call_counts("target/suite_name_1.yml"), 1);
+        assert!(!fs_service.has_calls("target/suite_name_1_roots.txt"));
+    }
+
+    #[test]
+    fn test_write_suite_files_should_capture_contents_in_memory() {
+        let fs_service = Arc::new(InMemoryFsService::new());
+        let mut resmoke_config_actor = build_mock_service(fs_service.clone());
+        let suite_info = ResmokeSuiteGenerationInfo {
+            task_name: "my_task".to_string(),
+            origin_suite: "original_suite".to_string(),
+            require_multiversion_generate_tasks: false,
+            sub_suites: vec![
+                SubSuite {
+                    index: 0,
+                    name: "suite_name".to_string(),
+                    origin_suite: "suite".to_string(),
+                    test_list: vec!["test_0.js".to_string(), "test_1.js".to_string()],
+                    ..Default::default()
+                },
+                SubSuite {
+                    index: 1,
+                    name: "suite_name".to_string(),
+                    origin_suite: "suite".to_string(),
+                    test_list: vec!["test_2.js".to_string()],
+                    ..Default::default()
+                },
+            ],
+        };
+
+        resmoke_config_actor.write_suite_files(suite_info);
+
+        assert!(resmoke_config_actor.errors.is_empty());
+        let files = fs_service.files();
+        assert_eq!(files.len(), 2);
+        let suite_0 = ResmokeSuiteConfig::from_str(&files["target/suite_name_0.yml"]).unwrap();
+        assert_eq!(
+            suite_0.selector.test_root,
+            Some(TestRoot::Roots {
+                roots: vec!["test_0.js".to_string(), "test_1.js".to_string()]
+            })
+        );
+        let suite_1 = ResmokeSuiteConfig::from_str(&files["target/suite_name_1.yml"]).unwrap();
+        assert_eq!(
+            suite_1.selector.test_root,
+            Some(TestRoot::Roots {
+                roots: vec!["test_2.js".to_string()]
+            })
+        );
+        assert!(files["target/suite_name_1.yml"].contains("Generated from 'suite'"));
+    }
+
+    struct MockFixtureTestDiscovery {}
+    impl TestDiscovery for MockFixtureTestDiscovery {
+        fn discover_tests(&self, _suite_name: &str) -> anyhow::Result<Vec<String>> {
+            Ok(vec![])
+        }
+
+        fn get_suite_config(&self, _suite_name: &str) -> anyhow::Result<ResmokeSuiteConfig> {
+            Ok(ResmokeSuiteConfig::from_str(
+                "
+                test_kind: js_test
+
+                selector:
+                  roots:
+                    - jstests/core/*.js
+
+                executor:
+                  fixture:
+                    class: ReplicaSetFixture
+                    num_nodes: 3
+                ",
+            )?)
+        }
+
+        fn get_multiversion_config(
+            &self,
+        ) -> anyhow::Result<crate::resmoke::resmoke_proxy::MultiversionConfig> {
+            Ok(crate::resmoke::resmoke_proxy::MultiversionConfig {
+                last_versions: vec![],
+                requires_fcv_tag: "requires_fcv".to_string(),
+                requires_fcv_tag_lts: None,
+                requires_fcv_tag_continuous: None,
+            })
+        }
+    }
+
+    #[test]
+    fn test_write_suite_files_should_apply_fixture_policy() {
+        let fs_service = Arc::new(InMemoryFsService::new());
+        let (_tx, rx) = mpsc::channel(1);
+        let mut resmoke_config_actor = WriteConfigActorImpl::new(
+            Arc::new(MockFixtureTestDiscovery {}),
+            fs_service.clone(),
+            rx,
+            "target".to_string(),
+            WriteConfigOptions {
+                fixture_policy: Some(FixturePolicy {
+                    min_nodes: 1,
+                    max_nodes: 5,
+                    tests_per_node: 2,
+                }),
+                ..Default::default()
+            },
+        );
+        let suite_info = ResmokeSuiteGenerationInfo {
+            task_name: "my_task".to_string(),
+            origin_suite: "original_suite".to_string(),
+            require_multiversion_generate_tasks: false,
+            sub_suites: vec![
+                SubSuite {
+                    index: 0,
+                    name: "suite_name".to_string(),
+                    origin_suite: "suite".to_string(),
+                    test_list: vec!["test_0.js".to_string(), "test_1.js".to_string()],
+                    ..Default::default()
+                },
+                SubSuite {
+                    index: 1,
+                    name: "suite_name".to_string(),
+                    origin_suite: "suite".to_string(),
+                    test_list: (0..10).map(|i| format!("test_{}.js", i + 2)).collect(),
+                    ..Default::default()
+                },
+            ],
+        };
+
+        resmoke_config_actor.write_suite_files(suite_info);
+
+        assert!(resmoke_config_actor.errors.is_empty());
+        let files = fs_service.files();
+        let num_nodes = |path: &str| {
+            ResmokeSuiteConfig::from_str(&files[path])
+                .unwrap()
+                .executor
+                .fixture_config()
+                .unwrap()
+                .unwrap()
+                .num_nodes()
+        };
+        assert_eq!(num_nodes("target/suite_name_0.yml"), Some(1));
+        assert_eq!(num_nodes("target/suite_name_1.yml"), Some(3));
+    }
+
     #[tokio::test]
     async fn test_errors_encountered_during_execution() {
         let fs_service = Arc::new(MockFsService::new_failure_mode());
         let test_discovery = Arc::new(MockTestDiscovery {});
-        let mut resmoke_config_actor =
-            ResmokeConfigActorService::new(test_discovery, fs_service, "target_dir", 3);
+        let mut resmoke_config_actor = ResmokeConfigActorService::new(
+            test_discovery,
+            fs_service,
+            "target_dir",
+            3,
+            WriteConfigOptions::default(),
+        );
         let suite_info = ResmokeSuiteGenerationInfo {
             task_name: "my_task".to_string(),
             origin_suite: "original_suite".to_string(),
@@ -465,4 +1240,295 @@ mod tests {
 
         assert_eq!(errors.len(), n_operations);
     }
+
+    #[tokio::test]
+    async fn test_stats_should_match_generated_suites() {
+        let fs_service = Arc::new(MockFsService::new());
+        let test_discovery = Arc::new(MockTestDiscovery {});
+        let mut resmoke_config_actor = ResmokeConfigActorService::new(
+            test_discovery,
+            fs_service,
+            "target_dir",
+            2,
+            WriteConfigOptions::default(),
+        );
+        let suite_info = ResmokeSuiteGenerationInfo {
+            task_name: "my_task".to_string(),
+            origin_suite: "original_suite".to_string(),
+            require_multiversion_generate_tasks: false,
+            sub_suites: vec![
+                SubSuite {
+                    index: 0,
+                    name: "suite".to_string(),
+                    origin_suite: "suite".to_string(),
+                    test_list: vec![
+                        "test_0.js".to_string(),
+                        "test_1.js".to_string(),
+                        "test_2.js".to_string(),
+                    ],
+                    ..Default::default()
+                },
+                SubSuite {
+                    index: 1,
+                    name: "suite".to_string(),
+                    origin_suite: "suite".to_string(),
+                    test_list: vec!["test_3.js".to_string()],
+                    ..Default::default()
+                },
+            ],
+        };
+        let n_operations = 3;
+
+        for _ in 0..n_operations {
+            resmoke_config_actor.write_sub_suite(&suite_info).await;
+        }
+        let errors = resmoke_config_actor.flush().await.unwrap();
+        let stats = resmoke_config_actor.stats().await.unwrap();
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            stats,
+            GenerationStats {
+                suites_parsed: 1,
+                suites_failed: 0,
+                sub_suites: 2 * n_operations,
+                tests_distributed: 4 * n_operations,
+                parsed_suites: vec!["suite".to_string()],
+                failed_suites: vec![],
+            }
+        );
+        assert_eq!(stats.avg_tests_per_sub_suite(), 2.0);
+    }
+
+    struct MockBrokenTestDiscovery {}
+    impl TestDiscovery for MockBrokenTestDiscovery {
+        fn discover_tests(&self, _suite_name: &str) -> anyhow::Result<Vec<String>> {
+            Ok(vec![])
+        }
+
+        fn get_suite_config(&self, suite_name: &str) -> anyhow::Result<ResmokeSuiteConfig> {
+            if suite_name == "broken_suite" {
+                bail!("Could not parse suite");
+            }
+            MockTestDiscovery {}.get_suite_config(suite_name)
+        }
+
+        fn get_multiversion_config(
+            &self,
+        ) -> anyhow::Result<crate::resmoke::resmoke_proxy::MultiversionConfig> {
+            Ok(crate::resmoke::resmoke_proxy::MultiversionConfig {
+                last_versions: vec![],
+                requires_fcv_tag: "requires_fcv".to_string(),
+                requires_fcv_tag_lts: None,
+                requires_fcv_tag_continuous: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stats_should_count_suites_shared_by_tasks_once() {
+        let fs_service = Arc::new(MockFsService::new());
+        let test_discovery = Arc::new(MockBrokenTestDiscovery {});
+        let mut resmoke_config_actor = ResmokeConfigActorService::new(
+            test_discovery,
+            fs_service,
+            "target_dir",
+            2,
+            WriteConfigOptions::default(),
+        );
+        let build_suite_info = |task_name: &str, origin_suite: &str| ResmokeSuiteGenerationInfo {
+            task_name: task_name.to_string(),
+            origin_suite: origin_suite.to_string(),
+            require_multiversion_generate_tasks: false,
+            sub_suites: vec![SubSuite {
+                index: 0,
+                name: task_name.to_string(),
+                origin_suite: origin_suite.to_string(),
+                test_list: vec!["test_0.js".to_string()],
+                ..Default::default()
+            }],
+        };
+
+        for task_name in ["task_0", "task_1"] {
+            resmoke_config_actor
+                .write_sub_suite(&build_suite_info(task_name, "shared_suite"))
+                .await;
+        }
+        for task_name in ["task_2", "task_3"] {
+            resmoke_config_actor
+                .write_sub_suite(&build_suite_info(task_name, "broken_suite"))
+                .await;
+        }
+        let errors = resmoke_config_actor.flush().await.unwrap();
+        let stats = resmoke_config_actor.stats().await.unwrap();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(stats.suites_parsed, 1);
+        assert_eq!(stats.parsed_suites, vec!["shared_suite".to_string()]);
+        assert_eq!(stats.suites_failed, 1);
+        assert_eq!(stats.failed_suites, vec!["broken_suite".to_string()]);
+        assert_eq!(stats.sub_suites, 2);
+    }
+
+    #[tokio::test]
+    async fn test_manifest_should_match_generated_suites() {
+        let fs_service = Arc::new(MockFsService::new());
+        let test_discovery = Arc::new(MockTestDiscovery {});
+        let mut resmoke_config_actor = ResmokeConfigActorService::new(
+            test_discovery,
+            fs_service,
+            "target_dir",
+            2,
+            WriteConfigOptions {
+                record_manifest: true,
+                ..Default::default()
+            },
+        );
+        let build_suite_info = |task_name: &str| ResmokeSuiteGenerationInfo {
+            task_name: task_name.to_string(),
+            origin_suite: "original_suite".to_string(),
+            require_multiversion_generate_tasks: false,
+            sub_suites: vec![
+                SubSuite {
+                    index: 0,
+                    name: task_name.to_string(),
+                    origin_suite: "original_suite".to_string(),
+                    test_list: vec!["test_0.js".to_string(), "test_1.js".to_string()],
+                    estimated_runtime: Some(30.0),
+                    ..Default::default()
+                },
+                SubSuite {
+                    index: 1,
+                    name: task_name.to_string(),
+                    origin_suite: "original_suite".to_string(),
+                    test_list: vec!["test_2.js".to_string()],
+                    ..Default::default()
+                },
+            ],
+        };
+
+        resmoke_config_actor
+            .write_sub_suite(&build_suite_info("task_b"))
+            .await;
+        resmoke_config_actor
+            .write_sub_suite(&build_suite_info("task_a"))
+            .await;
+        let errors = resmoke_config_actor.flush().await.unwrap();
+        let manifest = resmoke_config_actor.manifest().await.unwrap();
+
+        assert!(errors.is_empty());
+        let expected_suite = |task_name: &str| ManifestSuite {
+            task_name: task_name.to_string(),
+            origin_suite: "original_suite".to_string(),
+            sub_suites: vec![
+                ManifestSubSuite {
+                    name: format!("{}_0", task_name),
+                    tests: vec!["test_0.js".to_string(), "test_1.js".to_string()],
+                    estimated_runtime: Some(30.0),
+                },
+                ManifestSubSuite {
+                    name: format!("{}_1", task_name),
+                    tests: vec!["test_2.js".to_string()],
+                    estimated_runtime: None,
+                },
+            ],
+        };
+        assert_eq!(
+            manifest,
+            GenerationManifest {
+                schema_version: MANIFEST_SCHEMA_VERSION,
+                suites: vec![expected_suite("task_a"), expected_suite("task_b")],
+            }
+        );
+        let manifest_json = serde_json::to_value(&manifest).unwrap();
+        assert_eq!(manifest_json["schema_version"], 1);
+        assert_eq!(
+            manifest_json["suites"][0]["sub_suites"][0]["tests"],
+            serde_json::json!(["test_0.js", "test_1.js"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_manifest_should_be_empty_unless_requested() {
+        let fs_service = Arc::new(MockFsService::new());
+        let test_discovery = Arc::new(MockTestDiscovery {});
+        let mut resmoke_config_actor = ResmokeConfigActorService::new(
+            test_discovery,
+            fs_service,
+            "target_dir",
+            1,
+            WriteConfigOptions::default(),
+        );
+        let suite_info = ResmokeSuiteGenerationInfo {
+            task_name: "my_task".to_string(),
+            origin_suite: "original_suite".to_string(),
+            require_multiversion_generate_tasks: false,
+            sub_suites: vec![SubSuite {
+                index: 0,
+                name: "suite".to_string(),
+                origin_suite: "original_suite".to_string(),
+                test_list: vec!["test_0.js".to_string()],
+                ..Default::default()
+            }],
+        };
+
+        resmoke_config_actor.write_sub_suite(&suite_info).await;
+        resmoke_config_actor.flush().await.unwrap();
+
+        assert!(resmoke_config_actor
+            .manifest()
+            .await
+            .unwrap()
+            .suites
+            .is_empty());
+    }
+
+    // DryRunConfigActor tests.
+    #[tokio::test]
+    async fn test_dry_run_actor_should_record_plan() {
+        let mut dry_run_actor = DryRunConfigActor::new();
+        let suite_info = ResmokeSuiteGenerationInfo {
+            task_name: "my_task".to_string(),
+            origin_suite: "original_suite".to_string(),
+            require_multiversion_generate_tasks: false,
+            sub_suites: vec![
+                SubSuite {
+                    index: 0,
+                    name: "suite_name".to_string(),
+                    origin_suite: "original_suite".to_string(),
+                    test_list: vec![
+                        "test_0.js".to_string(),
+                        "test_1.js".to_string(),
+                        "test_2.js".to_string(),
+                    ],
+                    estimated_runtime: Some(30.0),
+                    ..Default::default()
+                },
+                SubSuite {
+                    index: 1,
+                    name: "suite_name".to_string(),
+                    origin_suite: "original_suite".to_string(),
+                    test_list: vec!["test_3.js".to_string()],
+                    estimated_runtime: Some(25.0),
+                    ..Default::default()
+                },
+            ],
+        };
+
+        dry_run_actor.write_sub_suite(&suite_info).await;
+        let errors = dry_run_actor.flush().await.unwrap();
+
+        assert!(errors.is_empty());
+        let plans = dry_run_actor.plans();
+        assert_eq!(plans.len(), 1);
+        assert_eq!(plans[0].task_name, "my_task");
+        assert_eq!(plans[0].origin_suite, "original_suite");
+        let sub_suites = &plans[0].sub_suites;
+        assert_eq!(sub_suites.len(), 2);
+        assert_eq!(sub_suites[0].name, "suite_name_0");
+        assert_eq!(sub_suites[0].test_count, 3);
+        assert_eq!(sub_suites[0].estimated_runtime, Some(30.0));
+        assert_eq!(sub_suites[1].name, "suite_name_1");
+        assert_eq!(sub_suites[1].test_count, 1);
+    }
 }
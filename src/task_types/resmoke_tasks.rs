@@ -4,12 +4,16 @@
 //! use that information to divide the tests into sub-suites that can be run in parallel.
 //!
 //! Each task will contain the generated sub-suites.
-use std::{cmp::min, collections::HashMap, sync::Arc};
+use std::{
+    cmp::{min, Ordering},
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_trait::async_trait;
 use maplit::hashmap;
-use rand::{prelude::SliceRandom, thread_rng};
+use rand::{prelude::SliceRandom, rngs::StdRng, thread_rng, SeedableRng};
 use shrub_rs::models::{
     commands::{fn_call, fn_call_with_params, EvgCommand},
     params::ParamValue,
@@ -25,15 +29,22 @@ use crate::{
         evg_task_history::{
             get_test_name, TaskHistoryService, TaskRuntimeHistory, TestRuntimeHistory,
         },
+        timing_source::TimingSource,
     },
     evergreen_names::{
         ADD_GIT_TAG, CONFIGURE_EVG_API_CREDS, DO_MULTIVERSION_SETUP, DO_SETUP,
         GEN_TASK_CONFIG_LOCATION, GET_PROJECT_WITH_NO_MODULES, MULTIVERSION_EXCLUDE_TAG,
         MULTIVERSION_EXCLUDE_TAGS_FILE, REQUIRE_MULTIVERSION_SETUP, RESMOKE_ARGS, RESMOKE_JOBS_MAX,
-        RUN_GENERATED_TESTS, SUITE_NAME,
+        RUN_GENERATED_TESTS, SUB_SUITE_ESTIMATED_RUNTIME, SUB_SUITE_TEST_COUNT, SUITE_NAME,
+    },
+    resmoke::{
+        resmoke_proxy::TestDiscovery,
+        resmoke_suite::{ResmokeSelector, ResmokeSuiteConfig},
+    },
+    utils::{
+        fs_service::FsService,
+        task_name::{name_generated_task, SuiteFileNaming},
     },
-    resmoke::resmoke_proxy::TestDiscovery,
-    utils::{fs_service::FsService, task_name::name_generated_task},
     SubtaskLimits, REQUIRED_PREFIX,
 };
 
@@ -43,6 +54,9 @@ use super::{
     resmoke_config_writer::ResmokeConfigActor,
 };
 
+/// Prefix of test tags naming the affinity group a test belongs to.
+const AFFINITY_TAG_PREFIX: &str = "must_run_together:";
+
 /// Parameters describing how a specific resmoke suite should be generated.
 #[derive(Clone, Debug, Default)]
 pub struct ResmokeGenParams {
@@ -132,11 +146,29 @@ impl ResmokeGenParams {
             );
         }
 
+        if !sub_suite.test_list.is_empty() {
+            run_test_vars.insert(
+                SUB_SUITE_TEST_COUNT.to_string(),
+                ParamValue::from(sub_suite.test_list.len() as u64),
+            );
+        }
+
+        if let Some(estimated_runtime) = sub_suite.estimated_runtime {
+            run_test_vars.insert(
+                SUB_SUITE_ESTIMATED_RUNTIME.to_string(),
+                ParamValue::from(estimated_runtime),
+            );
+        }
+
         run_test_vars
     }
 
     /// Build the resmoke arguments to use for a generate sub-task.
     ///
+    /// These are only the arguments added after the suite. The evergreen function running the
+    /// sub-task supplies `run --suites=...` itself, so unlike
+    /// `ResmokeSuiteConfig::run_command` this does not build a complete resmoke invocation.
+    ///
     /// # Arguments
     ///
     /// * `exclude_tags` - Resmoke tags to exclude.
@@ -215,6 +247,33 @@ pub struct SubSuite {
 
     /// Platform of build_variant the sub-suite is for.
     pub platform: Option<String>,
+
+    /// Estimated runtime (in seconds) of the tests in the sub-suite, if historic data exists.
+    pub estimated_runtime: Option<f64>,
+}
+
+impl SubSuite {
+    /// Build the resmoke configuration to run this sub-suite.
+    ///
+    /// If the origin suite selects its tests only through `include_files`, the sub-suite's
+    /// tests become its roots and `include_files` is cleared, since the roots now enumerate the
+    /// selection. See `ResmokeSuiteConfig::split_into_roots`.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin_config` - Configuration of the suite this sub-suite is based on.
+    ///
+    /// # Returns
+    ///
+    /// Resmoke configuration that runs the tests of this sub-suite.
+    pub fn build_config(&self, origin_config: &ResmokeSuiteConfig) -> ResmokeSuiteConfig {
+        if self.exclude_test_list.is_none()
+            && origin_config.selector.selects_only_by_include_files()
+        {
+            return origin_config.with_tests_as_roots(&self.test_list);
+        }
+        origin_config.with_new_tests(Some(&self.test_list), self.exclude_test_list.as_deref())
+    }
 }
 
 /// Information needed to generate resmoke configuration files for the generated task.
@@ -224,7 +283,6 @@ pub struct ResmokeSuiteGenerationInfo {
     pub task_name: String,
 
     /// Name of resmoke suite generated task is based on.
-    #[allow(dead_code)]
     pub origin_suite: String,
 
     /// List of generated sub-suites comprising task.
@@ -257,6 +315,18 @@ impl GeneratedSuite for GeneratedResmokeSuite {
     }
 }
 
+/// A hook to customize the evergreen sub-tasks generated for resmoke and fuzzer tasks.
+pub trait TaskDecorator: Sync + Send {
+    /// Modify a generated sub-task before it is added to the generated configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - Sub-task that was generated.
+    /// * `sub_suite` - Sub-suite the task runs, or `None` for fuzzer sub-tasks, which do not
+    ///   run a split suite.
+    fn decorate(&self, task: &mut EvgTask, sub_suite: Option<&SubSuite>);
+}
+
 /// A service for generating resmoke tasks.
 #[async_trait]
 pub trait GenResmokeTaskService: Sync + Send {
@@ -302,6 +372,13 @@ pub struct GenResmokeConfig {
 
     /// Enterprise directory.
     enterprise_dir: Option<String>,
+
+    /// Scheme used to name the generated suite files.
+    suite_file_naming: SuiteFileNaming,
+
+    /// Maximum number of sub-suites a task can be split into across all of its multiversion
+    /// combinations.
+    max_total_subtasks: Option<usize>,
 }
 
 impl GenResmokeConfig {
@@ -311,16 +388,26 @@ impl GenResmokeConfig {
     ///
     /// * `n_suite` - Number of sub-suites to split tasks into.
     /// * `use_task_split_fallback` - Disable evergreen task-history queries and use task
-    ///    splitting fallback.
+    ///   splitting fallback.
     /// * `enterprise_dir` - Directory enterprise files are stored in.
+    /// * `suite_file_naming` - Scheme used to name the generated suite files.
+    /// * `max_total_subtasks` - Maximum number of sub-suites a task can be split into across
+    ///   all of its multiversion combinations.
     ///
     /// # Returns
     ///
     /// New instance of `GenResmokeConfig`.
-    pub fn new(use_task_split_fallback: bool, enterprise_dir: Option<String>) -> Self {
+    pub fn new(
+        use_task_split_fallback: bool,
+        enterprise_dir: Option<String>,
+        suite_file_naming: SuiteFileNaming,
+        max_total_subtasks: Option<usize>,
+    ) -> Self {
         Self {
             use_task_split_fallback,
             enterprise_dir,
+            suite_file_naming,
+            max_total_subtasks,
         }
     }
 }
@@ -331,6 +418,9 @@ pub struct GenResmokeTaskServiceImpl {
     /// Service to query task runtime history.
     task_history_service: Arc<dyn TaskHistoryService>,
 
+    /// Local source of test runtimes to use before querying the task history service.
+    timing_source: Option<Arc<dyn TimingSource>>,
+
     /// Test discovery service.
     test_discovery: Arc<dyn TestDiscovery>,
 
@@ -347,6 +437,9 @@ pub struct GenResmokeTaskServiceImpl {
     config: GenResmokeConfig,
 
     subtask_limits: SubtaskLimits,
+
+    /// Hook to customize every generated sub-task.
+    task_decorator: Option<Arc<dyn TaskDecorator>>,
 }
 
 impl GenResmokeTaskServiceImpl {
@@ -355,30 +448,37 @@ impl GenResmokeTaskServiceImpl {
     /// # Arguments
     ///
     /// * `task_history_service` - An instance of the service to query task history.
+    /// * `timing_source` - Local test runtimes to prefer over the task history service.
     /// * `test_discovery` - An instance of the service to query tests belonging to a task.
     /// * `fs_service` - An instance of the service too work with the file system.
     /// * `gen_resmoke_config` - Configuration for how resmoke tasks should be generated.
+    /// * `task_decorator` - Hook to customize every generated sub-task.
     ///
     /// # Returns
     ///
     /// New instance of GenResmokeTaskService.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         task_history_service: Arc<dyn TaskHistoryService>,
+        timing_source: Option<Arc<dyn TimingSource>>,
         test_discovery: Arc<dyn TestDiscovery>,
         resmoke_config_actor: Arc<Mutex<dyn ResmokeConfigActor>>,
         multiversion_service: Arc<dyn MultiversionService>,
         fs_service: Arc<dyn FsService>,
         config: GenResmokeConfig,
         subtask_limits: SubtaskLimits,
+        task_decorator: Option<Arc<dyn TaskDecorator>>,
     ) -> Self {
         Self {
             task_history_service,
+            timing_source,
             test_discovery,
             resmoke_config_actor,
             multiversion_service,
             fs_service,
             config,
             subtask_limits,
+            task_decorator,
         }
     }
 }
@@ -389,6 +489,7 @@ impl GenResmokeTaskServiceImpl {
     /// # Arguments
     ///
     /// * `params` - Parameters for how tasks should be generated.
+    /// * `test_list` - Tests belonging to the task.
     /// * `task_stats` - Statistics on the historic runtimes of tests in the task.
     /// * `multiversion_name` - Name of task if performing multiversion generation.
     /// * `multiversion_tags` - Tag to include when performing multiversion generation.
@@ -400,13 +501,12 @@ impl GenResmokeTaskServiceImpl {
     fn split_task(
         &self,
         params: &ResmokeGenParams,
+        test_list: Vec<String>,
         task_stats: &TaskRuntimeHistory,
         multiversion_name: Option<&str>,
         multiversion_tags: Option<String>,
         build_variant: &BuildVariant,
     ) -> Result<Vec<SubSuite>> {
-        let origin_suite = multiversion_name.unwrap_or(&params.suite_name);
-        let test_list = self.get_test_list(params, multiversion_name)?;
         let total_runtime = task_stats
             .test_map
             .iter()
@@ -430,14 +530,18 @@ impl GenResmokeTaskServiceImpl {
             None => self.subtask_limits.default_subtasks_per_task,
         };
 
-        let num_tasks = *[
-            ideal_num_tasks,
+        let num_tasks = get_num_sub_suites(
             test_list.len(),
-            self.subtask_limits.max_subtasks_per_task,
-        ]
-        .iter()
-        .min()
-        .unwrap();
+            *[
+                ideal_num_tasks,
+                test_list.len(),
+                self.subtask_limits.max_subtasks_per_task,
+            ]
+            .iter()
+            .min()
+            .unwrap(),
+            self.subtask_limits.max_tests_per_suite,
+        );
 
         let runtime_per_subtask = total_runtime / num_tasks as f64;
         event!(
@@ -448,42 +552,79 @@ impl GenResmokeTaskServiceImpl {
             test_list.len()
         );
 
-        let sorted_test_list = sort_tests_by_runtime(test_list, task_stats);
-        let mut running_tests = vec![vec![]; num_tasks];
-        let mut running_runtimes = vec![0.0; num_tasks];
+        let affinity_groups =
+            self.get_affinity_groups(multiversion_name.unwrap_or(&params.suite_name))?;
+        let mut test_runtimes = vec![];
         let mut left_tests = vec![];
-
-        for test in sorted_test_list {
-            let min_idx = get_min_index(&running_runtimes);
-            let test_name = get_test_name(&test);
-            if let Some(test_stats) = task_stats.test_map.get(&test_name) {
-                running_runtimes[min_idx] += test_stats.average_runtime;
-                running_tests[min_idx].push(test.clone());
-            } else {
-                left_tests.push(test.clone());
+        for test in test_list {
+            match task_stats.test_map.get(&get_test_name(&test)) {
+                Some(test_stats) => test_runtimes.push((test, test_stats.average_runtime)),
+                None => left_tests.push(test),
             }
         }
+        left_tests.sort();
+
+        if test_runtimes.is_empty() && !left_tests.is_empty() {
+            // Without any historic runtimes there is nothing to balance on, so split the tests
+            // evenly by count.
+            return Ok(build_sub_suites(
+                params,
+                self.distribute_by_count(&left_tests, num_tasks, &affinity_groups)?,
+                vec![None; num_tasks],
+                multiversion_name,
+                multiversion_tags,
+            ));
+        }
+
+        let mut running_tests = if affinity_groups.is_empty() {
+            distribute_tests_by_runtime(&test_runtimes, num_tasks)
+        } else {
+            // Grouped tests without a runtime still need to join their group, so only the
+            // ungrouped ones are spread out afterwards.
+            let (grouped_tests, ungrouped_tests): (Vec<String>, Vec<String>) = left_tests
+                .into_iter()
+                .partition(|test| affinity_groups.contains_key(test));
+            left_tests = ungrouped_tests;
+            let tests: Vec<(String, f64, Option<String>)> = test_runtimes
+                .into_iter()
+                .chain(grouped_tests.into_iter().map(|test| (test, 0.0)))
+                .map(|(test, runtime)| {
+                    let group = affinity_groups.get(&test).cloned();
+                    (test, runtime, group)
+                })
+                .collect();
+            distribute_with_affinity(&tests, num_tasks)
+        };
+        let running_runtimes: Vec<f64> = running_tests
+            .iter()
+            .map(|tests| {
+                tests
+                    .iter()
+                    .filter_map(|test| task_stats.test_map.get(&get_test_name(test)))
+                    .map(|test_stats| test_stats.average_runtime)
+                    .sum()
+            })
+            .collect();
 
         let min_idx = get_min_index(&running_runtimes);
         for (i, test) in left_tests.iter().enumerate() {
             running_tests[(min_idx + i) % num_tasks].push(test.clone());
         }
 
-        let mut sub_suites = vec![];
-        for (i, slice) in running_tests.iter().enumerate() {
-            sub_suites.push(SubSuite {
-                index: i,
-                name: multiversion_name.unwrap_or(&params.task_name).to_string(),
-                test_list: slice.clone(),
-                origin_suite: origin_suite.to_string(),
-                exclude_test_list: None,
-                mv_exclude_tags: multiversion_tags.clone(),
-                is_enterprise: params.is_enterprise,
-                platform: params.platform.clone(),
-            });
+        let mut running_runtimes: Vec<Option<f64>> =
+            running_runtimes.into_iter().map(Some).collect();
+        if self.subtask_limits.sort_sub_suites_by_runtime {
+            (running_tests, running_runtimes) =
+                sort_sub_suites_by_runtime(running_tests, running_runtimes);
         }
 
-        Ok(sub_suites)
+        Ok(build_sub_suites(
+            params,
+            running_tests,
+            running_runtimes,
+            multiversion_name,
+            multiversion_tags,
+        ))
     }
 
     /// Get the list of tests belonging to the suite being generated.
@@ -519,6 +660,73 @@ impl GenResmokeTaskServiceImpl {
         Ok(test_list)
     }
 
+    /// Get the affinity group of each test in the given suite that belongs to one.
+    ///
+    /// A test belongs to an affinity group if it has a `must_run_together:<group>` tag. If a
+    /// test has several such tags, the first group by name is used.
+    ///
+    /// # Arguments
+    ///
+    /// * `suite_name` - Name of suite to get the affinity groups of.
+    ///
+    /// # Returns
+    ///
+    /// Map of tests to the affinity group they belong to.
+    fn get_affinity_groups(&self, suite_name: &str) -> Result<HashMap<String, String>> {
+        let test_tags = match self.test_discovery.get_test_tags(suite_name)? {
+            Some(test_tags) => test_tags,
+            None => return Ok(HashMap::new()),
+        };
+
+        Ok(test_tags
+            .into_iter()
+            .filter_map(|(test, tags)| {
+                tags.iter()
+                    .filter_map(|tag| tag.strip_prefix(AFFINITY_TAG_PREFIX))
+                    .min()
+                    .map(|group| (test.clone(), group.to_string()))
+            })
+            .collect())
+    }
+
+    /// Distribute tests evenly by count among sub-suites.
+    ///
+    /// If any of the tests belong to an affinity group, every test is counted as one unit of
+    /// runtime and the tests are distributed with `distribute_with_affinity` instead. Otherwise,
+    /// if a distribution seed is configured, the tests are shuffled with it first.
+    ///
+    /// # Arguments
+    ///
+    /// * `tests` - List of tests to distribute.
+    /// * `n` - Number of sub-suites to distribute tests among.
+    /// * `affinity_groups` - Map of tests to the affinity group they belong to.
+    ///
+    /// # Returns
+    ///
+    /// List of `n` sub-suites with the tests belonging to each.
+    fn distribute_by_count(
+        &self,
+        tests: &[String],
+        n: usize,
+        affinity_groups: &HashMap<String, String>,
+    ) -> Result<Vec<Vec<String>>> {
+        if tests.iter().any(|test| affinity_groups.contains_key(test)) {
+            if n == 0 {
+                bail!("Cannot distribute {} tests among 0 sub-suites", tests.len());
+            }
+            let tests: Vec<(String, f64, Option<String>)> = tests
+                .iter()
+                .map(|test| (test.clone(), 1.0, affinity_groups.get(test).cloned()))
+                .collect();
+            return Ok(distribute_with_affinity(&tests, n));
+        }
+
+        match self.subtask_limits.distribution_seed {
+            Some(seed) => distribute_tests_by_count_seeded(tests, n, seed),
+            None => distribute_tests_by_count(tests, n),
+        }
+    }
+
     /// Split a task with no historic runtime information.
     ///
     /// Since we don't have any runtime information, we will just split the tests evenly among
@@ -539,12 +747,31 @@ impl GenResmokeTaskServiceImpl {
         multiversion_name: Option<&str>,
         multiversion_tags: Option<String>,
     ) -> Result<Vec<SubSuite>> {
-        let mut sub_suites = vec![];
-
-        let origin_suite = multiversion_name.unwrap_or(&params.suite_name);
         let test_list = self.get_test_list(params, multiversion_name)?;
+        self.split_tests_evenly(params, test_list, multiversion_name, multiversion_tags)
+    }
+
+    /// Split the given tests evenly among the number of suites we want to create.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Parameters for how tasks should be generated.
+    /// * `test_list` - Tests belonging to the task.
+    /// * `multiversion_name` - Name of task if performing multiversion generation.
+    /// * `multiversion_tags` - Tag to include when performing multiversion generation.
+    ///
+    /// # Returns
+    ///
+    /// A list of sub-suites to run the given tests.
+    fn split_tests_evenly(
+        &self,
+        params: &ResmokeGenParams,
+        test_list: Vec<String>,
+        multiversion_name: Option<&str>,
+        multiversion_tags: Option<String>,
+    ) -> Result<Vec<SubSuite>> {
         if test_list.is_empty() {
-            return Ok(sub_suites);
+            return Ok(vec![]);
         }
 
         let requested_num_tasks = match params.num_tasks {
@@ -552,26 +779,99 @@ impl GenResmokeTaskServiceImpl {
             None => self.subtask_limits.default_subtasks_per_task,
         };
 
-        let n = min(test_list.len(), requested_num_tasks);
-        let len = test_list.len();
-        let (quo, rem) = (len / n, len % n);
-        let split = (quo + 1) * rem;
-        let iter = test_list[..split]
-            .chunks(quo + 1)
-            .chain(test_list[split..].chunks(quo));
-        for (index, tests) in iter.enumerate() {
-            sub_suites.push(SubSuite {
-                index,
-                name: multiversion_name.unwrap_or(&params.task_name).to_string(),
-                test_list: tests.to_vec(),
-                origin_suite: origin_suite.to_string(),
-                exclude_test_list: None,
-                mv_exclude_tags: multiversion_tags.clone(),
-                is_enterprise: params.is_enterprise,
-                platform: params.platform.clone(),
-            });
+        let n = get_num_sub_suites(
+            test_list.len(),
+            min(test_list.len(), requested_num_tasks),
+            self.subtask_limits.max_tests_per_suite,
+        );
+        let affinity_groups =
+            self.get_affinity_groups(multiversion_name.unwrap_or(&params.suite_name))?;
+        Ok(build_sub_suites(
+            params,
+            self.distribute_by_count(&test_list, n, &affinity_groups)?,
+            vec![None; n],
+            multiversion_name,
+            multiversion_tags,
+        ))
+    }
+
+    /// Get the runtime history of the given tests.
+    ///
+    /// Runtimes are taken from the local timing source when one is configured. The task
+    /// history service is only queried if some tests have no local runtime, and is then used
+    /// for just those tests.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Parameters for how tasks should be generated.
+    /// * `build_variant` - Build variant to query history for.
+    /// * `test_list` - Tests to get the runtime history of.
+    ///
+    /// # Returns
+    ///
+    /// Runtime history of the given tests.
+    async fn get_task_history(
+        &self,
+        params: &ResmokeGenParams,
+        build_variant: &BuildVariant,
+        test_list: &[String],
+    ) -> Result<TaskRuntimeHistory> {
+        let timing_source = match &self.timing_source {
+            Some(timing_source) => timing_source,
+            None => {
+                return self
+                    .task_history_service
+                    .get_task_history(&params.task_name, &build_variant.name)
+                    .await
+            }
+        };
+
+        let mut test_map = HashMap::new();
+        let mut missing_tests = 0;
+        for test in test_list {
+            match timing_source.test_runtime(test) {
+                Some(average_runtime) => {
+                    test_map.insert(
+                        get_test_name(test),
+                        TestRuntimeHistory {
+                            test_name: test.clone(),
+                            average_runtime,
+                            hooks: vec![],
+                        },
+                    );
+                }
+                None => missing_tests += 1,
+            }
+        }
+
+        if missing_tests > 0 {
+            let task_history = self
+                .task_history_service
+                .get_task_history(&params.task_name, &build_variant.name)
+                .await;
+            match task_history {
+                Ok(task_history) => {
+                    for (test_name, history) in task_history.test_map {
+                        test_map.entry(test_name).or_insert(history);
+                    }
+                }
+                Err(err) if test_map.is_empty() => return Err(err),
+                Err(err) => {
+                    warn!(
+                        build_variant = build_variant.name,
+                        task_name = params.task_name.as_str(),
+                        missing_tests = missing_tests,
+                        error = err.to_string().as_str(),
+                        "Could not get task history for tests without local timings",
+                    );
+                }
+            }
         }
-        Ok(sub_suites)
+
+        Ok(TaskRuntimeHistory {
+            task_name: params.task_name.clone(),
+            test_map,
+        })
     }
 
     /// Create version of the generated sub-tasks for all the multiversion combinations.
@@ -583,12 +883,12 @@ impl GenResmokeTaskServiceImpl {
     ///
     /// # Returns
     ///
-    /// List of all sub-suites for a multiversion task with generate tasks.
+    /// Sub-suites of each multiversion combination for a multiversion task with generate tasks.
     async fn create_multiversion_tasks(
         &self,
         params: &ResmokeGenParams,
         build_variant: &BuildVariant,
-    ) -> Result<Vec<SubSuite>> {
+    ) -> Result<Vec<Vec<SubSuite>>> {
         let mut mv_sub_suites = vec![];
         for multiversion_task in params.multiversion_generate_tasks.as_ref().unwrap() {
             let suites = self
@@ -599,12 +899,40 @@ impl GenResmokeTaskServiceImpl {
                     Some(multiversion_task.old_version.clone()),
                 )
                 .await?;
-            mv_sub_suites.extend_from_slice(&suites);
+            mv_sub_suites.push(suites);
         }
 
         Ok(mv_sub_suites)
     }
 
+    /// Fit the sub-suites of each suite of a task into the configured sub-suite budget.
+    ///
+    /// When the suites are split into more sub-suites than `max_total_subtasks` allows, the
+    /// number of sub-suites of each suite is scaled down with `clamp_sub_suite_counts` and the
+    /// extra sub-suites are merged into the ones that remain.
+    ///
+    /// # Arguments
+    ///
+    /// * `suite_groups` - Sub-suites split from each suite of the task.
+    ///
+    /// # Returns
+    ///
+    /// All the sub-suites of the task, within the budget.
+    fn limit_sub_suites(&self, suite_groups: Vec<Vec<SubSuite>>) -> Result<Vec<SubSuite>> {
+        let max_total_subtasks = match self.config.max_total_subtasks {
+            Some(max_total_subtasks) => max_total_subtasks,
+            None => return Ok(suite_groups.into_iter().flatten().collect()),
+        };
+
+        let desired_counts: Vec<usize> = suite_groups.iter().map(|g| g.len()).collect();
+        let counts = clamp_sub_suite_counts(&desired_counts, max_total_subtasks)?;
+        Ok(suite_groups
+            .into_iter()
+            .zip(counts)
+            .flat_map(|(sub_suites, n)| merge_sub_suites(sub_suites, n))
+            .collect())
+    }
+
     /// Create sub-suites based on the given information.
     ///
     /// # Arguments
@@ -627,14 +955,15 @@ impl GenResmokeTaskServiceImpl {
         let sub_suites = if self.config.use_task_split_fallback {
             self.split_task_fallback(params, multiversion_name, multiversion_tags.clone())?
         } else {
+            let test_list = self.get_test_list(params, multiversion_name)?;
             let task_history = self
-                .task_history_service
-                .get_task_history(&params.task_name, &build_variant.name)
+                .get_task_history(params, build_variant, &test_list)
                 .await;
 
             match task_history {
                 Ok(task_history) => self.split_task(
                     params,
+                    test_list,
                     &task_history,
                     multiversion_name,
                     multiversion_tags.clone(),
@@ -649,126 +978,620 @@ impl GenResmokeTaskServiceImpl {
                     );
                     // If we couldn't get the task history, then fallback to splitting the tests evenly
                     // among the desired number of sub-suites.
-                    self.split_task_fallback(params, multiversion_name, multiversion_tags.clone())?
+                    self.split_tests_evenly(
+                        params,
+                        test_list,
+                        multiversion_name,
+                        multiversion_tags.clone(),
+                    )?
                 }
             }
         };
 
-        Ok(sub_suites)
+        self.drop_fully_excluded_sub_suites(
+            multiversion_name.unwrap_or(&params.suite_name),
+            sub_suites,
+        )
+    }
+
+    /// Remove the sub-suites that would run nothing because the suite's tag filters exclude
+    /// all of their tests.
+    ///
+    /// This needs the tags of each test, so sub-suites are only removed if the test discovery
+    /// service knows them. If every sub-suite would be removed, they are all kept so the task
+    /// is still generated.
+    ///
+    /// # Arguments
+    ///
+    /// * `suite_name` - Name of the suite the sub-suites were split from.
+    /// * `sub_suites` - Sub-suites the suite was split into.
+    ///
+    /// # Returns
+    ///
+    /// The sub-suites that select at least one test, re-indexed to stay contiguous.
+    fn drop_fully_excluded_sub_suites(
+        &self,
+        suite_name: &str,
+        sub_suites: Vec<SubSuite>,
+    ) -> Result<Vec<SubSuite>> {
+        let test_tags = match self.test_discovery.get_test_tags(suite_name)? {
+            Some(test_tags) => test_tags,
+            None => return Ok(sub_suites),
+        };
+        let selector = self.test_discovery.get_suite_config(suite_name)?.selector;
+        let test_lists: Vec<Vec<String>> = sub_suites.iter().map(|s| s.test_list.clone()).collect();
+        let excluded =
+            find_fully_excluded_sub_suites(suite_name, &test_lists, &selector, Some(&test_tags));
+        if excluded.is_empty() || excluded.len() == sub_suites.len() {
+            return Ok(sub_suites);
+        }
+
+        Ok(sub_suites
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !excluded.contains(i))
+            .enumerate()
+            .map(|(index, (_, sub_suite))| SubSuite { index, ..sub_suite })
+            .collect())
     }
 }
 
-/// Sort tests by historic runtime descending.
+/// Estimate the total runtime of the given tests that a selector would select.
 ///
-/// Tests without historic runtime data will be placed at the end of the list.
+/// Only the tests' paths are known, so tests are selected with
+/// `ResmokeSelector::selects_path` and the selector's tag filters are not applied.
 ///
 /// # Arguments
 ///
-/// * `test_list` - List of tests.
-/// * `task_stats` - Historic task stats.
+/// * `tests` - Tests with their historic runtimes, or `None` if there is no runtime data.
+/// * `selector` - Selector choosing which tests are counted.
+/// * `default_runtime` - Runtime (in seconds) to count for selected tests without runtime data.
 ///
 /// # Returns
 ///
-/// List of sorted tests by historic runtime.
-fn sort_tests_by_runtime(
-    test_list: Vec<String>,
-    task_stats: &TaskRuntimeHistory,
-) -> Vec<std::string::String> {
-    let mut sorted_test_list = test_list;
-    sorted_test_list.sort_by(|test_file_a, test_file_b| {
-        let test_name_a = get_test_name(test_file_a);
-        let test_name_b = get_test_name(test_file_b);
-        let default_runtime = TestRuntimeHistory {
-            test_name: "default".to_string(),
-            average_runtime: 0.0,
-            hooks: vec![],
-        };
-        let runtime_history_a = task_stats
-            .test_map
-            .get(&test_name_a)
-            .unwrap_or(&default_runtime);
-        let runtime_history_b = task_stats
-            .test_map
-            .get(&test_name_b)
-            .unwrap_or(&default_runtime);
-        runtime_history_b
-            .average_runtime
-            .partial_cmp(&runtime_history_a.average_runtime)
-            .unwrap()
-    });
-    sorted_test_list
+/// Total estimated runtime (in seconds) of the selected tests.
+pub fn sum_runtime(
+    tests: &[(String, Option<f64>)],
+    selector: &ResmokeSelector,
+    default_runtime: f64,
+) -> f64 {
+    tests
+        .iter()
+        .filter(|(test, _)| selector.selects_path(test))
+        .map(|(_, runtime)| runtime.unwrap_or(default_runtime))
+        .sum()
 }
 
-/// Get the index of sub suite with the least total runtime of tests.
+/// Distribute tests among sub-suites so that the sub-suites have similar total runtimes.
+///
+/// Tests are assigned longest first to the sub-suite with the least total runtime so far.
+/// Tests with equal runtimes are assigned in order of their names, so the same input will
+/// always produce the same distribution.
 ///
 /// # Arguments
 ///
-/// * `running_runtimes` - Total runtimes of tests of sub suites.
+/// * `tests` - List of tests with their historic runtimes.
+/// * `n` - Number of sub-suites to distribute tests among.
 ///
 /// # Returns
 ///
-/// Index of sub suite with the least total runtime.
-fn get_min_index(running_runtimes: &[f64]) -> usize {
-    let mut min_idx = 0;
-    for (i, value) in running_runtimes.iter().enumerate() {
-        if value < &running_runtimes[min_idx] {
-            min_idx = i;
-        }
+/// List of `n` sub-suites with the tests belonging to each.
+pub fn distribute_tests_by_runtime(tests: &[(String, f64)], n: usize) -> Vec<Vec<String>> {
+    let mut running_tests: Vec<Vec<String>> = vec![vec![]; n];
+    let mut running_runtimes = vec![0.0; n];
+    if n == 0 {
+        return running_tests;
     }
-    min_idx
+
+    let mut sorted_tests: Vec<&(String, f64)> = tests.iter().collect();
+    sorted_tests.sort_by(|(test_a, runtime_a), (test_b, runtime_b)| {
+        runtime_b
+            .partial_cmp(runtime_a)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| test_a.cmp(test_b))
+    });
+
+    for (test, runtime) in sorted_tests {
+        // Prefer the sub-suite with fewer tests when runtimes are tied so that tests without
+        // any runtime are still spread out.
+        let min_idx = (0..n)
+            .min_by(|a, b| {
+                running_runtimes[*a]
+                    .partial_cmp(&running_runtimes[*b])
+                    .unwrap_or(Ordering::Equal)
+                    .then_with(|| running_tests[*a].len().cmp(&running_tests[*b].len()))
+            })
+            .unwrap();
+        running_runtimes[min_idx] += runtime;
+        running_tests[min_idx].push(test.clone());
+    }
+
+    running_tests
 }
 
-#[async_trait]
-impl GenResmokeTaskService for GenResmokeTaskServiceImpl {
-    /// Generate a task for running the given task in parallel.
-    ///
-    /// # Arguments
-    ///
-    /// * `params` - Parameters for how task should be generated.
-    /// * `build_variant` - Build variant to base task splitting on.
-    ///
-    /// # Returns
-    ///
-    /// A generated suite representing the split task.
-    async fn generate_resmoke_task(
-        &self,
-        params: &ResmokeGenParams,
-        build_variant: &BuildVariant,
-    ) -> Result<Box<dyn GeneratedSuite>> {
-        let sub_suites = if params.require_multiversion_generate_tasks {
-            self.create_multiversion_tasks(params, build_variant)
-                .await?
-        } else {
-            self.create_tasks(params, build_variant, None, None).await?
-        };
+/// Distribute tests among sub-suites by runtime while keeping tests with the same affinity
+/// group in the same sub-suite.
+///
+/// Each affinity group is treated as a single unit with the combined runtime of its tests, and
+/// every test without a group is its own unit. Units are then distributed the same way as
+/// `distribute_tests_by_runtime`, so groups may leave the sub-suites less balanced than they
+/// would otherwise be. Tests of a group keep the order they were given in.
+///
+/// # Arguments
+///
+/// * `tests` - List of tests with their historic runtimes and optional affinity group.
+/// * `n` - Number of sub-suites to distribute tests among.
+///
+/// # Returns
+///
+/// List of `n` sub-suites with the tests belonging to each.
+pub fn distribute_with_affinity(
+    tests: &[(String, f64, Option<String>)],
+    n: usize,
+) -> Vec<Vec<String>> {
+    let mut running_tests: Vec<Vec<String>> = vec![vec![]; n];
+    let mut running_runtimes = vec![0.0; n];
+    if n == 0 {
+        return running_tests;
+    }
 
-        let sub_task_total = sub_suites.len();
-        let suite_info = ResmokeSuiteGenerationInfo {
-            task_name: params.task_name.to_string(),
-            origin_suite: params.suite_name.to_string(),
-            sub_suites: sub_suites.clone(),
-            require_multiversion_generate_tasks: params.require_multiversion_generate_tasks,
-        };
-        let mut resmoke_config_actor = self.resmoke_config_actor.lock().await;
-        resmoke_config_actor.write_sub_suite(&suite_info).await;
+    let mut units: Vec<(Vec<String>, f64)> = vec![];
+    let mut group_units: HashMap<&str, usize> = HashMap::new();
+    for (test, runtime, group) in tests {
+        match group {
+            Some(group) => {
+                let index = *group_units.entry(group.as_str()).or_insert_with(|| {
+                    units.push((vec![], 0.0));
+                    units.len() - 1
+                });
+                units[index].0.push(test.clone());
+                units[index].1 += runtime;
+            }
+            None => units.push((vec![test.clone()], *runtime)),
+        }
+    }
 
-        Ok(Box::new(GeneratedResmokeSuite {
-            task_name: params.task_name.clone(),
-            sub_suites: sub_suites
-                .into_iter()
-                .map(|s| self.build_resmoke_sub_task(&s, sub_task_total, params, None))
-                .collect(),
-        }))
+    units.sort_by(|(tests_a, runtime_a), (tests_b, runtime_b)| {
+        runtime_b
+            .partial_cmp(runtime_a)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| tests_a[0].cmp(&tests_b[0]))
+    });
+
+    for (unit_tests, runtime) in units {
+        let min_idx = (0..n)
+            .min_by(|a, b| {
+                running_runtimes[*a]
+                    .partial_cmp(&running_runtimes[*b])
+                    .unwrap_or(Ordering::Equal)
+                    .then_with(|| running_tests[*a].len().cmp(&running_tests[*b].len()))
+            })
+            .unwrap();
+        running_runtimes[min_idx] += runtime;
+        running_tests[min_idx].extend(unit_tests);
     }
 
-    /// Build a shrub task to execute a split sub-task.
-    ///
-    /// # Arguments
-    ///
-    /// * `sub_suite` - Sub task to create task for.
-    /// * `params` - Parameters for how task should be generated.
-    ///
-    /// # Returns
+    running_tests
+}
+
+/// Determine how many sub-suites a task should be split into.
+///
+/// If `max_tests_per_suite` is given, it takes precedence over `num_sub_suites` (and any
+/// limits already applied to it), and just enough sub-suites are created to hold that many
+/// tests each. When tests are distributed by runtime, sub-suites are balanced on runtime, so
+/// individual sub-suites may still exceed the limit.
+///
+/// # Arguments
+///
+/// * `total_tests` - Number of tests being split.
+/// * `num_sub_suites` - Number of sub-suites to use if no test limit is given.
+/// * `max_tests_per_suite` - Maximum number of tests a sub-suite should contain.
+///
+/// # Returns
+///
+/// Number of sub-suites to split the tests into.
+pub fn get_num_sub_suites(
+    total_tests: usize,
+    num_sub_suites: usize,
+    max_tests_per_suite: Option<usize>,
+) -> usize {
+    match max_tests_per_suite {
+        Some(max_tests) if max_tests > 0 => total_tests.div_ceil(max_tests),
+        _ => num_sub_suites,
+    }
+}
+
+/// Scale the number of sub-suites requested by each suite down to fit a total budget.
+///
+/// If the requested counts already fit in the budget they are returned unchanged. Otherwise
+/// each count is scaled in proportion to its share of the total request, using the largest
+/// remainder method so that the adjusted counts use as much of the budget as possible. Every
+/// suite that requested sub-suites keeps at least one.
+///
+/// # Arguments
+///
+/// * `desired_counts` - Number of sub-suites requested by each suite.
+/// * `max_total_subtasks` - Maximum number of sub-suites across all suites.
+///
+/// # Returns
+///
+/// Adjusted number of sub-suites for each suite, in the same order, or an error if the budget
+/// is too small to give each suite a sub-suite.
+pub fn clamp_sub_suite_counts(
+    desired_counts: &[usize],
+    max_total_subtasks: usize,
+) -> Result<Vec<usize>> {
+    let total: usize = desired_counts.iter().sum();
+    if total <= max_total_subtasks {
+        return Ok(desired_counts.to_vec());
+    }
+
+    let n_suites = desired_counts.iter().filter(|c| **c > 0).count();
+    if n_suites > max_total_subtasks {
+        bail!(
+            "Cannot fit {} suites into a budget of {} sub-suites",
+            n_suites,
+            max_total_subtasks
+        );
+    }
+
+    let quotas: Vec<f64> = desired_counts
+        .iter()
+        .map(|c| *c as f64 * max_total_subtasks as f64 / total as f64)
+        .collect();
+    let mut counts: Vec<usize> = desired_counts
+        .iter()
+        .zip(&quotas)
+        .map(|(desired, quota)| match desired {
+            0 => 0,
+            _ => (quota.floor() as usize).max(1),
+        })
+        .collect();
+
+    // Give any remaining budget to the suites that lost the most to rounding down.
+    let mut by_remainder: Vec<usize> = (0..counts.len()).collect();
+    by_remainder.sort_by(|a, b| {
+        let rem_a = quotas[*a] - counts[*a] as f64;
+        let rem_b = quotas[*b] - counts[*b] as f64;
+        rem_b.partial_cmp(&rem_a).unwrap_or(Ordering::Equal)
+    });
+    let mut remaining = max_total_subtasks.saturating_sub(counts.iter().sum());
+    for index in by_remainder {
+        if remaining == 0 {
+            break;
+        }
+        if counts[index] < desired_counts[index] {
+            counts[index] += 1;
+            remaining -= 1;
+        }
+    }
+
+    // Suites bumped up to a single sub-suite may have pushed the total over the budget, so take
+    // sub-suites back from the largest suites.
+    while counts.iter().sum::<usize>() > max_total_subtasks {
+        let largest = (0..counts.len()).max_by_key(|i| counts[*i]).unwrap();
+        counts[largest] -= 1;
+    }
+
+    Ok(counts)
+}
+
+/// Distribute tests among as many sub-suites as needed to keep each under a maximum runtime.
+///
+/// Tests are packed in the order given: each test is added to the current sub-suite unless it
+/// would push the sub-suite's runtime over `max_runtime`, in which case a new sub-suite is
+/// started. A test that is longer than `max_runtime` on its own is placed in a sub-suite by
+/// itself rather than being dropped.
+///
+/// # Arguments
+///
+/// * `tests` - List of tests with their historic runtimes.
+/// * `max_runtime` - Maximum runtime (in seconds) of each sub-suite.
+///
+/// # Returns
+///
+/// List of sub-suites with the tests belonging to each.
+pub fn distribute_tests_by_max_runtime(
+    tests: &[(String, f64)],
+    max_runtime: f64,
+) -> Vec<Vec<String>> {
+    let mut sub_suites: Vec<Vec<String>> = vec![];
+    let mut current_runtime = 0.0;
+    for (test, runtime) in tests {
+        match sub_suites.last_mut() {
+            Some(current) if current_runtime + runtime <= max_runtime => {
+                current.push(test.clone());
+                current_runtime += runtime;
+            }
+            _ => {
+                sub_suites.push(vec![test.clone()]);
+                current_runtime = *runtime;
+            }
+        }
+    }
+    sub_suites
+}
+
+/// Find the sub-suites that would run nothing because the suite's tag filters exclude all of
+/// their tests.
+///
+/// A warning is logged for each such sub-suite. The check needs the tags of each test, so when
+/// `test_tags` is `None` it is skipped and no sub-suites are reported. Tests missing from
+/// `test_tags` are assumed to be selected, and sub-suites without any tests are not reported.
+///
+/// # Arguments
+///
+/// * `suite_name` - Name of the suite being split, used in the warning.
+/// * `test_lists` - Tests assigned to each sub-suite.
+/// * `selector` - Selector of the base suite.
+/// * `test_tags` - Tags of each test, if known.
+///
+/// # Returns
+///
+/// Indexes of the sub-suites whose tests would all be excluded.
+pub fn find_fully_excluded_sub_suites(
+    suite_name: &str,
+    test_lists: &[Vec<String>],
+    selector: &ResmokeSelector,
+    test_tags: Option<&HashMap<String, HashSet<String>>>,
+) -> Vec<usize> {
+    let test_tags = match test_tags {
+        Some(test_tags) => test_tags,
+        None => return vec![],
+    };
+    let tag_filter = selector.effective_includes().tag_filter;
+
+    test_lists
+        .iter()
+        .enumerate()
+        .filter(|(_, tests)| {
+            !tests.is_empty()
+                && tests.iter().all(|test| {
+                    test_tags
+                        .get(test)
+                        .is_some_and(|tags| !tag_filter.matches(tags))
+                })
+        })
+        .map(|(index, tests)| {
+            warn!(
+                suite_name = suite_name,
+                sub_suite = index,
+                n_tests = tests.len(),
+                "All tests in sub-suite are excluded by the suite's tag filters",
+            );
+            index
+        })
+        .collect()
+}
+
+/// Distribute tests evenly by count among sub-suites.
+///
+/// Tests are split into contiguous chunks, so the tests in each sub-suite stay in the order
+/// they were given. If there are more sub-suites than tests, the trailing sub-suites will be
+/// empty.
+///
+/// # Arguments
+///
+/// * `tests` - List of tests to distribute.
+/// * `n` - Number of sub-suites to distribute tests among.
+///
+/// # Returns
+///
+/// List of `n` sub-suites with the tests belonging to each.
+pub fn distribute_tests_by_count(tests: &[String], n: usize) -> Result<Vec<Vec<String>>> {
+    if n == 0 {
+        bail!("Cannot distribute {} tests among 0 sub-suites", tests.len());
+    }
+
+    let (quo, rem) = (tests.len() / n, tests.len() % n);
+    Ok((0..n)
+        .map(|i| {
+            let start = i * quo + min(i, rem);
+            let size = if i < rem { quo + 1 } else { quo };
+            tests[start..start + size].to_vec()
+        })
+        .collect())
+}
+
+/// Distribute tests evenly by count among sub-suites after shuffling them with the given seed.
+///
+/// The tests are sorted before being shuffled, so the same seed always produces the same
+/// assignment of the same tests to sub-suites, regardless of the order they are given in.
+///
+/// # Arguments
+///
+/// * `tests` - List of tests to distribute.
+/// * `n` - Number of sub-suites to distribute tests among.
+/// * `seed` - Seed for shuffling the tests.
+///
+/// # Returns
+///
+/// List of `n` sub-suites with the tests belonging to each.
+pub fn distribute_tests_by_count_seeded(
+    tests: &[String],
+    n: usize,
+    seed: u64,
+) -> Result<Vec<Vec<String>>> {
+    let mut shuffled = tests.to_vec();
+    shuffled.sort();
+    shuffled.shuffle(&mut StdRng::seed_from_u64(seed));
+    distribute_tests_by_count(&shuffled, n)
+}
+
+/// Build sub-suites for the given lists of tests.
+///
+/// # Arguments
+///
+/// * `params` - Parameters for how tasks should be generated.
+/// * `test_lists` - Tests belonging to each sub-suite.
+/// * `runtimes` - Estimated runtime of each sub-suite.
+/// * `multiversion_name` - Name of task if performing multiversion generation.
+/// * `multiversion_tags` - Tag to include when performing multiversion generation.
+///
+/// # Returns
+///
+/// A sub-suite for each list of tests.
+fn build_sub_suites(
+    params: &ResmokeGenParams,
+    test_lists: Vec<Vec<String>>,
+    runtimes: Vec<Option<f64>>,
+    multiversion_name: Option<&str>,
+    multiversion_tags: Option<String>,
+) -> Vec<SubSuite> {
+    let origin_suite = multiversion_name.unwrap_or(&params.suite_name);
+    test_lists
+        .into_iter()
+        .zip(runtimes)
+        .enumerate()
+        .map(|(index, (test_list, estimated_runtime))| SubSuite {
+            index,
+            name: multiversion_name.unwrap_or(&params.task_name).to_string(),
+            test_list,
+            origin_suite: origin_suite.to_string(),
+            exclude_test_list: None,
+            mv_exclude_tags: multiversion_tags.clone(),
+            is_enterprise: params.is_enterprise,
+            platform: params.platform.clone(),
+            estimated_runtime,
+        })
+        .collect()
+}
+
+/// Merge sub-suites together until there are at most `n` of them.
+///
+/// Sub-suites past the first `n` are merged round-robin into the first `n`, so the remaining
+/// sub-suites keep their indexes. Estimated runtimes are added together, and are unknown if the
+/// runtime of any merged sub-suite is unknown.
+///
+/// # Arguments
+///
+/// * `sub_suites` - Sub-suites to merge.
+/// * `n` - Maximum number of sub-suites to keep.
+///
+/// # Returns
+///
+/// At most `n` sub-suites running all the tests of the given sub-suites.
+fn merge_sub_suites(sub_suites: Vec<SubSuite>, n: usize) -> Vec<SubSuite> {
+    if n == 0 || sub_suites.len() <= n {
+        return sub_suites;
+    }
+
+    let mut merged: Vec<SubSuite> = vec![];
+    for (i, sub_suite) in sub_suites.into_iter().enumerate() {
+        if i < n {
+            merged.push(sub_suite);
+            continue;
+        }
+        let target = &mut merged[i % n];
+        target.test_list.extend(sub_suite.test_list);
+        target.estimated_runtime = match (target.estimated_runtime, sub_suite.estimated_runtime) {
+            (Some(runtime), Some(other)) => Some(runtime + other),
+            _ => None,
+        };
+    }
+    merged
+}
+
+/// Order sub-suites by their estimated runtime, longest first.
+///
+/// The sort is stable, so sub-suites with equal runtimes keep their relative order, and
+/// sub-suites without an estimated runtime are placed last. The tests within each sub-suite
+/// are not reordered.
+///
+/// # Arguments
+///
+/// * `test_lists` - Tests belonging to each sub-suite.
+/// * `runtimes` - Estimated runtime of each sub-suite.
+///
+/// # Returns
+///
+/// The test lists and runtimes of the sub-suites in their new order.
+fn sort_sub_suites_by_runtime(
+    test_lists: Vec<Vec<String>>,
+    runtimes: Vec<Option<f64>>,
+) -> (Vec<Vec<String>>, Vec<Option<f64>>) {
+    let mut sub_suites: Vec<(Vec<String>, Option<f64>)> =
+        test_lists.into_iter().zip(runtimes).collect();
+    sub_suites.sort_by(|(_, a), (_, b)| match (a, b) {
+        (Some(a), Some(b)) => b.partial_cmp(a).unwrap_or(Ordering::Equal),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+    sub_suites.into_iter().unzip()
+}
+
+/// Get the index of sub suite with the least total runtime of tests.
+///
+/// # Arguments
+///
+/// * `running_runtimes` - Total runtimes of tests of sub suites.
+///
+/// # Returns
+///
+/// Index of sub suite with the least total runtime.
+fn get_min_index(running_runtimes: &[f64]) -> usize {
+    let mut min_idx = 0;
+    for (i, value) in running_runtimes.iter().enumerate() {
+        if value < &running_runtimes[min_idx] {
+            min_idx = i;
+        }
+    }
+    min_idx
+}
+
+#[async_trait]
+impl GenResmokeTaskService for GenResmokeTaskServiceImpl {
+    /// Generate a task for running the given task in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Parameters for how task should be generated.
+    /// * `build_variant` - Build variant to base task splitting on.
+    ///
+    /// # Returns
+    ///
+    /// A generated suite representing the split task.
+    async fn generate_resmoke_task(
+        &self,
+        params: &ResmokeGenParams,
+        build_variant: &BuildVariant,
+    ) -> Result<Box<dyn GeneratedSuite>> {
+        let suite_groups = if params.require_multiversion_generate_tasks {
+            self.create_multiversion_tasks(params, build_variant)
+                .await?
+        } else {
+            vec![self.create_tasks(params, build_variant, None, None).await?]
+        };
+        let sub_suites = self.limit_sub_suites(suite_groups)?;
+
+        let sub_task_total = sub_suites.len();
+        let suite_info = ResmokeSuiteGenerationInfo {
+            task_name: params.task_name.to_string(),
+            origin_suite: params.suite_name.to_string(),
+            sub_suites: sub_suites.clone(),
+            require_multiversion_generate_tasks: params.require_multiversion_generate_tasks,
+        };
+        let mut resmoke_config_actor = self.resmoke_config_actor.lock().await;
+        resmoke_config_actor.write_sub_suite(&suite_info).await;
+
+        Ok(Box::new(GeneratedResmokeSuite {
+            task_name: params.task_name.clone(),
+            sub_suites: sub_suites
+                .into_iter()
+                .map(|s| self.build_resmoke_sub_task(&s, sub_task_total, params, None))
+                .collect(),
+        }))
+    }
+
+    /// Build a shrub task to execute a split sub-task.
+    ///
+    /// # Arguments
+    ///
+    /// * `sub_suite` - Sub task to create task for.
+    /// * `params` - Parameters for how task should be generated.
+    ///
+    /// # Returns
     ///
     /// A shrub task to execute the given sub-suite.
     fn build_resmoke_sub_task(
@@ -781,7 +1604,14 @@ impl GenResmokeTaskService for GenResmokeTaskServiceImpl {
         let exclude_tags = self
             .multiversion_service
             .exclude_tags_for_task(&params.task_name, sub_suite.mv_exclude_tags.clone());
-        let suite_file = name_generated_task(
+        let sub_task_name = name_generated_task(
+            &sub_suite.name,
+            sub_suite.index,
+            total_sub_suites,
+            params.is_enterprise,
+            params.platform.as_deref(),
+        );
+        let suite_file = self.config.suite_file_naming.suite_file_name(
             &sub_suite.name,
             sub_suite.index,
             total_sub_suites,
@@ -794,20 +1624,25 @@ impl GenResmokeTaskService for GenResmokeTaskServiceImpl {
 
         let formatted_name = format!(
             "{}{}",
-            suite_file,
+            sub_task_name,
             params.gen_task_suffix.as_deref().unwrap_or("")
         );
+        let mut evg_task = EvgTask {
+            name: formatted_name,
+            commands: Some(resmoke_commands(
+                RUN_GENERATED_TESTS,
+                run_test_vars,
+                params.require_multiversion_setup,
+            )),
+            depends_on: params.get_dependencies(),
+            ..Default::default()
+        };
+        if let Some(task_decorator) = &self.task_decorator {
+            task_decorator.decorate(&mut evg_task, Some(sub_suite));
+        }
+
         GeneratedSubTask {
-            evg_task: EvgTask {
-                name: formatted_name,
-                commands: Some(resmoke_commands(
-                    RUN_GENERATED_TESTS,
-                    run_test_vars,
-                    params.require_multiversion_setup,
-                )),
-                depends_on: params.get_dependencies(),
-                ..Default::default()
-            },
+            evg_task,
             use_large_distro: params.use_large_distro,
             use_xlarge_distro: params.use_xlarge_distro,
         }
@@ -850,17 +1685,94 @@ fn resmoke_commands(
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use rstest::rstest;
 
     use crate::{
-        evergreen::evg_task_history::TestRuntimeHistory,
-        resmoke::{resmoke_proxy::MultiversionConfig, resmoke_suite::ResmokeSuiteConfig},
+        evergreen::timing_source::JsonFileTimingSource,
+        resmoke::{resmoke_proxy::MultiversionConfig, resmoke_suite::TestRoot},
     };
 
     use super::*;
 
     const MOCK_ENTERPRISE_DIR: &str = "src/enterprise";
 
+    // SubSuite tests.
+    #[test]
+    fn test_sub_suite_build_config_should_run_sub_suite_tests() {
+        let origin_config = ResmokeSuiteConfig::from_str(
+            "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_files:
+                - jstests/core/skip.js
+
+            executor:
+              fixture:
+                class: MongoDFixture
+            ",
+        )
+        .unwrap();
+        let sub_suite = SubSuite {
+            index: 1,
+            test_list: vec![
+                "jstests/core/a.js".to_string(),
+                "jstests/core/b.js".to_string(),
+            ],
+            estimated_runtime: Some(42.0),
+            ..Default::default()
+        };
+
+        let config = sub_suite.build_config(&origin_config);
+
+        if let Some(TestRoot::Roots { roots }) = config.selector.test_root {
+            assert_eq!(roots, sub_suite.test_list);
+        } else {
+            panic!("Unexpected test root: {:?}", config.selector.test_root);
+        }
+        assert_eq!(config.selector.exclude_files, None);
+    }
+
+    #[test]
+    fn test_sub_suite_build_config_should_replace_include_files_with_roots() {
+        let origin_config = ResmokeSuiteConfig::from_str(
+            "
+            test_kind: js_test
+
+            selector:
+              include_files:
+                - jstests/core/**/*.js
+
+            executor:
+              fixture:
+                class: MongoDFixture
+            ",
+        )
+        .unwrap();
+        let sub_suite = SubSuite {
+            index: 0,
+            test_list: vec![
+                "jstests/core/a.js".to_string(),
+                "jstests/core/nested/b.js".to_string(),
+            ],
+            ..Default::default()
+        };
+
+        let config = sub_suite.build_config(&origin_config);
+
+        assert_eq!(
+            config.selector.test_root,
+            Some(TestRoot::Roots {
+                roots: sub_suite.test_list.clone()
+            })
+        );
+        assert_eq!(config.selector.include_files, None);
+    }
+
     // ResmokeGenParams tests.
     #[test]
     fn test_build_run_test_vars() {
@@ -917,6 +1829,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_run_test_vars_with_sub_suite_metadata() {
+        let params = ResmokeGenParams {
+            suite_name: "my_suite".to_string(),
+            ..Default::default()
+        };
+        let sub_suite = SubSuite {
+            test_list: vec!["test_0.js".to_string(), "test_1.js".to_string()],
+            estimated_runtime: Some(120.5),
+            ..Default::default()
+        };
+
+        let test_vars = params.build_run_test_vars("my_suite_0", &sub_suite, "", None);
+
+        assert_eq!(test_vars.len(), 6);
+        assert_eq!(
+            test_vars.get(SUB_SUITE_TEST_COUNT).unwrap(),
+            &ParamValue::from(2)
+        );
+        assert_eq!(
+            test_vars.get(SUB_SUITE_ESTIMATED_RUNTIME).unwrap(),
+            &ParamValue::from(120.5)
+        );
+    }
+
     #[test]
     fn test_build_run_test_vars_for_multiversion() {
         let params = ResmokeGenParams {
@@ -1082,6 +2019,19 @@ mod tests {
         }
     }
 
+    struct MockFailingTaskHistoryService {}
+
+    #[async_trait]
+    impl TaskHistoryService for MockFailingTaskHistoryService {
+        async fn get_task_history(
+            &self,
+            _task: &str,
+            _variant: &str,
+        ) -> Result<TaskRuntimeHistory> {
+            bail!("Task history is unavailable")
+        }
+    }
+
     struct MockTestDiscovery {
         test_list: Vec<String>,
     }
@@ -1109,6 +2059,10 @@ mod tests {
         fn write_file(&self, _path: &std::path::Path, _contents: &str) -> Result<()> {
             Ok(())
         }
+
+        fn create_dir_all(&self, _path: &std::path::Path) -> Result<()> {
+            Ok(())
+        }
     }
 
     struct MockResmokeConfigActor {}
@@ -1147,10 +2101,16 @@ mod tests {
         let fs_service = MockFsService {};
         let resmoke_config_actor = MockResmokeConfigActor {};
 
-        let config = GenResmokeConfig::new(false, Some(MOCK_ENTERPRISE_DIR.to_string()));
+        let config = GenResmokeConfig::new(
+            false,
+            Some(MOCK_ENTERPRISE_DIR.to_string()),
+            SuiteFileNaming::default(),
+            None,
+        );
 
         GenResmokeTaskServiceImpl::new(
             Arc::new(task_history_service),
+            None,
             Arc::new(test_discovery),
             Arc::new(Mutex::new(resmoke_config_actor)),
             Arc::new(multiversion_service),
@@ -1161,7 +2121,11 @@ mod tests {
                 large_required_task_runtime_threshold: 7200.0,
                 default_subtasks_per_task: 5,
                 max_subtasks_per_task: 10,
+                max_tests_per_suite: None,
+                distribution_seed: None,
+                sort_sub_suites_by_runtime: false,
             },
+            None,
         )
     }
 
@@ -1203,6 +2167,7 @@ mod tests {
         let sub_suites = gen_resmoke_service
             .split_task(
                 &params,
+                test_list,
                 &task_history,
                 None,
                 None,
@@ -1225,21 +2190,82 @@ mod tests {
         assert!(suite_2.test_list.contains(&"test_5.js".to_string()));
     }
     #[test]
-    fn test_split_task_with_missing_history_should_split_tasks_equally() {
+    fn test_split_task_should_keep_affinity_groups_together() {
         let num_tasks = 3;
-        let test_list: Vec<String> = (0..12)
-            .into_iter()
-            .map(|i| format!("test_{}.js", i))
-            .collect();
+        let test_list: Vec<String> = (0..7).map(|i| format!("test_{}.js", i)).collect();
         let task_history = TaskRuntimeHistory {
             task_name: "my task".to_string(),
             test_map: hashmap! {
                 "test_0".to_string() => build_mock_test_runtime("test_0.js", 100.0),
-                "test_1".to_string() => build_mock_test_runtime("test_1.js", 50.0),
+                "test_1".to_string() => build_mock_test_runtime("test_1.js", 56.0),
                 "test_2".to_string() => build_mock_test_runtime("test_2.js", 50.0),
+                "test_3".to_string() => build_mock_test_runtime("test_3.js", 35.0),
+                "test_4".to_string() => build_mock_test_runtime("test_4.js", 34.0),
+                "test_5".to_string() => build_mock_test_runtime("test_5.js", 30.0),
             },
         };
-        let gen_resmoke_service = build_mocked_service(test_list, task_history.clone());
+        let mut gen_resmoke_service = build_mocked_service(test_list.clone(), task_history.clone());
+        let group_tags: HashSet<String> = ["must_run_together:group_a".to_string()]
+            .iter()
+            .cloned()
+            .collect();
+        gen_resmoke_service.test_discovery = Arc::new(MockTaggedTestDiscovery {
+            test_list: test_list.clone(),
+            test_tags: hashmap! {
+                "test_1.js".to_string() => group_tags.clone(),
+                "test_2.js".to_string() => group_tags.clone(),
+                "test_6.js".to_string() => group_tags,
+            },
+        });
+        let params = ResmokeGenParams {
+            num_tasks: Some(num_tasks),
+            ..Default::default()
+        };
+
+        let sub_suites = gen_resmoke_service
+            .split_task(
+                &params,
+                test_list.clone(),
+                &task_history,
+                None,
+                None,
+                &BuildVariant {
+                    display_name: Some("build-variant".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(sub_suites.len(), num_tasks);
+        let grouped_suite = sub_suites
+            .iter()
+            .find(|s| s.test_list.contains(&"test_1.js".to_string()))
+            .unwrap();
+        assert!(grouped_suite.test_list.contains(&"test_2.js".to_string()));
+        assert!(grouped_suite.test_list.contains(&"test_6.js".to_string()));
+        let mut all_tests: Vec<String> = sub_suites
+            .iter()
+            .flat_map(|s| s.test_list.clone())
+            .collect();
+        all_tests.sort();
+        assert_eq!(all_tests, test_list);
+    }
+    #[test]
+    fn test_split_task_with_missing_history_should_split_tasks_equally() {
+        let num_tasks = 3;
+        let test_list: Vec<String> = (0..12)
+            .into_iter()
+            .map(|i| format!("test_{}.js", i))
+            .collect();
+        let task_history = TaskRuntimeHistory {
+            task_name: "my task".to_string(),
+            test_map: hashmap! {
+                "test_0".to_string() => build_mock_test_runtime("test_0.js", 100.0),
+                "test_1".to_string() => build_mock_test_runtime("test_1.js", 50.0),
+                "test_2".to_string() => build_mock_test_runtime("test_2.js", 50.0),
+            },
+        };
+        let gen_resmoke_service = build_mocked_service(test_list.clone(), task_history.clone());
 
         let params = ResmokeGenParams {
             num_tasks: Some(num_tasks),
@@ -1249,6 +2275,7 @@ mod tests {
         let sub_suites = gen_resmoke_service
             .split_task(
                 &params,
+                test_list,
                 &task_history,
                 None,
                 None,
@@ -1282,7 +2309,7 @@ mod tests {
                 "test_2".to_string() => build_mock_test_runtime("test_2.js", 50.0),
             },
         };
-        let gen_resmoke_service = build_mocked_service(test_list, task_history.clone());
+        let gen_resmoke_service = build_mocked_service(test_list.clone(), task_history.clone());
 
         let params = ResmokeGenParams {
             num_tasks: Some(num_tasks),
@@ -1292,6 +2319,7 @@ mod tests {
         let sub_suites = gen_resmoke_service
             .split_task(
                 &params,
+                test_list,
                 &task_history,
                 Some("multiversion_test"),
                 Some("multiversion_tag".to_string()),
@@ -1311,6 +2339,287 @@ mod tests {
             );
         }
     }
+    #[test]
+    fn test_split_tasks_should_estimate_runtime_of_sub_suites() {
+        let num_tasks = 2;
+        let test_list: Vec<String> = (0..3).map(|i| format!("test_{}.js", i)).collect();
+        let task_history = TaskRuntimeHistory {
+            task_name: "my task".to_string(),
+            test_map: hashmap! {
+                "test_0".to_string() => build_mock_test_runtime("test_0.js", 100.0),
+                "test_1".to_string() => build_mock_test_runtime("test_1.js", 60.0),
+                "test_2".to_string() => build_mock_test_runtime("test_2.js", 30.0),
+            },
+        };
+        let gen_resmoke_service = build_mocked_service(test_list.clone(), task_history.clone());
+
+        let params = ResmokeGenParams {
+            num_tasks: Some(num_tasks),
+            ..Default::default()
+        };
+
+        let sub_suites = gen_resmoke_service
+            .split_task(
+                &params,
+                test_list,
+                &task_history,
+                None,
+                None,
+                &BuildVariant {
+                    display_name: Some("build-variant".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(sub_suites.len(), num_tasks);
+        assert_eq!(sub_suites[0].estimated_runtime, Some(100.0));
+        assert_eq!(sub_suites[1].estimated_runtime, Some(90.0));
+    }
+
+    #[test]
+    fn test_split_tasks_should_order_sub_suites_by_runtime_when_requested() {
+        let num_tasks = 3;
+        let test_list: Vec<String> = (0..4).map(|i| format!("test_{}.js", i)).collect();
+        let task_history = TaskRuntimeHistory {
+            task_name: "my task".to_string(),
+            test_map: hashmap! {
+                "test_0".to_string() => build_mock_test_runtime("test_0.js", 100.0),
+                "test_1".to_string() => build_mock_test_runtime("test_1.js", 50.0),
+                "test_2".to_string() => build_mock_test_runtime("test_2.js", 40.0),
+                "test_3".to_string() => build_mock_test_runtime("test_3.js", 30.0),
+            },
+        };
+        let mut gen_resmoke_service = build_mocked_service(test_list.clone(), task_history.clone());
+        gen_resmoke_service
+            .subtask_limits
+            .sort_sub_suites_by_runtime = true;
+
+        let params = ResmokeGenParams {
+            num_tasks: Some(num_tasks),
+            ..Default::default()
+        };
+
+        let sub_suites = gen_resmoke_service
+            .split_task(
+                &params,
+                test_list,
+                &task_history,
+                None,
+                None,
+                &BuildVariant {
+                    display_name: Some("build-variant".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let runtimes: Vec<Option<f64>> = sub_suites.iter().map(|s| s.estimated_runtime).collect();
+        assert_eq!(runtimes, vec![Some(100.0), Some(70.0), Some(50.0)]);
+        let indexes: Vec<usize> = sub_suites.iter().map(|s| s.index).collect();
+        assert_eq!(indexes, vec![0, 1, 2]);
+        assert_eq!(sub_suites[0].test_list, vec!["test_0.js"]);
+        assert_eq!(sub_suites[1].test_list, vec!["test_2.js", "test_3.js"]);
+        assert_eq!(sub_suites[2].test_list, vec!["test_1.js"]);
+    }
+
+    #[test]
+    fn test_split_tasks_without_runtimes_should_split_by_count() {
+        let num_tasks = 3;
+        let test_list: Vec<String> = (0..7).map(|i| format!("test_{}.js", i)).collect();
+        let task_history = TaskRuntimeHistory {
+            task_name: "my task".to_string(),
+            test_map: hashmap! {},
+        };
+        let gen_resmoke_service = build_mocked_service(test_list.clone(), task_history.clone());
+
+        let params = ResmokeGenParams {
+            num_tasks: Some(num_tasks),
+            ..Default::default()
+        };
+
+        let sub_suites = gen_resmoke_service
+            .split_task(
+                &params,
+                test_list,
+                &task_history,
+                None,
+                None,
+                &BuildVariant {
+                    display_name: Some("build-variant".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(sub_suites.len(), num_tasks);
+        assert_eq!(
+            sub_suites
+                .iter()
+                .map(|s| s.test_list.len())
+                .collect::<Vec<_>>(),
+            vec![3, 2, 2]
+        );
+        assert!(sub_suites.iter().all(|s| s.estimated_runtime.is_none()));
+    }
+
+    // sort_sub_suites_by_runtime tests.
+    #[test]
+    fn test_sort_sub_suites_by_runtime_should_order_longest_first() {
+        let test_lists = vec![
+            vec!["test_0.js".to_string()],
+            vec!["test_1.js".to_string()],
+            vec!["test_2.js".to_string(), "test_3.js".to_string()],
+            vec!["test_4.js".to_string()],
+            vec!["test_5.js".to_string()],
+        ];
+        let runtimes = vec![Some(10.0), None, Some(30.0), Some(10.0), Some(20.0)];
+
+        let (test_lists, runtimes) = sort_sub_suites_by_runtime(test_lists, runtimes);
+
+        assert_eq!(
+            runtimes,
+            vec![Some(30.0), Some(20.0), Some(10.0), Some(10.0), None]
+        );
+        assert_eq!(
+            test_lists,
+            vec![
+                vec!["test_2.js", "test_3.js"],
+                vec!["test_5.js"],
+                vec!["test_0.js"],
+                vec!["test_4.js"],
+                vec!["test_1.js"],
+            ]
+        );
+    }
+
+    // get_task_history tests.
+    fn build_timing_source(contents: &str) -> Option<Arc<dyn TimingSource>> {
+        Some(Arc::new(JsonFileTimingSource::from_json(contents).unwrap()))
+    }
+
+    #[tokio::test]
+    async fn test_get_task_history_should_not_query_service_if_all_tests_are_cached() {
+        let test_list = vec![
+            "jstests/test_0.js".to_string(),
+            "jstests/test_1.js".to_string(),
+        ];
+        let mut gen_resmoke_service = build_mocked_service(
+            test_list.clone(),
+            TaskRuntimeHistory {
+                task_name: "my_task".to_string(),
+                test_map: hashmap! {},
+            },
+        );
+        gen_resmoke_service.task_history_service = Arc::new(MockFailingTaskHistoryService {});
+        gen_resmoke_service.timing_source =
+            build_timing_source(r#"{"jstests/test_0.js": 10.0, "jstests/test_1.js": 20.0}"#);
+
+        let task_history = gen_resmoke_service
+            .get_task_history(
+                &ResmokeGenParams::default(),
+                &BuildVariant::default(),
+                &test_list,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(task_history.test_map.len(), 2);
+        assert_eq!(task_history.test_map["test_0"].average_runtime, 10.0);
+        assert_eq!(
+            task_history.test_map["test_0"].test_name,
+            "jstests/test_0.js"
+        );
+        assert_eq!(task_history.test_map["test_1"].average_runtime, 20.0);
+    }
+
+    #[tokio::test]
+    async fn test_get_task_history_should_query_service_for_missing_tests() {
+        let test_list = vec![
+            "jstests/test_0.js".to_string(),
+            "jstests/test_1.js".to_string(),
+        ];
+        let gen_resmoke_service = GenResmokeTaskServiceImpl {
+            timing_source: build_timing_source(r#"{"jstests/test_0.js": 10.0}"#),
+            ..build_mocked_service(
+                test_list.clone(),
+                TaskRuntimeHistory {
+                    task_name: "my_task".to_string(),
+                    test_map: hashmap! {
+                        "test_0".to_string() => build_mock_test_runtime("jstests/test_0.js", 50.0),
+                        "test_1".to_string() => build_mock_test_runtime("jstests/test_1.js", 30.0),
+                    },
+                },
+            )
+        };
+
+        let task_history = gen_resmoke_service
+            .get_task_history(
+                &ResmokeGenParams::default(),
+                &BuildVariant::default(),
+                &test_list,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(task_history.test_map.len(), 2);
+        assert_eq!(task_history.test_map["test_0"].average_runtime, 10.0);
+        assert_eq!(task_history.test_map["test_1"].average_runtime, 30.0);
+    }
+
+    #[tokio::test]
+    async fn test_get_task_history_should_use_cached_tests_if_service_fails() {
+        let test_list = vec![
+            "jstests/test_0.js".to_string(),
+            "jstests/test_1.js".to_string(),
+        ];
+        let mut gen_resmoke_service = build_mocked_service(
+            test_list.clone(),
+            TaskRuntimeHistory {
+                task_name: "my_task".to_string(),
+                test_map: hashmap! {},
+            },
+        );
+        gen_resmoke_service.task_history_service = Arc::new(MockFailingTaskHistoryService {});
+        gen_resmoke_service.timing_source = build_timing_source(r#"{"jstests/test_0.js": 10.0}"#);
+
+        let task_history = gen_resmoke_service
+            .get_task_history(
+                &ResmokeGenParams::default(),
+                &BuildVariant::default(),
+                &test_list,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(task_history.test_map.len(), 1);
+        assert_eq!(task_history.test_map["test_0"].average_runtime, 10.0);
+    }
+
+    #[tokio::test]
+    async fn test_get_task_history_should_fail_if_nothing_is_cached_and_service_fails() {
+        let test_list = vec!["jstests/test_0.js".to_string()];
+        let mut gen_resmoke_service = build_mocked_service(
+            test_list.clone(),
+            TaskRuntimeHistory {
+                task_name: "my_task".to_string(),
+                test_map: hashmap! {},
+            },
+        );
+        gen_resmoke_service.task_history_service = Arc::new(MockFailingTaskHistoryService {});
+        gen_resmoke_service.timing_source = build_timing_source(r#"{"jstests/other.js": 10.0}"#);
+
+        let task_history = gen_resmoke_service
+            .get_task_history(
+                &ResmokeGenParams::default(),
+                &BuildVariant::default(),
+                &test_list,
+            )
+            .await;
+
+        assert!(task_history.is_err());
+    }
+
     // split_task_fallback tests
     #[test]
     fn test_split_task_fallback_should_split_tasks_count() {
@@ -1348,6 +2657,53 @@ mod tests {
         }
     }
     #[test]
+    fn test_split_task_fallback_should_keep_affinity_groups_together() {
+        let num_tasks = 3;
+        let test_list: Vec<String> = (0..6).map(|i| format!("test_{}.js", i)).collect();
+        let task_history = TaskRuntimeHistory {
+            task_name: "my task".to_string(),
+            test_map: hashmap! {},
+        };
+        let mut gen_resmoke_service = build_mocked_service(test_list.clone(), task_history);
+        let group_tags: HashSet<String> = ["must_run_together:group_a".to_string()]
+            .iter()
+            .cloned()
+            .collect();
+        gen_resmoke_service.test_discovery = Arc::new(MockTaggedTestDiscovery {
+            test_list: test_list.clone(),
+            test_tags: hashmap! {
+                "test_0.js".to_string() => group_tags.clone(),
+                "test_3.js".to_string() => group_tags.clone(),
+                "test_5.js".to_string() => group_tags,
+            },
+        });
+        let params = ResmokeGenParams {
+            num_tasks: Some(num_tasks),
+            ..Default::default()
+        };
+
+        let sub_suites = gen_resmoke_service
+            .split_task_fallback(&params, None, None)
+            .unwrap();
+
+        assert_eq!(sub_suites.len(), num_tasks);
+        let grouped_suite = sub_suites
+            .iter()
+            .find(|s| s.test_list.contains(&"test_0.js".to_string()))
+            .unwrap();
+        assert!(grouped_suite.test_list.contains(&"test_3.js".to_string()));
+        assert!(grouped_suite.test_list.contains(&"test_5.js".to_string()));
+        for sub_suite in sub_suites.iter().filter(|s| s.index != grouped_suite.index) {
+            assert!(!sub_suite.test_list.is_empty());
+        }
+        let mut all_tests: Vec<String> = sub_suites
+            .iter()
+            .flat_map(|s| s.test_list.clone())
+            .collect();
+        all_tests.sort();
+        assert_eq!(all_tests, test_list);
+    }
+    #[test]
     fn test_split_task_fallback_has_remainder() {
         let num_tasks = 3;
         let n_tests = 4;
@@ -1503,7 +2859,7 @@ mod tests {
         ];
         let gen_resmoke_service = build_mocked_service(test_list.clone(), task_history);
 
-        let suite_list = gen_resmoke_service
+        let suite_list: Vec<SubSuite> = gen_resmoke_service
             .create_multiversion_tasks(
                 &params,
                 &BuildVariant {
@@ -1512,7 +2868,10 @@ mod tests {
                 },
             )
             .await
-            .unwrap();
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect();
 
         assert_eq!(suite_list[0].name, "suite1_last_lts".to_string());
         assert_eq!(suite_list[0].mv_exclude_tags, Some("last-lts".to_string()));
@@ -1792,68 +3151,822 @@ mod tests {
             .await
             .unwrap();
     }
-    // resmoke_commands tests.
-    fn get_evg_fn_name(evg_command: &EvgCommand) -> Option<&str> {
-        if let EvgCommand::Function(func) = evg_command {
-            Some(&func.func)
-        } else {
-            None
-        }
-    }
-    #[test]
-    fn test_resmoke_commands() {
-        let commands = resmoke_commands("run test", hashmap! {}, false);
-        assert_eq!(commands.len(), 3);
-        assert_eq!(get_evg_fn_name(&commands[0]), Some("do setup"));
-        assert_eq!(get_evg_fn_name(&commands[2]), Some("run test"));
-    }
-    #[test]
-    fn test_resmoke_commands_should_include_multiversion() {
-        let commands = resmoke_commands("run test", hashmap! {}, true);
-        assert_eq!(commands.len(), 6);
-        assert_eq!(get_evg_fn_name(&commands[2]), Some("do setup"));
-        assert_eq!(get_evg_fn_name(&commands[4]), Some("do multiversion setup"));
-        assert_eq!(get_evg_fn_name(&commands[5]), Some("run test"));
-    }
-    // sort_tests_by_runtime tests.
+
+    #[tokio::test]
+    async fn test_generate_resmoke_task_should_fit_sub_suites_in_total_budget() {
+        let test_list: Vec<String> = (0..6).map(|i| format!("test_{}.js", i)).collect();
+        let task_history = TaskRuntimeHistory {
+            task_name: "my_task".to_string(),
+            test_map: hashmap! {},
+        };
+        let mut gen_resmoke_service = build_mocked_service(test_list, task_history);
+        gen_resmoke_service.config.max_total_subtasks = Some(4);
+        let params = ResmokeGenParams {
+            task_name: "my_task".to_string(),
+            multiversion_generate_tasks: Some(vec![
+                MultiversionGenerateTaskConfig {
+                    suite_name: "suite1_last_lts".to_string(),
+                    old_version: "last-lts".to_string(),
+                },
+                MultiversionGenerateTaskConfig {
+                    suite_name: "suite1_last_continuous".to_string(),
+                    old_version: "last-continuous".to_string(),
+                },
+            ]),
+            require_multiversion_generate_tasks: true,
+            num_tasks: Some(3),
+            ..Default::default()
+        };
+
+        let suite = gen_resmoke_service
+            .generate_resmoke_task(
+                &params,
+                &BuildVariant {
+                    display_name: Some("build-variant".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let task_names: Vec<String> = suite
+            .sub_tasks()
+            .into_iter()
+            .map(|sub_task| sub_task.evg_task.name)
+            .collect();
+        assert_eq!(
+            task_names,
+            vec![
+                "suite1_last_lts_0",
+                "suite1_last_lts_1",
+                "suite1_last_continuous_0",
+                "suite1_last_continuous_1",
+            ]
+        );
+    }
+
+    // merge_sub_suites tests.
+    #[test]
+    fn test_merge_sub_suites_should_keep_all_tests() {
+        let sub_suites: Vec<SubSuite> = (0..5)
+            .map(|i| SubSuite {
+                index: i,
+                test_list: vec![format!("test_{}.js", i)],
+                estimated_runtime: Some(10.0),
+                ..Default::default()
+            })
+            .collect();
+
+        let merged = merge_sub_suites(sub_suites, 2);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].index, 0);
+        assert_eq!(
+            merged[0].test_list,
+            vec!["test_0.js", "test_2.js", "test_4.js"]
+        );
+        assert_eq!(merged[0].estimated_runtime, Some(30.0));
+        assert_eq!(merged[1].index, 1);
+        assert_eq!(merged[1].test_list, vec!["test_1.js", "test_3.js"]);
+        assert_eq!(merged[1].estimated_runtime, Some(20.0));
+    }
+
+    // build_resmoke_sub_task tests.
+    #[test]
+    fn test_build_resmoke_sub_task_should_include_test_count() {
+        let gen_resmoke_service = build_mocked_service(
+            vec![],
+            TaskRuntimeHistory {
+                task_name: "my task".to_string(),
+                test_map: hashmap! {},
+            },
+        );
+        let params = ResmokeGenParams {
+            task_name: "my_task".to_string(),
+            suite_name: "my_suite".to_string(),
+            ..Default::default()
+        };
+        let sub_suite = SubSuite {
+            index: 0,
+            name: "my_task".to_string(),
+            test_list: vec![
+                "test_0.js".to_string(),
+                "test_1.js".to_string(),
+                "test_2.js".to_string(),
+            ],
+            origin_suite: "my_suite".to_string(),
+            ..Default::default()
+        };
+
+        let sub_task = gen_resmoke_service.build_resmoke_sub_task(&sub_suite, 1, &params, None);
+
+        let commands = sub_task.evg_task.commands.unwrap();
+        if let Some(EvgCommand::Function(func)) = commands.last() {
+            let vars = func.vars.as_ref().unwrap();
+            assert_eq!(vars.get(SUB_SUITE_TEST_COUNT), Some(&ParamValue::from(3)));
+            assert!(!vars.contains_key(SUB_SUITE_ESTIMATED_RUNTIME));
+        } else {
+            panic!("Unexpected last command: {:?}", commands.last());
+        }
+    }
+
+    #[test]
+    fn test_build_resmoke_sub_task_should_use_suite_file_naming() {
+        let mut gen_resmoke_service = build_mocked_service(
+            vec![],
+            TaskRuntimeHistory {
+                task_name: "my task".to_string(),
+                test_map: hashmap! {},
+            },
+        );
+        gen_resmoke_service.config.suite_file_naming = SuiteFileNaming::ZeroPadded(3);
+        let params = ResmokeGenParams {
+            task_name: "my_task".to_string(),
+            suite_name: "my_suite".to_string(),
+            ..Default::default()
+        };
+        let sub_suite = SubSuite {
+            index: 4,
+            name: "my_task".to_string(),
+            test_list: vec!["test_0.js".to_string()],
+            origin_suite: "my_suite".to_string(),
+            ..Default::default()
+        };
+
+        let sub_task = gen_resmoke_service.build_resmoke_sub_task(&sub_suite, 5, &params, None);
+
+        assert_eq!(sub_task.evg_task.name, "my_task_4");
+        let commands = sub_task.evg_task.commands.unwrap();
+        if let Some(EvgCommand::Function(func)) = commands.last() {
+            let vars = func.vars.as_ref().unwrap();
+            assert_eq!(
+                vars.get(SUITE_NAME),
+                Some(&ParamValue::from(
+                    "generated_resmoke_config/my_task_004.yml"
+                ))
+            );
+        } else {
+            panic!("Unexpected last command: {:?}", commands.last());
+        }
+    }
+
+    struct TagTaskDecorator;
+    impl TaskDecorator for TagTaskDecorator {
+        fn decorate(&self, task: &mut EvgTask, sub_suite: Option<&SubSuite>) {
+            task.tags
+                .get_or_insert_with(Vec::new)
+                .push(format!("from_{}", sub_suite.unwrap().origin_suite));
+            task.depends_on = Some(vec![TaskDependency {
+                name: "archive_dist_test".to_string(),
+                variant: None,
+            }]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_resmoke_task_should_decorate_every_sub_task() {
+        let test_list: Vec<String> = (0..6).map(|i| format!("test_{}.js", i)).collect();
+        let mut gen_resmoke_service = build_mocked_service(
+            test_list,
+            TaskRuntimeHistory {
+                task_name: "my_task".to_string(),
+                test_map: hashmap! {},
+            },
+        );
+        gen_resmoke_service.task_decorator = Some(Arc::new(TagTaskDecorator));
+        let params = ResmokeGenParams {
+            task_name: "my_task".to_string(),
+            suite_name: "my_suite".to_string(),
+            num_tasks: Some(3),
+            ..Default::default()
+        };
+
+        let suite = gen_resmoke_service
+            .generate_resmoke_task(
+                &params,
+                &BuildVariant {
+                    display_name: Some("build-variant".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let sub_tasks = suite.sub_tasks();
+        assert_eq!(sub_tasks.len(), 3);
+        for sub_task in sub_tasks {
+            assert_eq!(
+                sub_task.evg_task.tags,
+                Some(vec!["from_my_suite".to_string()])
+            );
+            assert_eq!(
+                sub_task.evg_task.depends_on.unwrap()[0].name,
+                "archive_dist_test"
+            );
+        }
+    }
+
+    struct MockTaggedTestDiscovery {
+        test_list: Vec<String>,
+        test_tags: HashMap<String, HashSet<String>>,
+    }
+
+    impl TestDiscovery for MockTaggedTestDiscovery {
+        fn discover_tests(&self, _suite_name: &str) -> Result<Vec<String>> {
+            Ok(self.test_list.clone())
+        }
+
+        fn get_suite_config(&self, _suite_name: &str) -> Result<ResmokeSuiteConfig> {
+            ResmokeSuiteConfig::from_str(
+                "
+                test_kind: js_test
+
+                selector:
+                  roots:
+                    - jstests/core/*.js
+                  exclude_with_any_tags:
+                    - requires_sharding
+
+                executor:
+                  fixture:
+                    class: MyFixture
+                ",
+            )
+            .map_err(anyhow::Error::from)
+        }
+
+        fn get_multiversion_config(&self) -> Result<MultiversionConfig> {
+            Ok(MultiversionConfig {
+                last_versions: vec![],
+                requires_fcv_tag: "requires_fcv".to_string(),
+                requires_fcv_tag_lts: None,
+                requires_fcv_tag_continuous: None,
+            })
+        }
+
+        fn get_test_tags(
+            &self,
+            _suite_name: &str,
+        ) -> Result<Option<HashMap<String, HashSet<String>>>> {
+            Ok(Some(self.test_tags.clone()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_resmoke_task_should_drop_fully_excluded_sub_suites() {
+        let test_list: Vec<String> = (0..6).map(|i| format!("test_{}.js", i)).collect();
+        let mut gen_resmoke_service = build_mocked_service(
+            vec![],
+            TaskRuntimeHistory {
+                task_name: "my_task".to_string(),
+                test_map: hashmap! {},
+            },
+        );
+        gen_resmoke_service.test_discovery = Arc::new(MockTaggedTestDiscovery {
+            test_list,
+            test_tags: hashmap! {
+                "test_2.js".to_string() => ["requires_sharding".to_string()].iter().cloned().collect(),
+                "test_3.js".to_string() => ["requires_sharding".to_string()].iter().cloned().collect(),
+            },
+        });
+        let params = ResmokeGenParams {
+            task_name: "my_task".to_string(),
+            suite_name: "my_suite".to_string(),
+            num_tasks: Some(3),
+            ..Default::default()
+        };
+
+        let suite = gen_resmoke_service
+            .generate_resmoke_task(
+                &params,
+                &BuildVariant {
+                    display_name: Some("build-variant".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let task_names: Vec<String> = suite
+            .sub_tasks()
+            .into_iter()
+            .map(|sub_task| sub_task.evg_task.name)
+            .collect();
+        assert_eq!(task_names, vec!["my_task_0", "my_task_1"]);
+    }
+
+    #[test]
+    fn test_build_resmoke_sub_task_without_decorator_should_have_no_tags() {
+        let gen_resmoke_service = build_mocked_service(
+            vec![],
+            TaskRuntimeHistory {
+                task_name: "my task".to_string(),
+                test_map: hashmap! {},
+            },
+        );
+        let params = ResmokeGenParams {
+            task_name: "my_task".to_string(),
+            suite_name: "my_suite".to_string(),
+            ..Default::default()
+        };
+        let sub_suite = SubSuite {
+            name: "my_task".to_string(),
+            test_list: vec!["test_0.js".to_string()],
+            origin_suite: "my_suite".to_string(),
+            ..Default::default()
+        };
+
+        let sub_task = gen_resmoke_service.build_resmoke_sub_task(&sub_suite, 1, &params, None);
+
+        assert_eq!(sub_task.evg_task.tags, None);
+    }
+
+    // resmoke_commands tests.
+    fn get_evg_fn_name(evg_command: &EvgCommand) -> Option<&str> {
+        if let EvgCommand::Function(func) = evg_command {
+            Some(&func.func)
+        } else {
+            None
+        }
+    }
+    #[test]
+    fn test_resmoke_commands() {
+        let commands = resmoke_commands("run test", hashmap! {}, false);
+        assert_eq!(commands.len(), 3);
+        assert_eq!(get_evg_fn_name(&commands[0]), Some("do setup"));
+        assert_eq!(get_evg_fn_name(&commands[2]), Some("run test"));
+    }
+    #[test]
+    fn test_resmoke_commands_should_include_multiversion() {
+        let commands = resmoke_commands("run test", hashmap! {}, true);
+        assert_eq!(commands.len(), 6);
+        assert_eq!(get_evg_fn_name(&commands[2]), Some("do setup"));
+        assert_eq!(get_evg_fn_name(&commands[4]), Some("do multiversion setup"));
+        assert_eq!(get_evg_fn_name(&commands[5]), Some("run test"));
+    }
+
+    // distribute_with_affinity tests.
+    #[test]
+    fn test_distribute_with_affinity_should_keep_groups_together() {
+        let group = Some("groupA".to_string());
+        let tests = vec![
+            ("test_0.js".to_string(), 10.0, group.clone()),
+            ("test_1.js".to_string(), 10.0, group.clone()),
+            ("test_2.js".to_string(), 20.0, None),
+            ("test_3.js".to_string(), 10.0, group),
+            ("test_4.js".to_string(), 5.0, None),
+            ("test_5.js".to_string(), 5.0, None),
+        ];
+
+        let result = distribute_with_affinity(&tests, 2);
+
+        assert_eq!(
+            result,
+            vec![
+                vec!["test_0.js", "test_1.js", "test_3.js"],
+                vec!["test_2.js", "test_4.js", "test_5.js"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_distribute_with_affinity_should_balance_ungrouped_tests_around_groups() {
+        let tests = vec![
+            ("test_0.js".to_string(), 30.0, Some("groupA".to_string())),
+            ("test_1.js".to_string(), 30.0, Some("groupA".to_string())),
+            ("test_2.js".to_string(), 20.0, Some("groupB".to_string())),
+            ("test_3.js".to_string(), 20.0, None),
+            ("test_4.js".to_string(), 20.0, None),
+            ("test_5.js".to_string(), 20.0, Some("groupB".to_string())),
+            ("test_6.js".to_string(), 10.0, None),
+        ];
+
+        let result = distribute_with_affinity(&tests, 3);
+
+        assert_eq!(result.len(), 3);
+        let bin_of = |test: &str| result.iter().position(|b| b.contains(&test.to_string()));
+        assert_eq!(bin_of("test_0.js"), bin_of("test_1.js"));
+        assert_eq!(bin_of("test_2.js"), bin_of("test_5.js"));
+        assert_ne!(bin_of("test_0.js"), bin_of("test_2.js"));
+        assert_eq!(
+            result,
+            vec![
+                vec!["test_0.js", "test_1.js"],
+                vec!["test_2.js", "test_5.js", "test_6.js"],
+                vec!["test_3.js", "test_4.js"],
+            ]
+        );
+        assert_eq!(result.iter().map(|b| b.len()).sum::<usize>(), tests.len());
+    }
+
+    #[test]
+    fn test_distribute_with_affinity_with_no_bins_should_be_empty() {
+        let tests = vec![("test_0.js".to_string(), 10.0, None)];
+
+        assert!(distribute_with_affinity(&tests, 0).is_empty());
+    }
+
+    // distribute_tests_by_runtime tests.
     #[rstest]
     #[case(vec![100.0, 50.0, 30.0, 25.0, 20.0, 15.0], vec![0, 1, 2, 3, 4, 5])]
     #[case(vec![15.0, 20.0, 25.0, 30.0, 50.0, 100.0], vec![5, 4, 3, 2, 1, 0])]
     #[case(vec![15.0, 50.0, 25.0, 30.0, 20.0, 100.0], vec![5, 1, 3, 2, 4, 0])]
-    #[case(vec![100.0, 50.0, 30.0], vec![0, 1, 2, 3, 4, 5])]
-    #[case(vec![30.0, 50.0, 100.0], vec![2, 1, 0, 3, 4, 5])]
-    #[case(vec![30.0, 100.0, 50.0], vec![1, 2, 0, 3, 4, 5])]
-    #[case(vec![], vec![0, 1, 2, 3, 4, 5])]
-    fn test_sort_tests_by_runtime(
+    #[case(vec![30.0, 30.0, 0.0, 30.0], vec![0, 1, 3, 2])]
+    #[case(vec![], vec![])]
+    fn test_distribute_tests_by_runtime_with_one_bin_should_sort_by_runtime(
         #[case] historic_runtimes: Vec<f64>,
-        #[case] sorted_indexes: Vec<i32>,
+        #[case] sorted_indexes: Vec<usize>,
     ) {
-        let test_list: Vec<String> = (0..sorted_indexes.len())
-            .into_iter()
+        let tests: Vec<(String, f64)> = historic_runtimes
+            .iter()
+            .enumerate()
+            .map(|(i, runtime)| (format!("test_{}.js", i), *runtime))
+            .collect();
+        let expected_result: Vec<String> = sorted_indexes
+            .iter()
             .map(|i| format!("test_{}.js", i))
             .collect();
-        let task_stats = TaskRuntimeHistory {
-            task_name: "my_task".to_string(),
-            test_map: (0..historic_runtimes.len())
-                .into_iter()
-                .map(|i| {
-                    (
-                        format!("test_{}", i),
-                        build_mock_test_runtime(
-                            format!("test_{}.js", i).as_ref(),
-                            historic_runtimes[i],
-                        ),
-                    )
-                })
-                .collect::<HashMap<_, _>>(),
+
+        let result = distribute_tests_by_runtime(&tests, 1);
+
+        assert_eq!(result, vec![expected_result]);
+    }
+
+    #[test]
+    fn test_distribute_tests_by_runtime_should_balance_runtimes() {
+        let tests = vec![
+            ("test_0.js".to_string(), 100.0),
+            ("test_1.js".to_string(), 60.0),
+            ("test_2.js".to_string(), 50.0),
+            ("test_3.js".to_string(), 40.0),
+            ("test_4.js".to_string(), 10.0),
+        ];
+
+        let result = distribute_tests_by_runtime(&tests, 2);
+
+        assert_eq!(
+            result,
+            vec![
+                vec!["test_0.js".to_string(), "test_3.js".to_string()],
+                vec![
+                    "test_1.js".to_string(),
+                    "test_2.js".to_string(),
+                    "test_4.js".to_string()
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_distribute_tests_by_runtime_should_be_deterministic() {
+        let tests: Vec<(String, f64)> = (0..20).map(|i| (format!("test_{}.js", i), 10.0)).collect();
+        let mut reversed_tests = tests.clone();
+        reversed_tests.reverse();
+
+        let result = distribute_tests_by_runtime(&tests, 3);
+
+        assert_eq!(result, distribute_tests_by_runtime(&reversed_tests, 3));
+        assert_eq!(
+            result.iter().map(|t| t.len()).collect::<Vec<_>>(),
+            vec![7, 7, 6]
+        );
+    }
+
+    #[test]
+    fn test_distribute_tests_by_runtime_with_more_bins_than_tests() {
+        let tests = vec![
+            ("test_0.js".to_string(), 0.0),
+            ("test_1.js".to_string(), 0.0),
+        ];
+
+        let result = distribute_tests_by_runtime(&tests, 4);
+
+        assert_eq!(
+            result,
+            vec![
+                vec!["test_0.js".to_string()],
+                vec!["test_1.js".to_string()],
+                vec![],
+                vec![],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_distribute_tests_by_runtime_with_no_bins() {
+        let tests = vec![("test_0.js".to_string(), 10.0)];
+
+        assert!(distribute_tests_by_runtime(&tests, 0).is_empty());
+    }
+
+    // distribute_tests_by_max_runtime tests.
+    #[test]
+    fn test_distribute_tests_by_max_runtime_should_pack_tests_under_cap() {
+        let tests = vec![
+            ("test_0".to_string(), 10.0),
+            ("test_1".to_string(), 5.0),
+            ("test_2".to_string(), 5.0),
+            ("test_3".to_string(), 15.0),
+            ("test_4".to_string(), 4.0),
+            ("test_5".to_string(), 8.0),
+        ];
+
+        let sub_suites = distribute_tests_by_max_runtime(&tests, 20.0);
+
+        assert_eq!(
+            sub_suites,
+            vec![
+                vec!["test_0", "test_1", "test_2"],
+                vec!["test_3", "test_4"],
+                vec!["test_5"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_distribute_tests_by_max_runtime_should_isolate_tests_over_cap() {
+        let tests = vec![
+            ("test_0".to_string(), 5.0),
+            ("test_1".to_string(), 50.0),
+            ("test_2".to_string(), 5.0),
+            ("test_3".to_string(), 60.0),
+        ];
+
+        let sub_suites = distribute_tests_by_max_runtime(&tests, 20.0);
+
+        assert_eq!(
+            sub_suites,
+            vec![
+                vec!["test_0"],
+                vec!["test_1"],
+                vec!["test_2"],
+                vec!["test_3"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_distribute_tests_by_max_runtime_with_no_tests_should_be_empty() {
+        assert!(distribute_tests_by_max_runtime(&[], 20.0).is_empty());
+    }
+
+    // sum_runtime tests.
+    #[rstest]
+    #[case(0.0, 30.0)]
+    #[case(5.0, 40.0)]
+    fn test_sum_runtime_should_count_selected_tests(
+        #[case] default_runtime: f64,
+        #[case] expected_runtime: f64,
+    ) {
+        let mut selector = ResmokeSelector::builder()
+            .roots(vec!["jstests/core/*.js".to_string()])
+            .build();
+        selector.exclude_files = Some(vec!["jstests/core/excluded.js".to_string()]);
+        let tests = vec![
+            ("jstests/core/a.js".to_string(), Some(10.0)),
+            ("jstests/core/b.js".to_string(), None),
+            ("jstests/core/c.js".to_string(), Some(20.0)),
+            ("jstests/core/d.js".to_string(), None),
+            ("jstests/core/excluded.js".to_string(), Some(100.0)),
+            ("jstests/auth/e.js".to_string(), None),
+        ];
+
+        assert_eq!(
+            sum_runtime(&tests, &selector, default_runtime),
+            expected_runtime
+        );
+    }
+
+    #[test]
+    fn test_sum_runtime_with_no_tests_should_be_zero() {
+        let selector = ResmokeSelector::builder()
+            .roots(vec!["jstests/core/*.js".to_string()])
+            .build();
+
+        assert_eq!(sum_runtime(&[], &selector, 5.0), 0.0);
+    }
+
+    // clamp_sub_suite_counts tests.
+    #[rstest]
+    #[case(vec![3, 5, 2], 10)]
+    #[case(vec![3, 5, 2], 20)]
+    #[case(vec![], 5)]
+    fn test_clamp_sub_suite_counts_under_budget_should_not_change(
+        #[case] desired_counts: Vec<usize>,
+        #[case] budget: usize,
+    ) {
+        assert_eq!(
+            clamp_sub_suite_counts(&desired_counts, budget).unwrap(),
+            desired_counts
+        );
+    }
+
+    #[rstest]
+    #[case(vec![100, 50, 50], 20, vec![10, 5, 5])]
+    #[case(vec![200, 100], 10, vec![7, 3])]
+    #[case(vec![10, 10, 10], 10, vec![4, 3, 3])]
+    #[case(vec![100, 1, 0], 10, vec![9, 1, 0])]
+    fn test_clamp_sub_suite_counts_over_budget_should_scale_proportionally(
+        #[case] desired_counts: Vec<usize>,
+        #[case] budget: usize,
+        #[case] expected: Vec<usize>,
+    ) {
+        let counts = clamp_sub_suite_counts(&desired_counts, budget).unwrap();
+
+        assert_eq!(counts, expected);
+        assert!(counts.iter().sum::<usize>() <= budget);
+    }
+
+    #[test]
+    fn test_clamp_sub_suite_counts_with_too_small_budget_should_fail() {
+        assert!(clamp_sub_suite_counts(&[5, 5, 5], 2).is_err());
+    }
+
+    // find_fully_excluded_sub_suites tests.
+    fn build_excluding_selector() -> ResmokeSelector {
+        ResmokeSuiteConfig::from_str(
+            "
+            test_kind: js_test
+
+            selector:
+              roots:
+                - jstests/core/*.js
+              exclude_with_any_tags:
+                - requires_sharding
+
+            executor:
+              fixture:
+                class: MyFixture
+            ",
+        )
+        .unwrap()
+        .selector
+    }
+
+    #[test]
+    fn test_find_fully_excluded_sub_suites_should_find_emptied_sub_suites() {
+        let test_lists = vec![
+            vec!["test_0.js".to_string(), "test_1.js".to_string()],
+            vec!["test_2.js".to_string(), "test_3.js".to_string()],
+            vec![],
+        ];
+        let test_tags = hashmap! {
+            "test_0.js".to_string() => HashSet::new(),
+            "test_1.js".to_string() => ["requires_sharding".to_string()].iter().cloned().collect(),
+            "test_2.js".to_string() => ["requires_sharding".to_string()].iter().cloned().collect(),
+            "test_3.js".to_string() => ["requires_sharding".to_string(), "slow".to_string()].iter().cloned().collect(),
         };
-        let expected_result: Vec<String> = (0..sorted_indexes.len())
-            .into_iter()
-            .map(|i| format!("test_{}.js", sorted_indexes[i]))
-            .collect();
-        let result = sort_tests_by_runtime(test_list, &task_stats);
-        assert_eq!(result, expected_result);
+
+        let excluded = find_fully_excluded_sub_suites(
+            "my_suite",
+            &test_lists,
+            &build_excluding_selector(),
+            Some(&test_tags),
+        );
+
+        assert_eq!(excluded, vec![1]);
     }
+
+    #[test]
+    fn test_find_fully_excluded_sub_suites_should_assume_unknown_tests_are_selected() {
+        let test_lists = vec![vec!["test_0.js".to_string(), "test_1.js".to_string()]];
+        let test_tags = hashmap! {
+            "test_0.js".to_string() => ["requires_sharding".to_string()].iter().cloned().collect(),
+        };
+
+        let excluded = find_fully_excluded_sub_suites(
+            "my_suite",
+            &test_lists,
+            &build_excluding_selector(),
+            Some(&test_tags),
+        );
+
+        assert!(excluded.is_empty());
+    }
+
+    #[test]
+    fn test_find_fully_excluded_sub_suites_should_skip_check_without_tags() {
+        let test_lists = vec![vec!["test_0.js".to_string()]];
+
+        let excluded = find_fully_excluded_sub_suites(
+            "my_suite",
+            &test_lists,
+            &build_excluding_selector(),
+            None,
+        );
+
+        assert!(excluded.is_empty());
+    }
+
+    // get_num_sub_suites tests.
+    #[rstest]
+    #[case(12, 5, None, 5)]
+    #[case(12, 5, Some(4), 3)]
+    #[case(13, 5, Some(4), 4)]
+    #[case(12, 2, Some(3), 4)]
+    #[case(12, 5, Some(0), 5)]
+    #[case(0, 5, Some(4), 0)]
+    fn test_get_num_sub_suites(
+        #[case] total_tests: usize,
+        #[case] num_sub_suites: usize,
+        #[case] max_tests_per_suite: Option<usize>,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(
+            get_num_sub_suites(total_tests, num_sub_suites, max_tests_per_suite),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(12, 4, vec![4, 4, 4])]
+    #[case(13, 4, vec![4, 3, 3, 3])]
+    fn test_split_task_fallback_should_respect_max_tests_per_suite(
+        #[case] n_tests: usize,
+        #[case] max_tests_per_suite: usize,
+        #[case] expected_sizes: Vec<usize>,
+    ) {
+        let test_list: Vec<String> = (0..n_tests).map(|i| format!("test_{}.js", i)).collect();
+        let task_history = TaskRuntimeHistory {
+            task_name: "my task".to_string(),
+            test_map: hashmap! {},
+        };
+        let mut gen_resmoke_service = build_mocked_service(test_list, task_history);
+        gen_resmoke_service.subtask_limits.max_tests_per_suite = Some(max_tests_per_suite);
+
+        let params = ResmokeGenParams {
+            num_tasks: Some(10),
+            ..Default::default()
+        };
+
+        let sub_suites = gen_resmoke_service
+            .split_task_fallback(&params, None, None)
+            .unwrap();
+
+        assert_eq!(
+            sub_suites
+                .iter()
+                .map(|s| s.test_list.len())
+                .collect::<Vec<_>>(),
+            expected_sizes
+        );
+    }
+
+    // distribute_tests_by_count tests.
+    #[rstest]
+    #[case(6, 3, vec![2, 2, 2])]
+    #[case(7, 3, vec![3, 2, 2])]
+    #[case(2, 4, vec![1, 1, 0, 0])]
+    #[case(0, 2, vec![0, 0])]
+    fn test_distribute_tests_by_count_should_preserve_order(
+        #[case] n_tests: usize,
+        #[case] n: usize,
+        #[case] expected_sizes: Vec<usize>,
+    ) {
+        let tests: Vec<String> = (0..n_tests).map(|i| format!("test_{}.js", i)).collect();
+
+        let result = distribute_tests_by_count(&tests, n).unwrap();
+
+        assert_eq!(
+            result.iter().map(|t| t.len()).collect::<Vec<_>>(),
+            expected_sizes
+        );
+        assert_eq!(result.concat(), tests);
+    }
+
+    #[test]
+    fn test_distribute_tests_by_count_with_no_bins_should_fail() {
+        let tests = vec!["test_0.js".to_string()];
+
+        assert!(distribute_tests_by_count(&tests, 0).is_err());
+    }
+
+    // distribute_tests_by_count_seeded tests.
+    #[test]
+    fn test_distribute_tests_by_count_seeded_should_be_reproducible() {
+        let tests: Vec<String> = (0..20).map(|i| format!("test_{}.js", i)).collect();
+
+        let result_0 = distribute_tests_by_count_seeded(&tests, 4, 42).unwrap();
+        let result_1 = distribute_tests_by_count_seeded(&tests, 4, 42).unwrap();
+        let result_2 = distribute_tests_by_count_seeded(&tests, 4, 7).unwrap();
+        let mut reversed = tests.clone();
+        reversed.reverse();
+        let result_3 = distribute_tests_by_count_seeded(&reversed, 4, 42).unwrap();
+
+        assert_eq!(result_0, result_1);
+        assert_eq!(result_0, result_3);
+        assert_ne!(result_0, result_2);
+        assert_eq!(
+            result_0.iter().map(|t| t.len()).collect::<Vec<_>>(),
+            vec![5, 5, 5, 5]
+        );
+        let mut all_tests = result_0.concat();
+        all_tests.sort();
+        let mut expected_tests = tests.clone();
+        expected_tests.sort();
+        assert_eq!(all_tests, expected_tests);
+    }
+
     // get_min_index tests.
     #[rstest]
     #[case(vec![100.0, 50.0, 30.0, 25.0, 20.0, 15.0], 5)]
@@ -1,6 +1,6 @@
 //! Service for interacting with the filesystem.
 use anyhow::Result;
-use std::path::Path;
+use std::{collections::HashMap, path::Path, sync::Mutex};
 
 /// A service for working with the file system.
 pub trait FsService: Sync + Send {
@@ -26,6 +26,17 @@ pub trait FsService: Sync + Send {
     ///
     /// Returns the unit value after contents have been written successfully.
     fn write_file(&self, path: &Path, contents: &str) -> Result<()>;
+
+    /// Create the given directory and any missing parent directories.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Directory to create.
+    ///
+    /// # Returns
+    ///
+    /// Returns the unit value after the directory has been created.
+    fn create_dir_all(&self, path: &Path) -> Result<()>;
 }
 
 pub struct FsServiceImpl {}
@@ -65,4 +76,119 @@ impl FsService for FsServiceImpl {
     fn write_file(&self, path: &Path, contents: &str) -> Result<()> {
         Ok(std::fs::write(path, contents)?)
     }
+
+    /// Create the given directory and any missing parent directories.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Directory to create.
+    ///
+    /// # Returns
+    ///
+    /// Returns the unit value after the directory has been created.
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        Ok(std::fs::create_dir_all(path)?)
+    }
+}
+
+/// An FsService that keeps written files in memory instead of writing them to disk.
+///
+/// Useful to capture generated files in tests without touching the filesystem.
+#[derive(Debug, Default)]
+pub struct InMemoryFsService {
+    /// Map of written paths to their contents.
+    files: Mutex<HashMap<String, String>>,
+}
+
+impl InMemoryFsService {
+    /// Create a new instance of InMemoryFsService with no files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the contents written to the given path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path the file was written to.
+    ///
+    /// # Returns
+    ///
+    /// Contents of the file, or `None` if nothing was written to the path.
+    pub fn contents(&self, path: &str) -> Option<String> {
+        self.files.lock().unwrap().get(path).cloned()
+    }
+
+    /// Get all the files written so far.
+    ///
+    /// # Returns
+    ///
+    /// Map of written paths to their contents.
+    pub fn files(&self) -> HashMap<String, String> {
+        self.files.lock().unwrap().clone()
+    }
+}
+
+impl FsService for InMemoryFsService {
+    /// Determine whether a file has been written to the given path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to check.
+    ///
+    /// # Returns
+    ///
+    /// true if a file has been written to the given path.
+    fn file_exists(&self, path: &str) -> bool {
+        self.files.lock().unwrap().contains_key(path)
+    }
+
+    /// Store the given contents in memory under the given location.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to store the contents under.
+    /// * `contents` - Contents of the file.
+    ///
+    /// # Returns
+    ///
+    /// Returns the unit value after contents have been stored.
+    fn write_file(&self, path: &Path, contents: &str) -> Result<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(path.to_string_lossy().to_string(), contents.to_string());
+        Ok(())
+    }
+
+    /// Directories are not tracked, so there is nothing to create.
+    fn create_dir_all(&self, _path: &Path) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // InMemoryFsService tests
+    #[test]
+    fn test_in_memory_fs_service_should_store_written_files() {
+        let fs_service = InMemoryFsService::new();
+
+        fs_service
+            .write_file(Path::new("target/suite_0.yml"), "contents 0")
+            .unwrap();
+        fs_service
+            .write_file(Path::new("target/suite_0.yml"), "contents 1")
+            .unwrap();
+
+        assert!(fs_service.file_exists("target/suite_0.yml"));
+        assert!(!fs_service.file_exists("target/suite_1.yml"));
+        assert_eq!(
+            fs_service.contents("target/suite_0.yml"),
+            Some("contents 1".to_string())
+        );
+        assert_eq!(fs_service.files().len(), 1);
+    }
 }
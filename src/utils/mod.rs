@@ -1,2 +1,3 @@
 pub mod fs_service;
 pub mod task_name;
+pub mod test_path;
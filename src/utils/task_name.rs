@@ -3,6 +3,53 @@
 use crate::evergreen_names::ENTERPRISE_MODULE;
 const GEN_SUFFIX: &str = "_gen";
 
+/// Scheme used to name the configuration files of generated sub-suites.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SuiteFileNaming {
+    /// Name files after the generated sub-task, e.g. `suite_3-linux`.
+    #[default]
+    SameAsTask,
+    /// Zero-pad the sub-suite index to a fixed width so files sort correctly, e.g. `suite_003`
+    /// with a width of 3. Indexes wider than the width are not truncated.
+    ZeroPadded(usize),
+}
+
+impl SuiteFileNaming {
+    /// Generate the name of the configuration file for a generated sub-suite.
+    ///
+    /// # Arguments
+    ///
+    /// * `display_name` - Name of parent task being generated.
+    /// * `sub_task_index` - Index of sub-task being named.
+    /// * `total_tasks` - Total number of sub-tasks generated for this parent task.
+    /// * `is_enterprise` - Whether the task is for an enterprise build variant.
+    /// * `platform` - Platform that task will run on.
+    ///
+    /// # Returns
+    ///
+    /// Name of the configuration file, without its extension.
+    pub fn suite_file_name(
+        &self,
+        display_name: &str,
+        sub_task_index: usize,
+        total_tasks: usize,
+        is_enterprise: bool,
+        platform: Option<&str>,
+    ) -> String {
+        let alignment = match self {
+            SuiteFileNaming::SameAsTask => index_alignment(total_tasks),
+            SuiteFileNaming::ZeroPadded(width) => *width,
+        };
+        format_generated_name(
+            display_name,
+            sub_task_index,
+            alignment,
+            is_enterprise,
+            platform,
+        )
+    }
+}
+
 /// Generate a name for a generated task.
 ///
 /// # Arguments
@@ -18,6 +65,36 @@ pub fn name_generated_task(
     total_tasks: usize,
     is_enterprise: bool,
     platform: Option<&str>,
+) -> String {
+    format_generated_name(
+        display_name,
+        sub_task_index,
+        index_alignment(total_tasks),
+        is_enterprise,
+        platform,
+    )
+}
+
+/// Number of digits sub-task indexes are padded to so they align for the given number of tasks.
+fn index_alignment(total_tasks: usize) -> usize {
+    (total_tasks as f64).log10().ceil() as usize
+}
+
+/// Build the name of a generated sub-task or its suite file.
+///
+/// # Arguments
+///
+/// * `display_name` - Name of parent task being generated.
+/// * `sub_task_index` - Index of sub-task being named.
+/// * `alignment` - Number of digits to zero-pad the index to.
+/// * `is_enterprise` - Whether the task is for an enterprise build variant.
+/// * `platform` - Platform that task will run on.
+fn format_generated_name(
+    display_name: &str,
+    sub_task_index: usize,
+    alignment: usize,
+    is_enterprise: bool,
+    platform: Option<&str>,
 ) -> String {
     let mut suffix = if is_enterprise {
         format!("-{}", ENTERPRISE_MODULE)
@@ -29,7 +106,6 @@ pub fn name_generated_task(
         suffix = format!("-{}{}", platform, suffix)
     }
 
-    let alignment = (total_tasks as f64).log10().ceil() as usize;
     format!(
         "{}_{:0fill$}{}",
         display_name,
@@ -84,6 +160,49 @@ mod tests {
         assert_eq!(task_name, expected);
     }
 
+    #[rstest]
+    #[case("task", 0, 10, false, None, "task_0")]
+    #[case("task", 42, 1001, false, None, "task_0042")]
+    #[case("task", 42, 1001, true, Some("linux"), "task_0042-linux-enterprise")]
+    fn test_suite_file_name_should_match_task_name_by_default(
+        #[case] name: &str,
+        #[case] index: usize,
+        #[case] total: usize,
+        #[case] is_enterprise: bool,
+        #[case] platform: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let naming = SuiteFileNaming::default();
+
+        let file_name = naming.suite_file_name(name, index, total, is_enterprise, platform);
+
+        assert_eq!(file_name, expected);
+        assert_eq!(
+            file_name,
+            name_generated_task(name, index, total, is_enterprise, platform)
+        );
+    }
+
+    #[rstest]
+    #[case(3, 0, 10, false, None, "task_000")]
+    #[case(3, 7, 2, false, Some("linux"), "task_007-linux")]
+    #[case(3, 42, 1001, true, None, "task_042-enterprise")]
+    #[case(2, 123, 200, false, None, "task_123")]
+    fn test_suite_file_name_with_zero_padding(
+        #[case] width: usize,
+        #[case] index: usize,
+        #[case] total: usize,
+        #[case] is_enterprise: bool,
+        #[case] platform: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let naming = SuiteFileNaming::ZeroPadded(width);
+
+        let file_name = naming.suite_file_name("task", index, total, is_enterprise, platform);
+
+        assert_eq!(file_name, expected);
+    }
+
     #[rstest]
     #[case("task_name", "task_name")]
     #[case("task_name_gen", "task_name")]
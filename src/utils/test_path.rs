@@ -0,0 +1,39 @@
+//! Utilities for working with test paths.
+
+/// Normalize a test path so equivalent spellings of the same path match.
+///
+/// Converts windows path separators (\) to unix style (/) and removes any leading `./`.
+///
+/// # Arguments
+///
+/// * `test_file` - Test path to normalize.
+///
+/// # Returns
+///
+/// Normalized test path.
+pub(crate) fn normalize_test_path(test_file: &str) -> String {
+    let normalized = test_file.replace('\\', "/");
+    let mut path = normalized.as_str();
+    while let Some(stripped) = path.strip_prefix("./") {
+        path = stripped;
+    }
+    path.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    // normalize_test_path tests.
+    #[rstest]
+    #[case("jstests/core/a.js", "jstests/core/a.js")]
+    #[case("jstests\\core\\a.js", "jstests/core/a.js")]
+    #[case("./jstests/core/a.js", "jstests/core/a.js")]
+    #[case(".\\./jstests\\core\\a.js", "jstests/core/a.js")]
+    #[case("../jstests/core/a.js", "../jstests/core/a.js")]
+    fn test_normalize_test_path(#[case] test_file: &str, #[case] expected: &str) {
+        assert_eq!(normalize_test_path(test_file), expected);
+    }
+}
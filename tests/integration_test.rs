@@ -34,6 +34,47 @@ fn test_end2end_execution() {
     assert_eq!(688, files.into_iter().collect::<Vec<_>>().len());
 }
 
+#[test]
+fn test_end2end_execution_should_fail_on_parse_error() {
+    let mut cmd = Command::cargo_bin("mongo-task-generator").unwrap();
+    let tmp_dir = TempDir::new("generated_resmoke_config").unwrap();
+    let suite_file = tmp_dir.path().join("broken_suite.yml");
+    std::fs::write(&suite_file, "test_kind: js_test\nselector: [\n").unwrap();
+    let target_dir = tmp_dir.path().join("generated");
+
+    let output = cmd
+        .env("MOCK_RESMOKE_SUITE_FILE", suite_file.to_str().unwrap())
+        .args(&[
+            "--target-directory",
+            target_dir.to_str().unwrap(),
+            "--expansion-file",
+            "tests/data/sample_expansions.yml",
+            "--evg-project-file",
+            "tests/data/evergreen.yml",
+            "--evg-auth-file",
+            "tests/data/sample_evergreen_auth.yml",
+            "--resmoke-command",
+            "python3 tests/mocks/resmoke.py",
+            "--use-task-split-fallback",
+            "--generate-sub-tasks-config",
+            "tests/data/sample_generate_subtasks_config.yml",
+            "--fail-on-parse-error",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to parse resmoke suites"));
+    assert!(!target_dir.join("evergreen_config.json").exists());
+    let written_suites = std::fs::read_dir(&target_dir)
+        .unwrap()
+        .filter(|entry| {
+            entry.as_ref().unwrap().path().extension() == Some(std::ffi::OsStr::new("yml"))
+        })
+        .count();
+    assert_eq!(written_suites, 0);
+}
+
 #[test]
 fn test_end2end_burn_in_execution() {
     let mut cmd = Command::cargo_bin("mongo-task-generator").unwrap();
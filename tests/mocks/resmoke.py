@@ -17,6 +17,12 @@ requires_fcv_tag: requires_fcv_51,requires_fcv_52,requires_fcv_53,requires_fcv_6
 
 
 def suiteconfig():
+    suite_file = os.environ.get("MOCK_RESMOKE_SUITE_FILE")
+    if suite_file:
+        with open(suite_file) as file:
+            print(file.read())
+        return
+
     print("""
 description: Suite description
 